@@ -17,6 +17,9 @@
 pub(crate) mod actions_state;
 use std::collections::HashMap;
 
+use ccp_shared::alignment_plan::AlignmentPlan;
+use ccp_shared::alignment_plan::PlannedAction;
+use ccp_shared::alignment_plan::PlannedPreAction;
 use ccp_shared::types::*;
 
 use super::roadmap_builder::RoadmapBuilder;
@@ -49,6 +52,16 @@ impl CCProverAlignmentRoadmap {
             .prepare_removal_actions()
             .build()
     }
+
+    /// Describes the roadmap without applying it.
+    pub(crate) fn to_plan(&self) -> AlignmentPlan {
+        let actions = self
+            .actions
+            .iter()
+            .map(CUProverAction::to_planned)
+            .collect();
+        AlignmentPlan::new(self.epoch, self.pre_action.to_planned(), actions)
+    }
 }
 
 impl PartialEq for CCProverAlignmentRoadmap {
@@ -70,8 +83,8 @@ impl PartialEq for CCProverAlignmentRoadmap {
 #[derive(Debug, PartialEq, Eq, Hash)]
 pub(crate) enum CUProverPreAction {
     NoAction,
-    /// Signals CCP to remove all collected proofs, this action is a result of epoch switching
-    /// and CCP will clean up old proofs to save space.
+    /// Signals CCP to move all collected proofs of the previous epoch to the archive,
+    /// this action is a result of epoch switching.
     CleanupProofCache,
 }
 
@@ -120,6 +133,27 @@ impl CUProverAction {
             new_cu_id,
         ))
     }
+
+    pub(crate) fn to_planned(&self) -> PlannedAction {
+        match self {
+            Self::CreateCUProver(state) => PlannedAction::CreateCUProver {
+                new_core_id: state.new_core_id,
+                new_cu_id: state.new_cu_id,
+            },
+            Self::RemoveCUProver(state) => PlannedAction::RemoveCUProver {
+                current_core_id: state.current_core_id,
+            },
+            Self::NewCCJob(state) => PlannedAction::NewCCJob {
+                current_core_id: state.current_core_id,
+                new_cu_id: state.new_cu_id,
+            },
+            Self::NewCCJobWithRepining(state) => PlannedAction::NewCCJobWithRepining {
+                current_core_id: state.current_core_id,
+                new_core_id: state.new_core_id,
+                new_cu_id: state.new_cu_id,
+            },
+        }
+    }
 }
 
 impl CUProverPreAction {
@@ -130,4 +164,11 @@ impl CUProverPreAction {
     pub(crate) fn no_action() -> Self {
         Self::NoAction
     }
+
+    pub(crate) fn to_planned(&self) -> PlannedPreAction {
+        match self {
+            Self::NoAction => PlannedPreAction::NoAction,
+            Self::CleanupProofCache => PlannedPreAction::CleanupProofCache,
+        }
+    }
 }
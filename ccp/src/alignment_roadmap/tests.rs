@@ -19,6 +19,8 @@ use std::collections::HashMap;
 use rand::rngs::SmallRng;
 use rand::Rng;
 
+use ccp_shared::alignment_plan::PlannedAction;
+use ccp_shared::alignment_plan::PlannedPreAction;
 use ccp_shared::types::CUAllocation;
 use ccp_shared::types::CUID;
 use ccp_shared::types::PhysicalCoreId;
@@ -531,6 +533,38 @@ fn consecutive_repinnings() {
     }
 }
 
+#[test]
+fn plan_describes_roadmap() {
+    let epoch = test::generate_epoch_params(1, 1);
+    let cu_id_1 = test::generate_cu_id(1);
+    let cu_id_2 = test::generate_cu_id(2);
+
+    let mut new_allocation = CUAllocation::new();
+    new_allocation.insert(1.into(), cu_id_1);
+    new_allocation.insert(3.into(), cu_id_2);
+
+    let mut current_allocation = HashMap::new();
+    current_allocation.insert(PhysicalCoreId::from(1), DumpProvider::running(cu_id_1));
+    current_allocation.insert(PhysicalCoreId::from(2), DumpProvider::running(cu_id_2));
+    let current_status = CCStatus::Running { epoch };
+
+    let roadmap =
+        CCProverAlignmentRoadmap::make(new_allocation, epoch, &current_allocation, current_status);
+    let plan = roadmap.to_plan();
+
+    assert_eq!(plan.epoch, epoch);
+    assert_eq!(plan.pre_action, PlannedPreAction::NoAction);
+    assert_eq!(
+        plan.actions,
+        vec![PlannedAction::NewCCJobWithRepining {
+            current_core_id: 2.into(),
+            new_core_id: 3.into(),
+            new_cu_id: cu_id_2,
+        }]
+    );
+    assert_eq!(plan.datasets_to_initialize, 1);
+}
+
 #[derive(Debug)]
 struct MockProver {
     #[allow(unused)]
@@ -0,0 +1,221 @@
+/*
+ * Copyright 2024 Fluence Labs Limited
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+use ccp_shared::commitment::CUCommitmentError;
+use ccp_shared::commitment::CUCommitmentPhase;
+use ccp_shared::commitment::CUCommitmentProgress;
+use ccp_shared::commitment::CommitmentGeneration;
+use ccp_shared::commitment::CommitmentProgress;
+use ccp_shared::commitment::InFlightCommitment;
+use ccp_shared::types::PhysicalCoreId;
+use ccp_shared::types::CUID;
+use parking_lot::Mutex;
+
+/// Numbers accepted commitment changes and tracks how they are applied,
+/// it's readable while the prover is locked by an alignment.
+#[derive(Clone, Debug, Default)]
+pub struct CommitmentTracker(Arc<Mutex<TrackerState>>);
+
+#[derive(Debug, Default)]
+struct TrackerState {
+    requested: CommitmentGeneration,
+    applied: CommitmentGeneration,
+    in_flight: Option<InFlight>,
+    last_error: Option<String>,
+    cu_errors: Vec<CUCommitmentError>,
+}
+
+#[derive(Debug)]
+struct InFlight {
+    generation: CommitmentGeneration,
+    cu_provers: BTreeMap<PhysicalCoreId, (Option<CUID>, CUCommitmentPhase)>,
+}
+
+impl CommitmentTracker {
+    /// Assigns a generation to an accepted commitment change.
+    pub fn request(&self) -> CommitmentGeneration {
+        let mut state = self.0.lock();
+        state.requested += 1;
+        state.requested
+    }
+
+    /// Takes back the last requested generation, if the change wasn't scheduled after all.
+    pub fn cancel(&self, generation: CommitmentGeneration) {
+        let mut state = self.0.lock();
+        if state.requested == generation {
+            state.requested -= 1;
+        }
+    }
+
+    pub fn is_pending(&self) -> bool {
+        let state = self.0.lock();
+        state.requested > state.applied
+    }
+
+    pub fn progress(&self) -> CommitmentProgress {
+        let state = self.0.lock();
+        let in_flight = state.in_flight.as_ref().map(|in_flight| {
+            let cu_provers = in_flight
+                .cu_provers
+                .iter()
+                .map(|(&core_id, (cu_id, phase))| CUCommitmentProgress {
+                    core_id,
+                    cu_id: *cu_id,
+                    phase: phase.clone(),
+                })
+                .collect();
+
+            InFlightCommitment {
+                generation: in_flight.generation,
+                cu_provers,
+            }
+        });
+
+        CommitmentProgress {
+            requested_generation: state.requested,
+            applied_generation: state.applied,
+            in_flight,
+            last_error: state.last_error.clone(),
+            cu_errors: state.cu_errors.clone(),
+        }
+    }
+
+    pub(crate) fn start(&self, generation: CommitmentGeneration) {
+        let mut state = self.0.lock();
+        state.in_flight = Some(InFlight {
+            generation,
+            cu_provers: BTreeMap::new(),
+        });
+    }
+
+    /// Registers a CU prover affected by the in-flight change.
+    pub(crate) fn add_cu(&self, core_id: PhysicalCoreId, cu_id: Option<CUID>) {
+        let phase = match cu_id {
+            Some(_) => CUCommitmentPhase::Pending,
+            None => CUCommitmentPhase::Stopping,
+        };
+
+        let mut state = self.0.lock();
+        if let Some(in_flight) = state.in_flight.as_mut() {
+            in_flight.cu_provers.insert(core_id, (cu_id, phase));
+        }
+    }
+
+    /// Updates a phase of a CU prover registered in the in-flight change, others are ignored.
+    pub(crate) fn set_phase(&self, core_id: PhysicalCoreId, phase: CUCommitmentPhase) {
+        let mut state = self.0.lock();
+        let cu_prover = state
+            .in_flight
+            .as_mut()
+            .and_then(|in_flight| in_flight.cu_provers.get_mut(&core_id));
+        if let Some((_, current_phase)) = cu_prover {
+            *current_phase = phase;
+        }
+    }
+
+    pub(crate) fn finish(&self, error: Option<String>) {
+        let mut state = self.0.lock();
+        let Some(in_flight) = state.in_flight.take() else {
+            return;
+        };
+
+        state.applied = state.applied.max(in_flight.generation);
+        state.last_error = error;
+        state.cu_errors = in_flight
+            .cu_provers
+            .into_iter()
+            .filter_map(|(core_id, (cu_id, phase))| match phase {
+                CUCommitmentPhase::Failed { error } => Some(CUCommitmentError {
+                    core_id,
+                    cu_id,
+                    error,
+                }),
+                _ => None,
+            })
+            .collect();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn generation_is_pending_until_applied() {
+        let tracker = CommitmentTracker::default();
+        assert!(!tracker.is_pending());
+
+        let generation = tracker.request();
+        assert_eq!(generation, 1);
+        assert!(tracker.is_pending());
+
+        tracker.start(generation);
+        tracker.finish(None);
+        assert!(!tracker.is_pending());
+
+        let progress = tracker.progress();
+        assert_eq!(progress.requested_generation, 1);
+        assert_eq!(progress.applied_generation, 1);
+        assert!(progress.in_flight.is_none());
+    }
+
+    #[test]
+    fn cancelled_generation_is_reused() {
+        let tracker = CommitmentTracker::default();
+
+        let generation = tracker.request();
+        tracker.cancel(generation);
+        assert!(!tracker.is_pending());
+        assert_eq!(tracker.request(), generation);
+    }
+
+    #[test]
+    fn failed_cu_provers_are_reported_after_finish() {
+        let tracker = CommitmentTracker::default();
+        let cu_id = CUID::new([1; 32]);
+
+        let generation = tracker.request();
+        tracker.start(generation);
+        tracker.add_cu(1.into(), Some(cu_id));
+        tracker.add_cu(2.into(), Some(cu_id));
+        tracker.set_phase(1.into(), CUCommitmentPhase::Running);
+        tracker.set_phase(
+            2.into(),
+            CUCommitmentPhase::Failed {
+                error: "failed".to_string(),
+            },
+        );
+
+        let in_flight = tracker.progress().in_flight.unwrap();
+        assert_eq!(in_flight.generation, generation);
+        assert_eq!(in_flight.cu_provers.len(), 2);
+
+        tracker.finish(Some("alignment failed".to_string()));
+        let progress = tracker.progress();
+        assert_eq!(progress.last_error.as_deref(), Some("alignment failed"));
+        assert_eq!(
+            progress.cu_errors,
+            vec![CUCommitmentError {
+                core_id: 2.into(),
+                cu_id: Some(cu_id),
+                error: "failed".to_string(),
+            }]
+        );
+    }
+}
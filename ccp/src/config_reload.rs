@@ -0,0 +1,287 @@
+/*
+ * Copyright 2024 Fluence Labs Limited
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::sync::Arc;
+
+use ccp_config::CCPConfig;
+use tokio::sync::Mutex;
+
+use crate::cpuids_handle::CpuIdsHandle;
+use crate::hashrate::prometheus::PrometheusEndpointSlot;
+use crate::runtime_settings::RuntimeSettings;
+
+/// Applies a reloaded config to the running prover, it works independently of the prover,
+/// so a reload isn't blocked by an alignment in progress.
+///
+/// Only `logs.report-hashrate`, `workers.hashes-per-round` (starting from the next job),
+/// the prometheus endpoint and `tokio.utility-thread-ids` are applied, other changes
+/// need a restart. The log level is left to the caller, which owns the tracing subscriber.
+#[derive(Clone)]
+pub struct ConfigReloader(Arc<ConfigReloaderInner>);
+
+struct ConfigReloaderInner {
+    /// The config in effect: applied changes are put here, ones needing a restart are not,
+    /// so they're reported on each reload until the restart.
+    current: Mutex<CCPConfig>,
+    runtime_settings: RuntimeSettings,
+    prometheus_endpoint: PrometheusEndpointSlot,
+    utility_core_ids_handle: CpuIdsHandle,
+}
+
+/// Keys of changed config values.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ReloadReport {
+    pub applied: Vec<&'static str>,
+    pub needs_restart: Vec<&'static str>,
+    /// Changes which failed to apply along with errors, the previous values stay in effect.
+    pub failed: Vec<(&'static str, String)>,
+}
+
+impl ConfigReloader {
+    pub(crate) fn new(
+        config: CCPConfig,
+        runtime_settings: RuntimeSettings,
+        prometheus_endpoint: PrometheusEndpointSlot,
+        utility_core_ids_handle: CpuIdsHandle,
+    ) -> Self {
+        Self(Arc::new(ConfigReloaderInner {
+            current: Mutex::new(config),
+            runtime_settings,
+            prometheus_endpoint,
+            utility_core_ids_handle,
+        }))
+    }
+
+    pub async fn reload(&self, new_config: &CCPConfig) -> ReloadReport {
+        let inner = &self.0;
+        let mut current = inner.current.lock().await;
+        let mut report = ReloadReport::default();
+
+        if current.logs.report_hashrate != new_config.logs.report_hashrate {
+            inner
+                .runtime_settings
+                .set_report_hashrate(new_config.logs.report_hashrate);
+            current.logs.report_hashrate = new_config.logs.report_hashrate;
+            report.applied.push("logs.report-hashrate");
+        }
+
+        if current.workers.hashes_per_round != new_config.workers.hashes_per_round {
+            inner
+                .runtime_settings
+                .set_hashes_per_round(new_config.workers.hashes_per_round);
+            current.workers.hashes_per_round = new_config.workers.hashes_per_round;
+            report.applied.push("workers.hashes-per-round");
+        }
+
+        if current.prometheus_endpoint != new_config.prometheus_endpoint {
+            let address = new_config
+                .prometheus_endpoint
+                .as_ref()
+                .map(|endpoint| (endpoint.host.clone(), endpoint.port));
+            match inner.prometheus_endpoint.rebind(address).await {
+                Ok(()) => {
+                    current.prometheus_endpoint = new_config.prometheus_endpoint.clone();
+                    report.applied.push("prometheus-endpoint");
+                }
+                Err(e) => report.failed.push(("prometheus-endpoint", e.to_string())),
+            }
+        }
+
+        if current.tokio.utility_cores_ids != new_config.tokio.utility_cores_ids {
+            inner
+                .utility_core_ids_handle
+                .set_cores(new_config.tokio.utility_cores_ids.clone());
+            current.tokio.utility_cores_ids = new_config.tokio.utility_cores_ids.clone();
+            report.applied.push("tokio.utility-thread-ids");
+        }
+
+        report.needs_restart = restart_required_changes(&current, new_config);
+        report
+    }
+}
+
+fn restart_required_changes(current: &CCPConfig, new_config: &CCPConfig) -> Vec<&'static str> {
+    let changes = [
+        (
+            "rpc-endpoint",
+            current.rpc_endpoint != new_config.rpc_endpoint,
+        ),
+        (
+            "optimizations",
+            current.optimizations != new_config.optimizations,
+        ),
+        ("state.path", current.state_dir != new_config.state_dir),
+        (
+            "workers.async-to-sync-queue-size",
+            current.workers.async_to_sync_queue_size != new_config.workers.async_to_sync_queue_size,
+        ),
+        (
+            "workers.sync-to-async-queue-size",
+            current.workers.sync_to_async_queue_size != new_config.workers.sync_to_async_queue_size,
+        ),
+        (
+            "tokio.worker-threads",
+            current.tokio.worker_threads != new_config.tokio.worker_threads,
+        ),
+        (
+            "tokio.max-blocking-threads",
+            current.tokio.max_blocking_threads != new_config.tokio.max_blocking_threads,
+        ),
+        ("proofs", current.proofs != new_config.proofs),
+        ("disk-guard", current.disk_guard != new_config.disk_guard),
+    ];
+
+    changes
+        .into_iter()
+        .filter_map(|(key, changed)| changed.then_some(key))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex as StdMutex;
+
+    use ccp_config::Proofs;
+    use prometheus_client::metrics::counter::Counter;
+
+    use super::*;
+    use crate::disk_guard::DiskSpaceGuard;
+    use crate::hashing_gate::HashingGate;
+    use crate::hashrate::prometheus::PrometheusMetrics;
+    use crate::hashrate::HashrateCollector;
+    use crate::proof_circuit_breaker::ProofCircuitBreaker;
+    use crate::proof_sink::ProofSinkMetrics;
+
+    fn config() -> CCPConfig {
+        CCPConfig {
+            rpc_endpoint: <_>::default(),
+            prometheus_endpoint: None,
+            optimizations: <_>::default(),
+            logs: <_>::default(),
+            state_dir: "state".into(),
+            workers: <_>::default(),
+            tokio: <_>::default(),
+            proofs: <_>::default(),
+            disk_guard: <_>::default(),
+        }
+    }
+
+    fn reloader(config: &CCPConfig) -> (ConfigReloader, RuntimeSettings, CpuIdsHandle) {
+        let metrics = PrometheusMetrics {
+            hashrate_collector: Arc::new(StdMutex::new(HashrateCollector::new())),
+            quarantined_proofs: Counter::default(),
+            proof_circuit_breaker: ProofCircuitBreaker::new(&Proofs::default()),
+            disk_space_guard: DiskSpaceGuard::new(
+                config.state_dir.clone(),
+                config.disk_guard,
+                HashingGate::default(),
+            ),
+            proof_sink_metrics: ProofSinkMetrics::default(),
+        };
+        let runtime_settings =
+            RuntimeSettings::new(config.logs.report_hashrate, config.workers.hashes_per_round);
+        let utility_core_ids_handle = CpuIdsHandle::new(config.tokio.utility_cores_ids.clone());
+        let reloader = ConfigReloader::new(
+            config.clone(),
+            runtime_settings.clone(),
+            PrometheusEndpointSlot::new(None, metrics),
+            utility_core_ids_handle.clone(),
+        );
+
+        (reloader, runtime_settings, utility_core_ids_handle)
+    }
+
+    #[tokio::test]
+    async fn safe_changes_are_applied() {
+        let config = config();
+        let (reloader, runtime_settings, utility_core_ids_handle) = reloader(&config);
+
+        let mut new_config = config.clone();
+        new_config.logs.report_hashrate = !config.logs.report_hashrate;
+        new_config.workers.hashes_per_round = 42;
+        new_config.tokio.utility_cores_ids = vec![1.into()];
+
+        let report = reloader.reload(&new_config).await;
+        assert_eq!(
+            report.applied,
+            vec![
+                "logs.report-hashrate",
+                "workers.hashes-per-round",
+                "tokio.utility-thread-ids"
+            ]
+        );
+        assert!(report.needs_restart.is_empty());
+        assert_eq!(
+            runtime_settings.report_hashrate(),
+            new_config.logs.report_hashrate
+        );
+        assert_eq!(runtime_settings.hashes_per_round(), 42);
+        assert_eq!(utility_core_ids_handle.get_cores(), vec![1.into()]);
+
+        let report = reloader.reload(&new_config).await;
+        assert_eq!(report, ReloadReport::default());
+    }
+
+    #[tokio::test]
+    async fn restart_required_changes_are_kept_reported() {
+        let config = config();
+        let (reloader, _, _) = reloader(&config);
+
+        let mut new_config = config.clone();
+        new_config.state_dir = "another_state".into();
+        new_config.tokio.worker_threads = Some(2);
+
+        for _ in 0..2 {
+            let report = reloader.reload(&new_config).await;
+            assert!(report.applied.is_empty());
+            assert_eq!(
+                report.needs_restart,
+                vec!["state.path", "tokio.worker-threads"]
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn prometheus_endpoint_is_kept_if_bind_fails() {
+        let config = config();
+        let (reloader, _, _) = reloader(&config);
+        let taken = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let taken_port = taken.local_addr().unwrap().port();
+
+        let mut new_config = config.clone();
+        new_config.prometheus_endpoint = Some(ccp_config::PrometheusEndpoint {
+            host: "127.0.0.1".to_string(),
+            port: taken_port,
+        });
+        // the change is retried on each reload until it's applied
+        for _ in 0..2 {
+            let report = reloader.reload(&new_config).await;
+            assert!(report.applied.is_empty());
+            let failed = report
+                .failed
+                .iter()
+                .map(|(key, _)| *key)
+                .collect::<Vec<_>>();
+            assert_eq!(failed, vec!["prometheus-endpoint"]);
+        }
+
+        std::mem::drop(taken);
+        let report = reloader.reload(&new_config).await;
+        assert_eq!(report.applied, vec!["prometheus-endpoint"]);
+        assert!(report.failed.is_empty());
+        assert!(std::net::TcpListener::bind(("127.0.0.1", taken_port)).is_err());
+    }
+}
@@ -19,6 +19,10 @@ use ccp_config::RandomXFlags;
 use ccp_config::ThreadsPerCoreAllocationPolicy;
 use ccp_config::Workers;
 
+use crate::commitment_tracker::CommitmentTracker;
+use crate::hashing_gate::HashingGate;
+use crate::runtime_settings::RuntimeSettings;
+
 #[derive(Clone, Debug)]
 pub struct CUProverConfig {
     pub randomx_flags: RandomXFlags,
@@ -26,20 +30,36 @@ pub struct CUProverConfig {
     /// aims to utilize benefits of hyper-threading.
     pub threads_per_core_policy: ThreadsPerCoreAllocationPolicy,
 
-    pub hashes_per_round: usize,
+    /// Keeps hashes per round, which could be changed for next jobs.
+    pub runtime_settings: RuntimeSettings,
     pub async_to_sync_queue_size: usize,
     pub sync_to_async_queue_size: usize,
+
+    /// Allows to suspend hashing without dropping caches and datasets.
+    pub hashing_gate: HashingGate,
+
+    /// CU provers report phases of applying a commitment change to it.
+    pub commitment_tracker: CommitmentTracker,
 }
 
 impl CUProverConfig {
-    pub fn new(ccp_optimizations: Optimizations, workers: Workers) -> Self {
+    pub fn new(
+        ccp_optimizations: Optimizations,
+        workers: Workers,
+        runtime_settings: RuntimeSettings,
+        hashing_gate: HashingGate,
+        commitment_tracker: CommitmentTracker,
+    ) -> Self {
         Self {
             randomx_flags: ccp_optimizations.randomx_flags,
             threads_per_core_policy: ccp_optimizations.threads_per_core_policy,
 
-            hashes_per_round: workers.hashes_per_round,
+            runtime_settings,
             async_to_sync_queue_size: workers.async_to_sync_queue_size,
             sync_to_async_queue_size: workers.sync_to_async_queue_size,
+
+            hashing_gate,
+            commitment_tracker,
         }
     }
 }
@@ -14,11 +14,17 @@
  * limitations under the License.
  */
 
+use std::sync::atomic::AtomicU64;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+
 use ccp_msr::MSRModeEnforcer;
 use ccp_randomx::cache::CacheHandle;
 use ccp_randomx::dataset::DatasetHandle;
 use ccp_randomx::Dataset;
 use ccp_randomx::RandomXFlags;
+use ccp_shared::commitment::CUCommitmentPhase;
+use ccp_shared::status::CUProverStatus;
 use ccp_shared::types::*;
 use ccp_utils::run_utils::run_unordered;
 use cpu_utils::CPUTopology;
@@ -31,6 +37,7 @@ use super::proving_thread_utils::ThreadAllocator;
 use super::status::CUStatus;
 use super::status::ToCUStatus;
 use super::CUResult;
+use crate::commitment_tracker::CommitmentTracker;
 use crate::utility_thread::message::ToUtilityInlet;
 
 /// Intended to prove that a specific physical core was assigned to the Fluence network
@@ -43,6 +50,7 @@ pub struct CUProver {
     cpu_topology: CPUTopology,
     dataset: Dataset,
     status: CUStatus,
+    commitment_tracker: CommitmentTracker,
 }
 
 impl CUProver {
@@ -70,6 +78,7 @@ impl CUProver {
             cpu_topology: topology,
             dataset,
             status: CUStatus::Idle,
+            commitment_tracker: config.commitment_tracker,
         };
         Ok(prover)
     }
@@ -80,16 +89,22 @@ impl CUProver {
 
         self.status = CUStatus::Running { cu_id };
 
+        self.set_commitment_phase(CUCommitmentPhase::CreatingCache);
         let thread = &mut self.threads.head;
         let randomx_flags = self.randomx_flags;
         let cache = thread.create_cache(epoch, cu_id, randomx_flags).await?;
 
+        self.set_commitment_phase(CUCommitmentPhase::InitializingDataset { percent: 0 });
         let dataset_handle = self.dataset.handle();
         let cache_handle = cache.handle();
         self.initialize_dataset(epoch, cache_handle, dataset_handle.clone())
             .await?;
 
-        self.run_proving_jobs(epoch, dataset_handle, cu_id).await
+        self.set_commitment_phase(CUCommitmentPhase::StartingJobs);
+        self.run_proving_jobs(epoch, dataset_handle, cu_id).await?;
+        self.set_commitment_phase(CUCommitmentPhase::Running);
+
+        Ok(())
     }
 
     #[allow(clippy::needless_lifetimes)]
@@ -156,6 +171,11 @@ impl CUProver {
         self.pinned_core_id
     }
 
+    fn set_commitment_phase(&self, phase: CUCommitmentPhase) {
+        self.commitment_tracker
+            .set_phase(self.pinned_core_id, phase);
+    }
+
     #[allow(clippy::needless_lifetimes)]
     async fn initialize_dataset<'threads>(
         &'threads mut self,
@@ -167,6 +187,9 @@ impl CUProver {
 
         let threads_number = self.threads.len() as u64;
         let dataset_size = dataset.items_count();
+        let initialized_items = Arc::new(AtomicU64::new(0));
+        let commitment_tracker = self.commitment_tracker.clone();
+        let pinned_core_id = self.pinned_core_id;
 
         let closure = |thread_id: usize, thread: &'threads mut ProvingThreadAsync| {
             let thread_id = thread_id as u64;
@@ -174,6 +197,8 @@ impl CUProver {
             let start_item = (dataset_size * thread_id) / threads_number;
             let next_start_item = (dataset_size * (thread_id + 1)) / threads_number;
             let items_count = next_start_item - start_item;
+            let initialized_items = initialized_items.clone();
+            let commitment_tracker = commitment_tracker.clone();
 
             thread
                 .initialize_dataset(
@@ -183,6 +208,18 @@ impl CUProver {
                     start_item,
                     items_count,
                 )
+                .inspect(move |result| {
+                    if result.is_ok() {
+                        let initialized = initialized_items
+                            .fetch_add(items_count, Ordering::Relaxed)
+                            + items_count;
+                        let percent = (initialized * 100 / dataset_size.max(1)) as u8;
+                        commitment_tracker.set_phase(
+                            pinned_core_id,
+                            CUCommitmentPhase::InitializingDataset { percent },
+                        );
+                    }
+                })
                 .boxed()
         };
 
@@ -211,6 +248,22 @@ impl CUProver {
     }
 }
 
+impl CUProver {
+    pub(crate) fn detailed_status(&self) -> CUProverStatus {
+        let cu_id = match self.status {
+            CUStatus::Running { cu_id } => Some(cu_id),
+            CUStatus::Idle => None,
+        };
+
+        CUProverStatus {
+            status: self.status,
+            cu_id,
+            pinned_core_id: self.pinned_core_id,
+            thread_core_ids: self.threads.iter().map(|thread| thread.core_id()).collect(),
+        }
+    }
+}
+
 impl ToCUStatus for CUProver {
     fn status(&self) -> CUStatus {
         self.status
@@ -22,6 +22,7 @@ use cpu_utils::CPUTopologyError;
 use cpu_utils::PhysicalCoreId;
 
 use super::proving_thread::ProvingThreadError;
+use crate::errors::CCProverErrorKind;
 
 #[derive(ThisError, Debug)]
 pub enum CUProverError {
@@ -58,6 +59,44 @@ impl CUProverError {
         let thread_allocation_error = ThreadAllocationError::LogicalCPUNotFound { core_id };
         Self::ThreadAllocation(thread_allocation_error)
     }
+
+    pub fn kind(&self) -> CCProverErrorKind {
+        match self {
+            Self::ThreadError(error) => error.kind(),
+            // threads of a CU prover fail for the same reason as a rule
+            Self::ThreadErrors(errors) => errors
+                .first()
+                .map_or(CCProverErrorKind::Internal, ProvingThreadError::kind),
+            Self::RandomXError(RandomXError::CacheAllocationFailed { .. })
+            | Self::RandomXError(RandomXError::DatasetAllocationError { .. }) => {
+                CCProverErrorKind::DatasetAllocationFailed
+            }
+            Self::RandomXError(RandomXError::VMCreationFailed(_)) | Self::ChannelError(_) => {
+                CCProverErrorKind::Internal
+            }
+            Self::ThreadAllocation(ThreadAllocationError::LogicalCPUNotFound { core_id }) => {
+                CCProverErrorKind::CoreNotFound {
+                    core_id: Some(*core_id),
+                }
+            }
+            Self::ThreadAllocation(ThreadAllocationError::TopologyError(error))
+            | Self::TopologyError(error) => topology_error_kind(error),
+        }
+    }
+}
+
+fn topology_error_kind(error: &CPUTopologyError) -> CCProverErrorKind {
+    match error {
+        CPUTopologyError::PhysicalCoreNotFound { core_id }
+        | CPUTopologyError::LogicalCoresNotFound { core_id }
+        | CPUTopologyError::CPUSetNotFound { core_id } => CCProverErrorKind::CoreNotFound {
+            core_id: Some(*core_id),
+        },
+        CPUTopologyError::PhysicalCoresNotFound => {
+            CCProverErrorKind::CoreNotFound { core_id: None }
+        }
+        _ => CCProverErrorKind::Internal,
+    }
 }
 
 impl From<Vec<ProvingThreadError>> for CUProverError {
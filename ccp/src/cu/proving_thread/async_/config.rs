@@ -15,20 +15,24 @@
  */
 
 use crate::cu::CUProverConfig;
+use crate::hashing_gate::HashingGate;
+use crate::runtime_settings::RuntimeSettings;
 
 #[derive(Debug, Clone)]
 pub struct ProvingThreadConfig {
-    pub hashes_per_round: usize,
+    pub runtime_settings: RuntimeSettings,
     pub async_to_sync_queue_size: usize,
     pub sync_to_async_queue_size: usize,
+    pub hashing_gate: HashingGate,
 }
 
 impl ProvingThreadConfig {
     pub fn from_cu_prover_config(cu_config: &CUProverConfig) -> Self {
         Self {
-            hashes_per_round: cu_config.hashes_per_round,
+            runtime_settings: cu_config.runtime_settings.clone(),
             async_to_sync_queue_size: cu_config.async_to_sync_queue_size,
             sync_to_async_queue_size: cu_config.sync_to_async_queue_size,
+            hashing_gate: cu_config.hashing_gate.clone(),
         }
     }
 }
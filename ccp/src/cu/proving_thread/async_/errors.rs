@@ -21,6 +21,8 @@ use tokio::sync::mpsc;
 use crate::cu::proving_thread::sync::ProvingThreadSyncFacadeError;
 use ccp_msr::MSRError;
 
+use crate::errors::CCProverErrorKind;
+
 #[derive(ThisError, Debug)]
 pub enum ProvingThreadAsyncError {
     #[error(transparent)]
@@ -44,6 +46,15 @@ impl ProvingThreadAsyncError {
     pub fn join_error(error: Box<dyn Any + Send>) -> Self {
         Self::JoinThreadFailed(error)
     }
+
+    pub fn kind(&self) -> CCProverErrorKind {
+        match self {
+            Self::MsrError(_) => CCProverErrorKind::MsrFailure,
+            Self::ChannelError(_) | Self::SyncThreadError(_) | Self::JoinThreadFailed(_) => {
+                CCProverErrorKind::Internal
+            }
+        }
+    }
 }
 
 impl<T> From<mpsc::error::SendError<T>> for ProvingThreadAsyncError {
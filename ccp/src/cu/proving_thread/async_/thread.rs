@@ -30,13 +30,15 @@ use crate::cu::proving_thread::facade::ProvingThreadFacade;
 use crate::cu::proving_thread::messages::*;
 use crate::cu::proving_thread::sync::to_utility_message::ToUtilityInlet;
 use crate::cu::proving_thread::sync::ProvingThreadSync;
+use crate::runtime_settings::RuntimeSettings;
 
 #[derive(Debug)]
 pub(crate) struct ProvingThreadAsync {
     to_sync: AsyncToSyncInlet,
     from_sync: SyncToAsyncOutlet,
     sync_thread: ProvingThreadSync,
-    hashes_per_round: usize,
+    runtime_settings: RuntimeSettings,
+    core_id: LogicalCoreId,
 }
 
 impl ProvingThreadAsync {
@@ -50,16 +52,28 @@ impl ProvingThreadAsync {
             mpsc::channel::<AsyncToSyncMessage>(config.async_to_sync_queue_size);
         let (to_async, from_sync) =
             mpsc::channel::<SyncToAsyncMessage>(config.sync_to_async_queue_size);
-        let sync_thread =
-            ProvingThreadSync::spawn(core_id, msr_enforcer, from_async, to_async, to_utility);
+        let sync_thread = ProvingThreadSync::spawn(
+            core_id,
+            msr_enforcer,
+            from_async,
+            to_async,
+            to_utility,
+            config.hashing_gate,
+        );
 
         Self {
             to_sync,
             from_sync,
             sync_thread,
-            hashes_per_round: config.hashes_per_round,
+            runtime_settings: config.runtime_settings,
+            core_id,
         }
     }
+
+    /// Returns a logical core the thread is pinned to.
+    pub(crate) fn core_id(&self) -> LogicalCoreId {
+        self.core_id
+    }
 }
 
 impl ProvingThreadFacade for ProvingThreadAsync {
@@ -135,14 +149,16 @@ impl ProvingThreadFacade for ProvingThreadAsync {
         let job = NewCCJob::new(epoch, dataset, flags, cu_id);
         let message = AsyncToSyncMessage::NewCCJob {
             job,
-            hashes_per_round: self.hashes_per_round,
+            hashes_per_round: self.runtime_settings.hashes_per_round(),
         };
         self.to_sync.send(message).await.map_err(Into::into)
     }
 
     async fn pin(&mut self, core_id: LogicalCoreId) -> Result<(), Self::Error> {
         let message = AsyncToSyncMessage::PinThread(PinThread { core_id });
-        self.to_sync.send(message).await.map_err(Into::into)
+        self.to_sync.send(message).await?;
+        self.core_id = core_id;
+        Ok(())
     }
 
     async fn pause(&mut self) -> Result<(), Self::Error> {
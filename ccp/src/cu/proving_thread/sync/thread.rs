@@ -15,6 +15,7 @@
  */
 
 use std::thread;
+use std::time::Duration;
 use std::time::Instant;
 
 use ccp_msr::MSREnforce;
@@ -34,11 +35,15 @@ use super::STFResult;
 use super::STResult;
 use crate::cu::proving_thread::messages::*;
 use crate::cu::proving_thread::sync::errors::ProvingThreadSyncFacadeError;
+use crate::hashing_gate::HashingGate;
 use crate::hashrate::ThreadHashrateRecord;
 
 const CHANNEL_DROPPED_MESSAGE: &str =
     "ThreadState::WaitForMessage async part of the ptt channel is dropped";
 
+/// How long a thread sleeps between checks of a closed hashing gate.
+const CLOSED_GATE_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
 #[derive(Debug)]
 pub(crate) struct ProvingThreadSync {
     handle: thread::JoinHandle<STFResult<()>>,
@@ -51,9 +56,16 @@ impl ProvingThreadSync {
         from_async: AsyncToSyncOutlet,
         to_async: SyncToAsyncInlet,
         to_utility: ToUtilityInlet,
+        hashing_gate: HashingGate,
     ) -> Self {
-        let thread_closure =
-            Self::proving_closure(core_id, msr_enforcer, from_async, to_async, to_utility);
+        let thread_closure = Self::proving_closure(
+            core_id,
+            msr_enforcer,
+            from_async,
+            to_async,
+            to_utility,
+            hashing_gate,
+        );
         let handle = thread::spawn(thread_closure);
 
         Self { handle }
@@ -71,6 +83,7 @@ impl ProvingThreadSync {
         mut from_async: AsyncToSyncOutlet,
         to_async: SyncToAsyncInlet,
         to_utility: ToUtilityInlet,
+        hashing_gate: HashingGate,
     ) -> Box<dyn FnMut() -> STFResult<()> + Send + 'static> {
         let to_utility_outer = to_utility.clone();
 
@@ -101,7 +114,12 @@ impl ProvingThreadSync {
                     ThreadState::CCJob { mut job } => {
                         use tokio::sync::mpsc::error::TryRecvError;
 
-                        job.cc_prove(core_id, &to_utility)?;
+                        if hashing_gate.is_open() {
+                            job.cc_prove(core_id, &to_utility)?;
+                        } else {
+                            // keep the job, so hashing is resumed right after the gate is opened
+                            thread::sleep(CLOSED_GATE_POLL_INTERVAL);
+                        }
 
                         match from_async.try_recv() {
                             Ok(message) => ThreadState::NewMessage { message },
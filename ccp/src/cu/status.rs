@@ -14,14 +14,7 @@
  * limitations under the License.
  */
 
-use ccp_shared::types::CUID;
-
-/// Represents a status of a CU prover.
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
-pub enum CUStatus {
-    Running { cu_id: CUID },
-    Idle,
-}
+pub use ccp_shared::status::CUStatus;
 
 pub trait ToCUStatus {
     fn status(&self) -> CUStatus;
@@ -29,6 +29,7 @@ use super::CUProver;
 use super::CUProverConfig;
 use crate::cu::status::CUStatus;
 use crate::cu::status::ToCUStatus;
+use crate::runtime_settings::RuntimeSettings;
 use crate::utility_thread::message::RawProof;
 use crate::utility_thread::message::ToUtilityMessage;
 
@@ -82,9 +83,11 @@ fn create_config(cores_count: usize) -> CUProverConfig {
         threads_per_core_policy: ThreadsPerCoreAllocationPolicy::Exact {
             threads_per_physical_core: std::num::NonZeroUsize::new(cores_count).unwrap(),
         },
-        hashes_per_round: 1024,
+        runtime_settings: RuntimeSettings::new(false, 1024),
         async_to_sync_queue_size: 1,
         sync_to_async_queue_size: 1,
+        hashing_gate: <_>::default(),
+        commitment_tracker: <_>::default(),
     }
 }
 
@@ -0,0 +1,285 @@
+/*
+ * Copyright 2024 Fluence Labs Limited
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::Instant;
+
+use parking_lot::Mutex;
+use prometheus_client::metrics::gauge::Gauge;
+use prometheus_client::registry::Registry;
+use tokio::task::JoinHandle;
+use tokio_util::sync::CancellationToken;
+
+use ccp_config::CriticalDiskPolicy;
+use ccp_config::DiskGuard;
+
+use crate::hashing_gate::GateCloseReason;
+use crate::hashing_gate::HashingGate;
+use crate::status::DiskSpaceLevel;
+
+/// Checks before writes are throttled, since hashrate records are written very often.
+const MIN_RECHECK_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Watches free space on the filesystem of the state directory, where proofs and
+/// hashrate are written, and applies the configured policy when it's critically low.
+#[derive(Clone, Debug)]
+pub(crate) struct DiskSpaceGuard {
+    state: Arc<Mutex<GuardState>>,
+    state_dir: PathBuf,
+    config: DiskGuard,
+    hashing_gate: HashingGate,
+    free_space_gauge: Gauge,
+    level_gauge: Gauge,
+}
+
+#[derive(Debug)]
+struct GuardState {
+    level: DiskSpaceLevel,
+    last_check: Option<Instant>,
+}
+
+impl DiskSpaceGuard {
+    pub(crate) fn new(state_dir: PathBuf, config: DiskGuard, hashing_gate: HashingGate) -> Self {
+        let state = GuardState {
+            level: DiskSpaceLevel::Normal,
+            last_check: None,
+        };
+
+        Self {
+            state: Arc::new(Mutex::new(state)),
+            state_dir,
+            config,
+            hashing_gate,
+            free_space_gauge: Gauge::default(),
+            level_gauge: Gauge::default(),
+        }
+    }
+
+    /// Checks free space right away and returns the new level.
+    pub(crate) fn check(&self) -> DiskSpaceLevel {
+        let mut state = self.state.lock();
+        self.check_locked(&mut state)
+    }
+
+    /// Checks free space before a write, if the last check isn't too recent.
+    pub(crate) fn check_before_write(&self) -> DiskSpaceLevel {
+        let mut state = self.state.lock();
+        match state.last_check {
+            Some(last_check) if last_check.elapsed() < MIN_RECHECK_INTERVAL => state.level,
+            _ => self.check_locked(&mut state),
+        }
+    }
+
+    pub(crate) fn level(&self) -> DiskSpaceLevel {
+        self.state.lock().level
+    }
+
+    /// Returns false if the sliding hashrate CSV shouldn't be written because of low disk space.
+    pub(crate) fn allows_sliding_hashrate(&self) -> bool {
+        self.config.critical_policy != CriticalDiskPolicy::StopSlidingCsv
+            || self.check_before_write() != DiskSpaceLevel::Critical
+    }
+
+    pub(crate) fn apply_to_registry(&self, registry: &mut Registry) {
+        registry.register(
+            "free_disk_space_bytes",
+            "Free space on the filesystem of the state directory",
+            self.free_space_gauge.clone(),
+        );
+        registry.register(
+            "disk_space_level",
+            "Free disk space level: 0 is normal, 1 is warning, 2 is critical",
+            self.level_gauge.clone(),
+        );
+    }
+
+    fn check_locked(&self, state: &mut GuardState) -> DiskSpaceLevel {
+        state.last_check = Some(Instant::now());
+
+        let free_space = match free_space(&self.state_dir) {
+            Ok(free_space) => free_space,
+            Err(e) => {
+                log::warn!(
+                    "failed to get free space of {}: {e}",
+                    self.state_dir.display()
+                );
+                return state.level;
+            }
+        };
+        self.free_space_gauge
+            .set(i64::try_from(free_space).unwrap_or(i64::MAX));
+
+        let new_level = if free_space < self.config.critical_free_space_bytes {
+            DiskSpaceLevel::Critical
+        } else if free_space < self.config.warn_free_space_bytes {
+            DiskSpaceLevel::Warning
+        } else {
+            DiskSpaceLevel::Normal
+        };
+
+        if new_level != state.level {
+            self.on_level_changed(state.level, new_level, free_space);
+            state.level = new_level;
+        }
+
+        new_level
+    }
+
+    fn on_level_changed(&self, old_level: DiskSpaceLevel, new_level: DiskSpaceLevel, free: u64) {
+        let state_dir = self.state_dir.display();
+        match new_level {
+            DiskSpaceLevel::Normal => {
+                log::info!("free disk space of {state_dir} is back to normal: {free} bytes")
+            }
+            DiskSpaceLevel::Warning => {
+                log::warn!("free disk space of {state_dir} is low: {free} bytes")
+            }
+            DiskSpaceLevel::Critical => log::error!(
+                "free disk space of {state_dir} is critically low: {free} bytes, applying {:?} policy",
+                self.config.critical_policy
+            ),
+        }
+        self.level_gauge.set(new_level as i64);
+
+        if self.config.critical_policy != CriticalDiskPolicy::PauseProvers {
+            return;
+        }
+
+        if new_level == DiskSpaceLevel::Critical {
+            self.hashing_gate.close(GateCloseReason::LowDiskSpace);
+        } else if old_level == DiskSpaceLevel::Critical {
+            log::info!("resuming hashing paused because of low disk space");
+            self.hashing_gate.open(GateCloseReason::LowDiskSpace);
+        }
+    }
+}
+
+fn free_space(path: &Path) -> nix::Result<u64> {
+    let stat = nix::sys::statvfs::statvfs(path)?;
+    #[allow(clippy::useless_conversion)]
+    let free_space = u64::from(stat.blocks_available()) * u64::from(stat.fragment_size());
+    Ok(free_space)
+}
+
+/// Periodically checks free disk space in background.
+pub(crate) struct DiskSpaceMonitor {
+    cancellation: CancellationToken,
+    handle: JoinHandle<()>,
+}
+
+impl DiskSpaceMonitor {
+    pub(crate) fn spawn(guard: DiskSpaceGuard) -> Self {
+        let cancellation = CancellationToken::new();
+        let handle = tokio::task::spawn(run_disk_space_monitor(guard, cancellation.clone()));
+
+        Self {
+            cancellation,
+            handle,
+        }
+    }
+
+    pub(crate) async fn shutdown(&mut self) -> Result<(), tokio::task::JoinError> {
+        log::info!("Shutting down disk space monitor");
+        self.cancellation.cancel();
+        (&mut self.handle).await
+    }
+}
+
+async fn run_disk_space_monitor(guard: DiskSpaceGuard, cancellation: CancellationToken) {
+    let mut interval = tokio::time::interval(guard.config.check_interval);
+    loop {
+        tokio::select! {
+            _ = interval.tick() => {
+                let guard = guard.clone();
+                // statvfs is a blocking call
+                if let Err(e) = tokio::task::spawn_blocking(move || guard.check()).await {
+                    log::warn!("disk space check failed: {e}");
+                }
+            }
+            _ = cancellation.cancelled() => return,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn guard_with(config: DiskGuard) -> (DiskSpaceGuard, HashingGate, tempdir::TempDir) {
+        let dir = tempdir::TempDir::new("disk_guard").unwrap();
+        let gate = HashingGate::default();
+        let guard = DiskSpaceGuard::new(dir.path().to_path_buf(), config, gate.clone());
+        (guard, gate, dir)
+    }
+
+    #[test]
+    fn enough_space_is_normal() {
+        let config = DiskGuard {
+            warn_free_space_bytes: 0,
+            critical_free_space_bytes: 0,
+            ..DiskGuard::default()
+        };
+        let (guard, gate, _dir) = guard_with(config);
+
+        assert_eq!(guard.check(), DiskSpaceLevel::Normal);
+        assert!(gate.is_open());
+        assert!(guard.allows_sliding_hashrate());
+    }
+
+    #[test]
+    fn low_space_is_warning() {
+        let config = DiskGuard {
+            warn_free_space_bytes: u64::MAX,
+            critical_free_space_bytes: 0,
+            ..DiskGuard::default()
+        };
+        let (guard, _gate, _dir) = guard_with(config);
+
+        assert_eq!(guard.check(), DiskSpaceLevel::Warning);
+    }
+
+    #[test]
+    fn critical_level_pauses_provers() {
+        let config = DiskGuard {
+            warn_free_space_bytes: u64::MAX,
+            critical_free_space_bytes: u64::MAX,
+            critical_policy: CriticalDiskPolicy::PauseProvers,
+            ..DiskGuard::default()
+        };
+        let (guard, gate, _dir) = guard_with(config);
+
+        assert_eq!(guard.check(), DiskSpaceLevel::Critical);
+        assert!(!gate.is_open());
+        assert!(guard.allows_sliding_hashrate());
+    }
+
+    #[test]
+    fn critical_level_stops_sliding_csv() {
+        let config = DiskGuard {
+            warn_free_space_bytes: u64::MAX,
+            critical_free_space_bytes: u64::MAX,
+            critical_policy: CriticalDiskPolicy::StopSlidingCsv,
+            ..DiskGuard::default()
+        };
+        let (guard, gate, _dir) = guard_with(config);
+
+        assert!(!guard.allows_sliding_hashrate());
+        assert!(gate.is_open());
+    }
+}
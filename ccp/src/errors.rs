@@ -17,8 +17,12 @@
 use thiserror::Error as ThisError;
 use tokio::task::JoinError;
 
+use ccp_shared::types::Difficulty;
+use ccp_shared::types::PhysicalCoreId;
+
 use crate::cu::CUProverError;
 use crate::hashrate::HashrateError;
+use crate::proof_sink::ProofSinkError;
 use crate::utility_thread::UtilityThreadError;
 
 #[derive(ThisError, Debug)]
@@ -38,8 +42,70 @@ pub enum CCProverError {
     #[error(transparent)]
     UtilityThreadError(#[from] UtilityThreadError),
 
+    #[error(transparent)]
+    ProofSinkError(#[from] ProofSinkError),
+
     #[error(transparent)]
     IOError(#[from] tokio::io::Error),
+
+    #[error("difficulty {difficulty} is rejected: it has {zero_bits} leading zero bits, at least {min_zero_bits} are required")]
+    AbsurdDifficulty {
+        difficulty: Difficulty,
+        zero_bits: u32,
+        min_zero_bits: u32,
+    },
+}
+
+/// A coarse classification of prover errors, so callers could react on them
+/// without parsing messages.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CCProverErrorKind {
+    /// A core isn't found in the CPU topology, e.g. a CU is allocated on a non-existing core.
+    CoreNotFound {
+        core_id: Option<PhysicalCoreId>,
+    },
+    /// Memory for a RandomX cache or dataset can't be allocated.
+    DatasetAllocationFailed,
+    /// MSR registers can't be read or written.
+    MsrFailure,
+    /// The state directory or proof storage isn't accessible.
+    StorageIo,
+    /// Supplied parameters are rejected.
+    InvalidParams,
+    Internal,
+}
+
+impl CCProverError {
+    /// Classifies the error, per CU errors of `CUProverErrors` are classified separately,
+    /// see `CUProverError::kind`.
+    pub fn kind(&self) -> CCProverErrorKind {
+        match self {
+            Self::CUProverError(error) => error.kind(),
+            Self::CUProverErrors(errors) => match errors.as_slice() {
+                [error] => error.kind(),
+                _ => CCProverErrorKind::Internal,
+            },
+            Self::HashrateError(error) => hashrate_error_kind(error),
+            Self::UtilityThreadError(UtilityThreadError::IOError(_)) => {
+                CCProverErrorKind::StorageIo
+            }
+            Self::UtilityThreadError(UtilityThreadError::HashrateError(error)) => {
+                hashrate_error_kind(error)
+            }
+            Self::IOError(_) => CCProverErrorKind::StorageIo,
+            Self::AbsurdDifficulty { .. } => CCProverErrorKind::InvalidParams,
+            Self::JoinError(_) | Self::UtilityThreadError(_) | Self::ProofSinkError(_) => {
+                CCProverErrorKind::Internal
+            }
+        }
+    }
+}
+
+fn hashrate_error_kind(error: &HashrateError) -> CCProverErrorKind {
+    match error {
+        HashrateError::IOError(_) => CCProverErrorKind::StorageIo,
+        HashrateError::CSVError(_) => CCProverErrorKind::Internal,
+    }
 }
 
 impl From<Vec<CUProverError>> for CCProverError {
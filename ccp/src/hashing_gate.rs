@@ -0,0 +1,102 @@
+/*
+ * Copyright 2024 Fluence Labs Limited
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::sync::atomic::AtomicU8;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+
+/// Allows to suspend hashing on all proving threads without dropping their caches and datasets.
+/// The gate could be closed for several independent reasons, hashing is resumed only
+/// when all of them are gone.
+#[derive(Clone, Debug, Default)]
+pub struct HashingGate {
+    closed_by: Arc<AtomicU8>,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[repr(u8)]
+pub(crate) enum GateCloseReason {
+    LowDiskSpace = 1,
+    Paused = 2,
+}
+
+impl HashingGate {
+    pub(crate) fn close(&self, reason: GateCloseReason) {
+        self.closed_by.fetch_or(reason as u8, Ordering::AcqRel);
+    }
+
+    pub(crate) fn open(&self, reason: GateCloseReason) {
+        self.closed_by.fetch_and(!(reason as u8), Ordering::AcqRel);
+    }
+
+    pub(crate) fn is_open(&self) -> bool {
+        self.closed_by.load(Ordering::Acquire) == 0
+    }
+
+    /// Suspends hashing until `resume` is called, proving jobs are kept,
+    /// so they continue right after resuming, even if they were changed meanwhile.
+    pub fn pause(&self) {
+        self.close(GateCloseReason::Paused);
+    }
+
+    /// Resumes hashing paused by `pause`, it could still be suspended for other reasons.
+    pub fn resume(&self) {
+        self.open(GateCloseReason::Paused);
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.closed_by.load(Ordering::Acquire) & GateCloseReason::Paused as u8 != 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gate_is_open_by_default() {
+        let gate = HashingGate::default();
+        assert!(gate.is_open());
+    }
+
+    #[test]
+    fn gate_is_shared_between_clones() {
+        let gate = HashingGate::default();
+        let clone = gate.clone();
+
+        gate.close(GateCloseReason::LowDiskSpace);
+        assert!(!clone.is_open());
+
+        clone.open(GateCloseReason::LowDiskSpace);
+        assert!(gate.is_open());
+    }
+
+    #[test]
+    fn resume_keeps_other_reasons() {
+        let gate = HashingGate::default();
+
+        gate.pause();
+        gate.close(GateCloseReason::LowDiskSpace);
+        assert!(gate.is_paused());
+
+        gate.resume();
+        assert!(!gate.is_paused());
+        assert!(!gate.is_open());
+
+        gate.open(GateCloseReason::LowDiskSpace);
+        assert!(gate.is_open());
+    }
+}
@@ -59,6 +59,7 @@ pub(crate) struct ThreadHashrateRaw {
     cc_job_duration: ParameterStatus<Duration>,
     checked_hashes_count: u64,
     found_proofs_count: u64,
+    rejected_proofs_count: u64,
 }
 
 /// Processed cumulative hashrate for a sync thread.
@@ -146,6 +147,13 @@ impl HashrateCollector {
             .account_proof_found()
     }
 
+    pub(crate) fn proof_rejected(&mut self, core_id: LogicalCoreId) {
+        self.entries
+            .entry(core_id)
+            .or_default()
+            .account_proof_rejected()
+    }
+
     fn observe_epoch(&mut self, new_epoch: EpochParameters) -> EpochObservation {
         match self.status {
             CollectorStatus::Idle => {
@@ -229,6 +237,10 @@ impl ThreadHashrateRaw {
     pub(crate) fn account_proof_found(&mut self) {
         self.found_proofs_count += 1;
     }
+
+    pub(crate) fn account_proof_rejected(&mut self) {
+        self.rejected_proofs_count += 1;
+    }
 }
 
 impl Collector for ThreadHashrateRaw {
@@ -254,6 +266,15 @@ impl Collector for ThreadHashrateRaw {
         )?;
         found_proofs_counter.encode(found_proofs_encoder)?;
 
+        let rejected_proofs_counter = ConstCounter::new(self.rejected_proofs_count);
+        let rejected_proofs_encoder = encoder.encode_descriptor(
+            "rejected_proofs",
+            "Found proofs rejected by self-verification",
+            None,
+            rejected_proofs_counter.metric_type(),
+        )?;
+        rejected_proofs_counter.encode(rejected_proofs_encoder)?;
+
         Ok(())
     }
 }
@@ -268,4 +289,11 @@ impl<T> ParameterStatus<T> {
             ParameterStatus::NotMeasured => ParameterStatus::NotMeasured,
         }
     }
+
+    pub fn measured(self) -> Option<T> {
+        match self {
+            ParameterStatus::Measured(value) => Some(value),
+            ParameterStatus::NotMeasured => None,
+        }
+    }
 }
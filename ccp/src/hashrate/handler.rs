@@ -14,6 +14,7 @@
  * limitations under the License.
  */
 
+use ccp_shared::hashrate::HashrateReport;
 use ccp_shared::types::LogicalCoreId;
 use std::path::PathBuf;
 use std::sync::Arc;
@@ -24,30 +25,35 @@ use super::HashrateCollector;
 use super::HashrateSaver;
 use super::SlidingHashrateCollector;
 use super::ThreadHashrateRecord;
+use crate::disk_guard::DiskSpaceGuard;
 use crate::hashrate::collector::EpochObservation;
-use crate::hashrate::sliding_collector::SlidingHashrate;
+use crate::runtime_settings::RuntimeSettings;
 
 pub(crate) struct HashrateHandler {
     collector: Arc<Mutex<HashrateCollector>>,
-    instant_hashrate_enabled: bool,
-    sliding_collector: SlidingHashrateCollector,
+    /// Tells whether instant hashrate is saved, it could be changed at runtime.
+    runtime_settings: RuntimeSettings,
+    sliding_collector: Arc<Mutex<SlidingHashrateCollector>>,
     saver: HashrateSaver,
+    disk_space_guard: DiskSpaceGuard,
 }
 
 impl HashrateHandler {
     pub(crate) fn new(
         collector: Arc<Mutex<HashrateCollector>>,
+        sliding_collector: Arc<Mutex<SlidingHashrateCollector>>,
         state_dir_path: PathBuf,
-        instant_hashrate_enabled: bool,
+        runtime_settings: RuntimeSettings,
+        disk_space_guard: DiskSpaceGuard,
     ) -> HResult<Self> {
-        let sliding_collector = SlidingHashrateCollector::new();
         let saver = HashrateSaver::from_directory(state_dir_path)?;
 
         let handler = Self {
             collector,
             sliding_collector,
-            instant_hashrate_enabled,
+            runtime_settings,
             saver,
+            disk_space_guard,
         };
 
         Ok(handler)
@@ -64,8 +70,13 @@ impl HashrateHandler {
             self.saver.cleanup_sliding_hashrate()?;
         }
 
-        self.sliding_collector.account_record(record);
-        if self.instant_hashrate_enabled {
+        self.sliding_collector
+            .lock()
+            .unwrap()
+            .account_record(record);
+        if self.runtime_settings.report_hashrate()
+            && self.disk_space_guard.allows_sliding_hashrate()
+        {
             self.saver.save_hashrate_entry(&record)?;
         }
 
@@ -77,6 +88,11 @@ impl HashrateHandler {
         guard.proof_found(core_id)
     }
 
+    pub(crate) fn proof_rejected(&mut self, core_id: LogicalCoreId) {
+        let mut guard = self.collector.lock().unwrap();
+        guard.proof_rejected(core_id)
+    }
+
     pub(crate) fn handle_cum_tick(&self) -> HResult<()> {
         let guard = self.collector.lock().unwrap();
         let hashrate = guard.collect();
@@ -84,7 +100,9 @@ impl HashrateHandler {
     }
 
     #[allow(dead_code)]
-    pub(crate) fn sliding_hashrate(&self) -> &SlidingHashrate {
-        self.sliding_collector.hashrate()
+    pub(crate) fn report(&self) -> HashrateReport {
+        let collector = self.collector.lock().unwrap();
+        let sliding_collector = self.sliding_collector.lock().unwrap();
+        super::make_report(&collector, &sliding_collector, [])
     }
 }
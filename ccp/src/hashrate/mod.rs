@@ -20,11 +20,8 @@ mod handler;
 mod hashratable;
 pub(crate) mod prometheus;
 mod record;
+mod report;
 mod saver;
-#[cfg(feature = "crossterm")]
-mod sliding_collector;
-#[cfg(not(feature = "crossterm"))]
-#[path = "sliding_collector_noop.rs"]
 mod sliding_collector;
 
 pub(crate) type HResult<T> = Result<T, HashrateError>;
@@ -33,5 +30,6 @@ pub(crate) use collector::HashrateCollector;
 pub(crate) use errors::HashrateError;
 pub(crate) use handler::HashrateHandler;
 pub(crate) use record::ThreadHashrateRecord;
+pub(crate) use report::make_report;
 pub(crate) use saver::HashrateSaver;
 pub(crate) use sliding_collector::SlidingHashrateCollector;
@@ -23,16 +23,25 @@ use axum::http;
 use axum::response;
 use axum::response::ErrorResponse;
 use axum::routing::get;
+use prometheus_client::metrics::counter::Counter;
 use prometheus_client::registry::Registry;
+use tokio::net::TcpListener;
 use tokio::net::ToSocketAddrs;
 use tokio::task::JoinHandle;
 use tokio_util::sync::CancellationToken;
 
 use super::HashrateCollector;
+use crate::disk_guard::DiskSpaceGuard;
+use crate::proof_circuit_breaker::ProofCircuitBreaker;
+use crate::proof_sink::ProofSinkMetrics;
 
-#[derive(Clone, Default, Debug)]
+#[derive(Clone, Debug)]
 pub(crate) struct PrometheusMetrics {
     pub(crate) hashrate_collector: Arc<Mutex<HashrateCollector>>,
+    pub(crate) quarantined_proofs: Counter,
+    pub(crate) proof_circuit_breaker: ProofCircuitBreaker,
+    pub(crate) disk_space_guard: DiskSpaceGuard,
+    pub(crate) proof_sink_metrics: ProofSinkMetrics,
 }
 
 async fn handler_404() -> impl response::IntoResponse {
@@ -57,6 +66,15 @@ async fn handle_metrics(
             guard.apply_to_registry(&mut registry);
         }
 
+        registry.register(
+            "quarantined_proofs",
+            "Unreadable proof files moved to quarantine",
+            state.quarantined_proofs.clone(),
+        );
+        state.proof_circuit_breaker.apply_to_registry(&mut registry);
+        state.disk_space_guard.apply_to_registry(&mut registry);
+        state.proof_sink_metrics.apply_to_registry(&mut registry);
+
         prometheus_client::encoding::text::encode(&mut buf, &registry).map_err(|e| {
             log::warn!("Metrics encode error: {}", e);
             ErrorResponse::from(http::StatusCode::INTERNAL_SERVER_ERROR)
@@ -76,24 +94,28 @@ async fn handle_metrics(
         })
 }
 
-async fn run_prometheus_endpoint(
+async fn bind_prometheus_endpoint(
     prometheus_listen_address: impl ToSocketAddrs + std::fmt::Debug,
-    hashrate_collector: Arc<Mutex<HashrateCollector>>,
-    cancellation: CancellationToken,
-) -> tokio::io::Result<()> {
-    let state = PrometheusMetrics { hashrate_collector };
-    let app = axum::Router::new()
-        .route("/metrics", get(handle_metrics))
-        .fallback(handler_404)
-        .with_state(state);
+) -> tokio::io::Result<TcpListener> {
     log::info!("Starting a prometheus endpoint at {prometheus_listen_address:?}");
-    let listener = tokio::net::TcpListener::bind(&prometheus_listen_address)
+    TcpListener::bind(&prometheus_listen_address)
         .await
         .inspect_err(|e| {
             log::error!(
                 "Failed to start a prometheus endpoint at {prometheus_listen_address:?}: {e}"
             );
-        })?;
+        })
+}
+
+async fn run_prometheus_endpoint(
+    listener: TcpListener,
+    state: PrometheusMetrics,
+    cancellation: CancellationToken,
+) -> tokio::io::Result<()> {
+    let app = axum::Router::new()
+        .route("/metrics", get(handle_metrics))
+        .fallback(handler_404)
+        .with_state(state);
     let server = axum::serve(listener, app.into_make_service());
     server
         .with_graceful_shutdown(cancellation.cancelled_owned())
@@ -108,22 +130,43 @@ pub(crate) struct PrometheusEndpoint {
 }
 
 impl PrometheusEndpoint {
+    /// Binds the address in background, a failure is returned by `shutdown`.
     pub(crate) fn new(
         prometheus_listen_address: impl ToSocketAddrs + std::fmt::Debug + Send + Sync + 'static,
-        hashrate_collector: Arc<Mutex<HashrateCollector>>,
+        metrics: PrometheusMetrics,
     ) -> Self {
         let cancellation = CancellationToken::new();
 
+        let endpoint_cancellation = cancellation.clone();
+        let handle = tokio::task::spawn(async move {
+            let listener = bind_prometheus_endpoint(prometheus_listen_address).await?;
+            run_prometheus_endpoint(listener, metrics, endpoint_cancellation).await
+        });
+
+        Self {
+            cancellation,
+            handle,
+        }
+    }
+
+    /// Unlike `new`, fails right away if the address can't be bound.
+    pub(crate) async fn bind(
+        prometheus_listen_address: impl ToSocketAddrs + std::fmt::Debug,
+        metrics: PrometheusMetrics,
+    ) -> tokio::io::Result<Self> {
+        let listener = bind_prometheus_endpoint(prometheus_listen_address).await?;
+        let cancellation = CancellationToken::new();
+
         let handle = tokio::task::spawn(run_prometheus_endpoint(
-            prometheus_listen_address,
-            hashrate_collector,
+            listener,
+            metrics,
             cancellation.clone(),
         ));
 
-        Self {
+        Ok(Self {
             cancellation,
             handle,
-        }
+        })
     }
 
     pub(crate) async fn shutdown(&mut self) -> tokio::io::Result<()> {
@@ -132,3 +175,47 @@ impl PrometheusEndpoint {
         (&mut self.handle).await?
     }
 }
+
+/// Keeps a prometheus endpoint, so it could be moved to another address
+/// or stopped while the prover is running.
+#[derive(Clone)]
+pub(crate) struct PrometheusEndpointSlot {
+    endpoint: Arc<tokio::sync::Mutex<Option<PrometheusEndpoint>>>,
+    metrics: PrometheusMetrics,
+}
+
+impl PrometheusEndpointSlot {
+    pub(crate) fn new(address: Option<(String, u16)>, metrics: PrometheusMetrics) -> Self {
+        let endpoint = address.map(|address| PrometheusEndpoint::new(address, metrics.clone()));
+
+        Self {
+            endpoint: Arc::new(tokio::sync::Mutex::new(endpoint)),
+            metrics,
+        }
+    }
+
+    /// Starts a new endpoint at the address, if it's provided, and stops the running one.
+    /// The new address is bound first, so the running endpoint is kept if it fails,
+    /// e.g. if the port is taken, including by the running endpoint itself.
+    pub(crate) async fn rebind(&self, address: Option<(String, u16)>) -> tokio::io::Result<()> {
+        let mut guard = self.endpoint.lock().await;
+        let new_endpoint = match address {
+            Some(address) => Some(PrometheusEndpoint::bind(address, self.metrics.clone()).await?),
+            None => None,
+        };
+
+        if let Some(mut endpoint) = std::mem::replace(&mut *guard, new_endpoint) {
+            if let Err(e) = endpoint.shutdown().await {
+                log::warn!("Prometheus endpoint failed: {e}");
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) async fn shutdown(&self) -> tokio::io::Result<()> {
+        match self.endpoint.lock().await.take() {
+            Some(mut endpoint) => endpoint.shutdown().await,
+            None => Ok(()),
+        }
+    }
+}
@@ -0,0 +1,145 @@
+/*
+ * Copyright 2024 Fluence Labs Limited
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::collections::BTreeMap;
+
+use ccp_shared::hashrate::CUHashrate;
+use ccp_shared::hashrate::HashrateReport;
+use ccp_shared::hashrate::HashrateStats;
+use ccp_shared::hashrate::HashrateWindows;
+use ccp_shared::hashrate::LogicalCoreHashrate;
+use ccp_shared::types::LogicalCoreId;
+use ccp_shared::types::CUID;
+
+use super::collector::ThreadHashrate;
+use super::sliding_collector::SlidingThreadHashrate;
+use super::HashrateCollector;
+use super::SlidingHashrateCollector;
+
+/// Combines cumulative and sliding hashrate of threads into a report,
+/// threads of each CU from `cu_threads` are also summed up into a CU breakdown.
+pub(crate) fn make_report(
+    collector: &HashrateCollector,
+    sliding_collector: &SlidingHashrateCollector,
+    cu_threads: impl IntoIterator<Item = (CUID, Vec<LogicalCoreId>)>,
+) -> HashrateReport {
+    let mut core_stats = BTreeMap::<LogicalCoreId, HashrateStats>::new();
+    for (core_id, hashrate) in collector.collect() {
+        core_stats.insert(core_id, cumulative_stats(&hashrate));
+    }
+    for (&core_id, sliding_hashrate) in sliding_collector.hashrate() {
+        core_stats.entry(core_id).or_default().windows = windows(sliding_hashrate);
+    }
+
+    let mut cus = cu_threads
+        .into_iter()
+        .map(|(cu_id, core_ids)| {
+            let hashrate = core_ids
+                .iter()
+                .filter_map(|core_id| core_stats.get(core_id))
+                .fold(HashrateStats::default(), HashrateStats::merge);
+
+            CUHashrate {
+                cu_id,
+                core_ids,
+                hashrate,
+            }
+        })
+        .collect::<Vec<_>>();
+    cus.sort_by_key(|cu| cu.cu_id);
+
+    let logical_cores = core_stats
+        .into_iter()
+        .map(|(core_id, hashrate)| LogicalCoreHashrate { core_id, hashrate })
+        .collect();
+
+    HashrateReport { logical_cores, cus }
+}
+
+fn cumulative_stats(hashrate: &ThreadHashrate) -> HashrateStats {
+    HashrateStats {
+        effective_hashrate: hashrate.effective_hashrate,
+        pure_hashrate: hashrate.hashrate.measured(),
+        proofs_found: hashrate.proofs_found,
+        cache_creation_secs: hashrate
+            .cache_creation
+            .map(|duration| duration.as_secs_f64())
+            .measured(),
+        dataset_initialization_secs: hashrate
+            .dataset_initialization
+            .map(|duration| duration.as_secs_f64())
+            .measured(),
+        windows: HashrateWindows::default(),
+    }
+}
+
+fn windows(hashrate: &SlidingThreadHashrate) -> HashrateWindows {
+    HashrateWindows {
+        secs_10: hashrate.window_10.compute_hashrate(),
+        secs_60: hashrate.window_60.compute_hashrate(),
+        secs_900: hashrate.window_900.compute_hashrate(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use ccp_shared::types::EpochParameters;
+    use ccp_shared::types::GlobalNonce;
+
+    use super::*;
+    use crate::hashrate::ThreadHashrateRecord;
+
+    #[test]
+    fn cu_hashrate_sums_up_threads() {
+        let epoch = EpochParameters::new(GlobalNonce::new([2; 32]), <_>::default());
+        let cu_id = CUID::new([1; 32]);
+        let cores = [LogicalCoreId::from(1), LogicalCoreId::from(2)];
+
+        let mut collector = HashrateCollector::new();
+        let mut sliding_collector = SlidingHashrateCollector::new();
+        for (core_id, cache_creation) in cores.into_iter().zip([1, 3]) {
+            let cache_creation = Duration::from_secs(cache_creation);
+            collector.account_record(ThreadHashrateRecord::cache_creation(
+                epoch,
+                core_id,
+                cache_creation,
+            ));
+
+            let record =
+                ThreadHashrateRecord::checked_hashes(epoch, core_id, Duration::from_secs(1), 100);
+            collector.account_record(record);
+            sliding_collector.account_record(record);
+            collector.proof_found(core_id);
+        }
+
+        let report = make_report(&collector, &sliding_collector, [(cu_id, cores.to_vec())]);
+
+        assert_eq!(report.logical_cores.len(), 2);
+        assert_eq!(report.logical_cores[0].core_id, cores[0]);
+        assert_eq!(report.logical_cores[0].hashrate.pure_hashrate, Some(100.0));
+        assert_eq!(report.logical_cores[0].hashrate.windows.secs_10, 100.0);
+
+        assert_eq!(report.cus.len(), 1);
+        let cu = &report.cus[0];
+        assert_eq!(cu.cu_id, cu_id);
+        assert_eq!(cu.hashrate.pure_hashrate, Some(200.0));
+        assert_eq!(cu.hashrate.windows.secs_900, 200.0);
+        assert_eq!(cu.hashrate.proofs_found, 2);
+        assert_eq!(cu.hashrate.cache_creation_secs, Some(3.0));
+    }
+}
@@ -33,7 +33,7 @@ pub(crate) struct SlidingThreadHashrate {
     pub(crate) window_900: SlidingWindow<900>,
 }
 
-/// Intended for debugging purpose, collect hashrate if run with the --report-hashrate flag.
+/// Collects hashrate of threads over the last 10, 60 and 900 seconds.
 #[derive(Clone, Debug, Default)]
 pub(crate) struct SlidingHashrateCollector {
     hashrate: SlidingHashrate,
@@ -97,7 +97,14 @@ impl<const SECS: u64> SlidingWindow<SECS> {
         let mut overall_hashes_found = 0;
         let mut overall_duration = Duration::default();
 
-        for record in self.records.iter() {
+        // records are pruned only on new ones, so they could be outdated if a thread is idle
+        let now = Instant::now();
+        let is_actual = |record: &&SlidingWindowRecord| match now.checked_sub(self.window_size) {
+            Some(window_start) => record.time >= window_start,
+            None => true,
+        };
+
+        for record in self.records.iter().take_while(is_actual) {
             overall_hashes_found += record.checked_hashes_count;
             overall_duration += record.duration;
         }
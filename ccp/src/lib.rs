@@ -27,17 +27,31 @@
 )]
 
 mod alignment_roadmap;
+pub mod commitment_tracker;
+pub mod config_reload;
 pub mod cpuids_handle;
 mod cu;
+mod disk_guard;
 mod errors;
+pub mod hashing_gate;
 mod hashrate;
+mod proof_ack;
+mod proof_archive;
+mod proof_circuit_breaker;
+mod proof_index;
+mod proof_sink;
 mod proof_storage;
+mod proof_subscription;
+pub mod proofs_handle;
 pub mod prover;
+pub mod runtime_settings;
 mod state_storage;
 pub mod status;
+mod stored_proof;
 pub(crate) mod utility_thread;
 
 pub use errors::CCProverError;
+pub use errors::CCProverErrorKind;
 pub use prover::CCProver;
 pub use prover::CCResult;
 
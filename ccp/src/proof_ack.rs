@@ -0,0 +1,87 @@
+/*
+ * Copyright 2024 Fluence Labs Limited
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::path::PathBuf;
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use ccp_shared::proof::ProofIdx;
+use ccp_shared::types::GlobalNonce;
+
+use crate::utility_thread::save_reliably;
+
+const EXPECT_DEFAULT_SERIALIZER: &str = "the default serde serializer shouldn't fail";
+
+/// All proofs of the epoch with the global nonce up to the proof idx (inclusive)
+/// are acknowledged by Nox as submitted on-chain.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub(crate) struct ProofAckWatermark {
+    pub(crate) global_nonce: GlobalNonce,
+    pub(crate) proof_idx: ProofIdx,
+}
+
+/// Keeps the last acknowledged watermark in the state dir.
+#[derive(Debug)]
+pub(crate) struct AckStorage {
+    watermark_path: PathBuf,
+}
+
+impl ProofAckWatermark {
+    pub(crate) fn new(global_nonce: GlobalNonce, proof_idx: ProofIdx) -> Self {
+        Self {
+            global_nonce,
+            proof_idx,
+        }
+    }
+}
+
+impl AckStorage {
+    pub(crate) fn new(watermark_path: PathBuf) -> Self {
+        Self { watermark_path }
+    }
+
+    pub(crate) async fn save_watermark(
+        &self,
+        watermark: ProofAckWatermark,
+    ) -> tokio::io::Result<()> {
+        let data = serde_json::to_vec(&watermark).expect(EXPECT_DEFAULT_SERIALIZER);
+        let path = self.watermark_path.clone();
+
+        tokio::task::spawn_blocking(move || save_reliably(&path, data)).await??;
+        Ok(())
+    }
+
+    pub(crate) async fn try_to_load_watermark(
+        &self,
+    ) -> tokio::io::Result<Option<ProofAckWatermark>> {
+        if !tokio::fs::try_exists(&self.watermark_path).await? {
+            return Ok(None);
+        }
+
+        let data = tokio::fs::read(&self.watermark_path).await?;
+        match serde_json::from_slice(&data) {
+            Ok(watermark) => Ok(Some(watermark)),
+            Err(e) => {
+                log::warn!(
+                    "failed to parse proof ack watermark from {:?}, ignoring: {e}",
+                    self.watermark_path
+                );
+                Ok(None)
+            }
+        }
+    }
+}
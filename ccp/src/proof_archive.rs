@@ -0,0 +1,257 @@
+/*
+ * Copyright 2024 Fluence Labs Limited
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::path::Path;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use ccp_shared::proof::CCProof;
+use ccp_shared::proof::ProofIdx;
+use ccp_shared::types::GlobalNonce;
+
+use crate::proof_storage::ensure_dir;
+use crate::stored_proof::StoredProof;
+
+/// Keeps proof directories of previous epochs, so proofs which Nox hasn't fetched
+/// before an epoch switch could be still served.
+///
+/// Each epoch is stored in a subdirectory named `<sequence number>-<global nonce>`,
+/// only the last `retained_epochs` epochs are kept.
+#[derive(Debug)]
+pub(crate) struct ProofArchive {
+    archive_directory: PathBuf,
+    retained_epochs: usize,
+}
+
+#[derive(Debug)]
+struct ArchivedEpoch {
+    seq: u64,
+    global_nonce: GlobalNonce,
+    path: PathBuf,
+}
+
+impl ProofArchive {
+    pub(crate) fn new(archive_directory: PathBuf, retained_epochs: usize) -> Self {
+        Self {
+            archive_directory,
+            retained_epochs,
+        }
+    }
+
+    /// Moves the proof directory to the archive, the proof directory is removed
+    /// if archiving is disabled.
+    pub(crate) async fn archive(
+        &self,
+        proof_directory: &Path,
+        global_nonce: GlobalNonce,
+    ) -> tokio::io::Result<()> {
+        if self.retained_epochs == 0 {
+            return tokio::fs::remove_dir_all(proof_directory).await;
+        }
+
+        ensure_dir(&self.archive_directory).await?;
+        let mut epochs = self.list_epochs().await?;
+
+        // the same epoch could be archived twice, e.g. after a restart, keep the latest one
+        for epoch in epochs.iter().filter(|e| e.global_nonce == global_nonce) {
+            tokio::fs::remove_dir_all(&epoch.path).await?;
+        }
+        epochs.retain(|e| e.global_nonce != global_nonce);
+
+        let seq = epochs.iter().map(|e| e.seq + 1).max().unwrap_or_default();
+        let archived_path = self
+            .archive_directory
+            .join(format!("{seq:010}-{global_nonce}"));
+        tokio::fs::rename(proof_directory, &archived_path).await?;
+        log::info!("proofs of epoch {global_nonce} are archived to {archived_path:?}");
+
+        // the just archived epoch is the last one, so it's retained anyway
+        let outdated_count = (epochs.len() + 1).saturating_sub(self.retained_epochs);
+        for epoch in epochs.iter().take(outdated_count) {
+            log::info!("removing outdated proof archive {:?}", epoch.path);
+            tokio::fs::remove_dir_all(&epoch.path).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Gets at most `limit` archived proofs of the epoch with the provided global nonce,
+    /// which proof_id is strictly bigger than the provided proof id, ordered by proof id.
+    pub(crate) async fn get_proofs_after(
+        &self,
+        global_nonce: GlobalNonce,
+        proof_idx: ProofIdx,
+        limit: usize,
+    ) -> tokio::io::Result<Vec<CCProof>> {
+        let epochs = self.list_epochs().await?;
+        let epoch = match epochs.iter().find(|e| e.global_nonce == global_nonce) {
+            Some(epoch) => epoch,
+            None => return Ok(vec![]),
+        };
+
+        let mut proofs = Vec::new();
+        let mut directory = tokio::fs::read_dir(&epoch.path).await?;
+        while let Some(entry) = directory.next_entry().await? {
+            let entry_proof_idx = entry
+                .file_name()
+                .to_str()
+                .and_then(|name| ProofIdx::from_str(name).ok());
+            if !matches!(entry_proof_idx, Some(entry_proof_idx) if entry_proof_idx > proof_idx) {
+                continue;
+            }
+
+            let file_content = tokio::fs::read(entry.path()).await?;
+            match StoredProof::deserialize(&file_content) {
+                Ok(proof) => proofs.push(proof),
+                Err(e) => {
+                    log::warn!(
+                        "failed to parse archived proof file {:?}: {e}, ignoring",
+                        entry.path()
+                    );
+                }
+            }
+        }
+
+        proofs.sort_unstable_by_key(|proof| proof.id.idx);
+        proofs.truncate(limit);
+        Ok(proofs)
+    }
+
+    /// Returns archived epochs ordered by their sequence number.
+    async fn list_epochs(&self) -> tokio::io::Result<Vec<ArchivedEpoch>> {
+        if !tokio::fs::try_exists(&self.archive_directory).await? {
+            return Ok(vec![]);
+        }
+
+        let mut epochs = Vec::new();
+        let mut directory = tokio::fs::read_dir(&self.archive_directory).await?;
+        while let Some(entry) = directory.next_entry().await? {
+            if !entry.file_type().await?.is_dir() {
+                continue;
+            }
+
+            let file_name = entry.file_name();
+            let parsed = file_name
+                .to_str()
+                .and_then(|name| name.split_once('-'))
+                .and_then(|(seq, global_nonce)| {
+                    Some((
+                        u64::from_str(seq).ok()?,
+                        GlobalNonce::from_str(global_nonce).ok()?,
+                    ))
+                });
+
+            match parsed {
+                Some((seq, global_nonce)) => epochs.push(ArchivedEpoch {
+                    seq,
+                    global_nonce,
+                    path: entry.path(),
+                }),
+                None => {
+                    log::warn!("unexpected entry in the proof archive: {file_name:?}, ignoring")
+                }
+            }
+        }
+
+        epochs.sort_unstable_by_key(|e| e.seq);
+        Ok(epochs)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use ccp_test_utils::test_values::*;
+
+    use super::ProofArchive;
+
+    async fn store_proofs(proof_directory: &Path, global_nonce_seed: u8, count: u64) {
+        tokio::fs::create_dir(proof_directory).await.unwrap();
+        for idx in 0..count {
+            let proof = generate_dummy_proof(global_nonce_seed, idx);
+            let path = proof_directory.join(idx.to_string());
+            tokio::fs::write(path, serde_json::to_vec(&proof).unwrap())
+                .await
+                .unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn archived_proofs_are_served() {
+        let state_dir = tempdir::TempDir::new("state").unwrap();
+        let proof_directory = state_dir.path().join("proofs");
+        let archive = ProofArchive::new(state_dir.path().join("archive"), 2);
+
+        store_proofs(&proof_directory, 1, 5).await;
+        archive
+            .archive(&proof_directory, generate_global_nonce(1))
+            .await
+            .unwrap();
+        assert!(!proof_directory.exists());
+
+        let proofs = archive
+            .get_proofs_after(generate_global_nonce(1), "1".parse().unwrap(), 2)
+            .await
+            .unwrap();
+        let indices = proofs
+            .iter()
+            .map(|p| p.id.idx.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(indices, vec!["2", "3"]);
+
+        let proofs = archive
+            .get_proofs_after(generate_global_nonce(2), "0".parse().unwrap(), usize::MAX)
+            .await
+            .unwrap();
+        assert!(proofs.is_empty());
+    }
+
+    #[tokio::test]
+    async fn only_last_epochs_are_retained() {
+        let state_dir = tempdir::TempDir::new("state").unwrap();
+        let proof_directory = state_dir.path().join("proofs");
+        let archive = ProofArchive::new(state_dir.path().join("archive"), 2);
+
+        for seed in 1..=3 {
+            store_proofs(&proof_directory, seed, 2).await;
+            archive
+                .archive(&proof_directory, generate_global_nonce(seed))
+                .await
+                .unwrap();
+        }
+
+        let start_idx = "0".parse().unwrap();
+        let first_epoch = archive
+            .get_proofs_after(generate_global_nonce(1), start_idx, usize::MAX)
+            .await
+            .unwrap();
+        assert!(first_epoch.is_empty());
+
+        for seed in 2..=3 {
+            let proofs = archive
+                .get_proofs_after(generate_global_nonce(seed), start_idx, usize::MAX)
+                .await
+                .unwrap();
+            assert_eq!(proofs.len(), 1);
+        }
+
+        let epoch_dirs = std::fs::read_dir(state_dir.path().join("archive"))
+            .unwrap()
+            .count();
+        assert_eq!(epoch_dirs, 2);
+    }
+}
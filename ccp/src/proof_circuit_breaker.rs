@@ -0,0 +1,203 @@
+/*
+ * Copyright 2024 Fluence Labs Limited
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::Instant;
+
+use parking_lot::Mutex;
+use prometheus_client::metrics::counter::Counter;
+use prometheus_client::metrics::gauge::Gauge;
+use prometheus_client::registry::Registry;
+
+use ccp_config::Proofs;
+use ccp_shared::types::CUID;
+
+use crate::status::ProofCircuitStatus;
+use crate::status::ProofCircuitTripReason;
+
+const RATE_WINDOW: Duration = Duration::from_secs(60);
+
+/// Stops persisting proofs until the next epoch, if they are found dangerously often,
+/// e.g. because of a misconfigured difficulty, so that the disk isn't filled with proofs.
+#[derive(Clone, Debug)]
+pub(crate) struct ProofCircuitBreaker {
+    state: Arc<Mutex<BreakerState>>,
+    max_proofs_per_cu_per_minute: u64,
+    max_proofs_per_epoch: u64,
+    tripped_gauge: Gauge,
+    dropped_proofs: Counter,
+}
+
+#[derive(Debug)]
+struct BreakerState {
+    status: ProofCircuitStatus,
+    epoch_proofs: u64,
+    /// Start of the current rate window and the number of proofs found in it per CU.
+    cu_windows: HashMap<CUID, (Instant, u64)>,
+}
+
+impl ProofCircuitBreaker {
+    pub(crate) fn new(config: &Proofs) -> Self {
+        let state = BreakerState {
+            status: ProofCircuitStatus::Closed,
+            epoch_proofs: 0,
+            cu_windows: HashMap::new(),
+        };
+
+        Self {
+            state: Arc::new(Mutex::new(state)),
+            max_proofs_per_cu_per_minute: config.max_proofs_per_cu_per_minute,
+            max_proofs_per_epoch: config.max_proofs_per_epoch,
+            tripped_gauge: Gauge::default(),
+            dropped_proofs: Counter::default(),
+        }
+    }
+
+    /// Accounts a found proof and returns true if it could be persisted.
+    pub(crate) fn allow(&self, cu_id: CUID) -> bool {
+        let mut state = self.state.lock();
+        if let ProofCircuitStatus::Tripped { .. } = state.status {
+            self.dropped_proofs.inc();
+            return false;
+        }
+
+        let now = Instant::now();
+        let (window_start, window_proofs) = state.cu_windows.entry(cu_id).or_insert((now, 0));
+        if now.duration_since(*window_start) >= RATE_WINDOW {
+            *window_start = now;
+            *window_proofs = 0;
+        }
+        *window_proofs += 1;
+
+        let reason = if *window_proofs > self.max_proofs_per_cu_per_minute {
+            ProofCircuitTripReason::CURateExceeded {
+                cu_id,
+                proofs_per_minute: self.max_proofs_per_cu_per_minute,
+            }
+        } else if state.epoch_proofs >= self.max_proofs_per_epoch {
+            ProofCircuitTripReason::EpochLimitExceeded {
+                proofs: self.max_proofs_per_epoch,
+            }
+        } else {
+            state.epoch_proofs += 1;
+            return true;
+        };
+
+        log::error!(
+            "proof circuit breaker tripped: {reason}, further proofs won't be persisted \
+             until the next epoch, please check the difficulty"
+        );
+        state.status = ProofCircuitStatus::Tripped { reason };
+        self.tripped_gauge.set(1);
+        self.dropped_proofs.inc();
+        false
+    }
+
+    /// Closes the circuit, it's intended to be called on a new epoch.
+    pub(crate) fn reset(&self) {
+        let mut state = self.state.lock();
+        if let ProofCircuitStatus::Tripped { .. } = state.status {
+            log::info!("proof circuit breaker is reset on a new epoch");
+        }
+
+        state.status = ProofCircuitStatus::Closed;
+        state.epoch_proofs = 0;
+        state.cu_windows.clear();
+        self.tripped_gauge.set(0);
+    }
+
+    pub(crate) fn status(&self) -> ProofCircuitStatus {
+        self.state.lock().status
+    }
+
+    pub(crate) fn apply_to_registry(&self, registry: &mut Registry) {
+        registry.register(
+            "proof_circuit_tripped",
+            "Whether proofs aren't persisted because they are found too often",
+            self.tripped_gauge.clone(),
+        );
+        registry.register(
+            "dropped_proofs",
+            "Found proofs which weren't persisted by the proof circuit breaker",
+            self.dropped_proofs.clone(),
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ccp_config::Proofs;
+    use ccp_test_utils::test_values::generate_cu_id;
+
+    use super::ProofCircuitBreaker;
+    use crate::status::ProofCircuitStatus;
+    use crate::status::ProofCircuitTripReason;
+
+    fn breaker(
+        max_proofs_per_cu_per_minute: u64,
+        max_proofs_per_epoch: u64,
+    ) -> ProofCircuitBreaker {
+        let config = Proofs {
+            max_proofs_per_cu_per_minute,
+            max_proofs_per_epoch,
+            ..<_>::default()
+        };
+        ProofCircuitBreaker::new(&config)
+    }
+
+    #[test]
+    fn trips_on_cu_rate() {
+        let breaker = breaker(2, 100);
+        assert!(breaker.allow(generate_cu_id(1)));
+        assert!(breaker.allow(generate_cu_id(1)));
+        assert!(breaker.allow(generate_cu_id(2)));
+        assert!(!breaker.allow(generate_cu_id(1)));
+        // the circuit is tripped for all CUs
+        assert!(!breaker.allow(generate_cu_id(2)));
+
+        let expected_reason = ProofCircuitTripReason::CURateExceeded {
+            cu_id: generate_cu_id(1),
+            proofs_per_minute: 2,
+        };
+        assert_eq!(
+            breaker.status(),
+            ProofCircuitStatus::Tripped {
+                reason: expected_reason
+            }
+        );
+    }
+
+    #[test]
+    fn trips_on_epoch_limit_until_reset() {
+        let breaker = breaker(100, 3);
+        for cu in 0..3 {
+            assert!(breaker.allow(generate_cu_id(cu)));
+        }
+        assert!(!breaker.allow(generate_cu_id(3)));
+        assert_eq!(
+            breaker.status(),
+            ProofCircuitStatus::Tripped {
+                reason: ProofCircuitTripReason::EpochLimitExceeded { proofs: 3 }
+            }
+        );
+
+        breaker.reset();
+        assert_eq!(breaker.status(), ProofCircuitStatus::Closed);
+        assert!(breaker.allow(generate_cu_id(3)));
+    }
+}
@@ -0,0 +1,161 @@
+/*
+ * Copyright 2024 Fluence Labs Limited
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::collections::BTreeMap;
+use std::ops::Bound;
+use std::sync::Arc;
+
+use parking_lot::RwLock;
+use tokio::sync::broadcast;
+
+use ccp_shared::proof::CCProof;
+use ccp_shared::proof::ProofIdx;
+
+/// How many new proofs could be buffered for a slow subscriber before it lags.
+const NEW_PROOFS_CHANNEL_CAPACITY: usize = 1024;
+
+/// An ordered in-memory mirror of the proof directory, it's shared between
+/// the utility thread, which stores new proofs, and the proof drainer,
+/// which serves them.
+#[derive(Clone, Debug)]
+pub(crate) struct ProofIndex {
+    proofs: Arc<RwLock<BTreeMap<ProofIdx, CCProof>>>,
+    /// Each inserted proof is also broadcasted to subscribers.
+    new_proofs: broadcast::Sender<CCProof>,
+}
+
+impl ProofIndex {
+    pub(crate) fn new() -> Self {
+        let (new_proofs, _) = broadcast::channel(NEW_PROOFS_CHANNEL_CAPACITY);
+        Self {
+            proofs: <_>::default(),
+            new_proofs,
+        }
+    }
+
+    pub(crate) fn insert(&self, proof: CCProof) {
+        {
+            let mut guard = self.proofs.write();
+            guard.insert(proof.id.idx, proof);
+        }
+        // an error means there are no subscribers at the moment
+        let _ = self.new_proofs.send(proof);
+    }
+
+    /// Subscribes to proofs inserted after this call.
+    pub(crate) fn subscribe(&self) -> broadcast::Receiver<CCProof> {
+        self.new_proofs.subscribe()
+    }
+
+    /// Returns all proofs ordered by proof idx.
+    pub(crate) fn get_all(&self) -> Vec<CCProof> {
+        let guard = self.proofs.read();
+        guard.values().copied().collect()
+    }
+
+    /// Returns at most `limit` proofs which proof idx is strictly bigger than
+    /// the provided one, ordered by proof idx.
+    pub(crate) fn get_after(&self, proof_idx: ProofIdx, limit: usize) -> Vec<CCProof> {
+        let guard = self.proofs.read();
+        guard
+            .range((Bound::Excluded(proof_idx), Bound::Unbounded))
+            .take(limit)
+            .map(|(_, proof)| *proof)
+            .collect()
+    }
+
+    /// Removes proofs which proof idx is less or equal to the provided one,
+    /// returns indices of the removed proofs.
+    pub(crate) fn remove_up_to(&self, proof_idx: ProofIdx) -> Vec<ProofIdx> {
+        let mut guard = self.proofs.write();
+        let retained = guard.split_off(&proof_idx);
+
+        let mut removed = std::mem::replace(&mut *guard, retained);
+        if let Some(proof) = guard.remove(&proof_idx) {
+            removed.insert(proof_idx, proof);
+        }
+
+        removed.into_keys().collect()
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        let guard = self.proofs.read();
+        guard.is_empty()
+    }
+
+    pub(crate) fn clear(&self) {
+        let mut guard = self.proofs.write();
+        guard.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ccp_shared::proof::ProofIdx;
+    use ccp_test_utils::test_values::*;
+
+    use super::ProofIndex;
+
+    #[test]
+    fn get_after_is_ordered_and_limited() {
+        let index = ProofIndex::new();
+        for idx in [3, 0, 4, 1, 2] {
+            index.insert(generate_dummy_proof(1, idx));
+        }
+
+        let proofs = index.get_after(generate_dummy_proof(1, 0).id.idx, 2);
+
+        assert_eq!(
+            proofs,
+            vec![generate_dummy_proof(1, 1), generate_dummy_proof(1, 2)]
+        );
+    }
+
+    #[test]
+    fn remove_up_to_is_inclusive() {
+        let index = ProofIndex::new();
+        for idx in 0..5 {
+            index.insert(generate_dummy_proof(1, idx));
+        }
+
+        let removed = index.remove_up_to(generate_dummy_proof(1, 2).id.idx);
+        let proofs = index.get_after(ProofIdx::zero(), usize::MAX);
+
+        assert_eq!(
+            removed,
+            vec![
+                generate_dummy_proof(1, 0).id.idx,
+                generate_dummy_proof(1, 1).id.idx,
+                generate_dummy_proof(1, 2).id.idx
+            ]
+        );
+        assert_eq!(
+            proofs,
+            vec![generate_dummy_proof(1, 3), generate_dummy_proof(1, 4)]
+        );
+    }
+
+    #[test]
+    fn clear_removes_all_proofs() {
+        let index = ProofIndex::new();
+        index.insert(generate_dummy_proof(1, 1));
+        index.clear();
+
+        let proofs = index.get_after(ProofIdx::zero(), usize::MAX);
+
+        assert!(proofs.is_empty());
+    }
+}
@@ -0,0 +1,46 @@
+/*
+ * Copyright 2024 Fluence Labs Limited
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use thiserror::Error as ThisError;
+use tokio::task::JoinError;
+
+#[derive(ThisError, Debug)]
+pub enum ProofSinkError {
+    #[error("invalid webhook url {url}: {error}")]
+    InvalidUrl {
+        url: String,
+        #[source]
+        error: hyper::http::uri::InvalidUri,
+    },
+
+    #[error(transparent)]
+    HttpError(#[from] hyper::Error),
+
+    #[error("webhook responded with unexpected status {0}")]
+    UnexpectedStatus(hyper::StatusCode),
+
+    #[error("the webhook outbox is full, {entries} proofs aren't delivered yet")]
+    OutboxFull { entries: usize },
+
+    #[error("free disk space is critically low")]
+    LowDiskSpace,
+
+    #[error(transparent)]
+    IOError(#[from] std::io::Error),
+
+    #[error(transparent)]
+    JoinError(#[from] JoinError),
+}
@@ -0,0 +1,86 @@
+/*
+ * Copyright 2024 Fluence Labs Limited
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::path::PathBuf;
+
+use tokio::io::AsyncWriteExt;
+
+use ccp_shared::proof::CCProof;
+
+use super::PSResult;
+use super::ProofSink;
+
+const EXPECT_DEFAULT_SERIALIZER: &str = "the default serde serializer shouldn't fail";
+
+/// Appends each proof as a JSON line to a file, e.g. for audit purposes.
+#[derive(Debug)]
+pub(crate) struct JsonLinesSink {
+    path: PathBuf,
+}
+
+impl JsonLinesSink {
+    pub(crate) fn new(path: PathBuf) -> Self {
+        Self { path }
+    }
+}
+
+#[async_trait::async_trait]
+impl ProofSink for JsonLinesSink {
+    async fn on_new_proof(&self, proof: &CCProof) -> PSResult<()> {
+        let mut line = serde_json::to_vec(proof).expect(EXPECT_DEFAULT_SERIALIZER);
+        line.push(b'\n');
+
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .await?;
+        file.write_all(&line).await?;
+        file.sync_data().await?;
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ccp_shared::proof::CCProof;
+    use ccp_test_utils::test_values::*;
+
+    use super::JsonLinesSink;
+    use crate::proof_sink::ProofSink;
+
+    #[tokio::test]
+    async fn proofs_are_appended_as_lines() {
+        let dir = tempdir::TempDir::new("export").unwrap();
+        let path = dir.path().join("proofs.jsonl");
+        let sink = JsonLinesSink::new(path.clone());
+
+        let proofs = (0..3u64)
+            .map(|idx| generate_dummy_proof(1, idx))
+            .collect::<Vec<_>>();
+        for proof in &proofs {
+            sink.on_new_proof(proof).await.unwrap();
+        }
+
+        let exported = std::fs::read_to_string(path).unwrap();
+        let exported = exported
+            .lines()
+            .map(|line| serde_json::from_str::<CCProof>(line).unwrap())
+            .collect::<Vec<_>>();
+        assert_eq!(exported, proofs);
+    }
+}
@@ -0,0 +1,103 @@
+/*
+ * Copyright 2024 Fluence Labs Limited
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+mod errors;
+mod json_lines;
+mod webhook;
+
+use std::path::Path;
+
+use ccp_config::ProofSinkConfig;
+use ccp_shared::proof::CCProof;
+use prometheus_client::metrics::counter::Counter;
+use prometheus_client::registry::Registry;
+
+use crate::disk_guard::DiskSpaceGuard;
+
+pub use errors::ProofSinkError;
+pub(crate) use json_lines::JsonLinesSink;
+pub(crate) use webhook::WebhookSink;
+
+pub(crate) type PSResult<T> = Result<T, ProofSinkError>;
+
+const PROOF_OUTBOX_DIR: &str = "proof_outbox";
+
+/// A destination where found proofs are sent after they are stored in the proof directory.
+///
+/// It's called by the utility thread, so implementations shouldn't block for long,
+/// e.g. network delivery should be done in background.
+#[async_trait::async_trait]
+pub(crate) trait ProofSink: Send + Sync {
+    async fn on_new_proof(&self, proof: &CCProof) -> PSResult<()>;
+}
+
+/// Counters of proofs, which webhooks gave up on; proofs are still kept in the proof storage.
+#[derive(Clone, Debug, Default)]
+pub(crate) struct ProofSinkMetrics {
+    rejected_proofs: Counter,
+    dropped_proofs: Counter,
+}
+
+impl ProofSinkMetrics {
+    pub(crate) fn apply_to_registry(&self, registry: &mut Registry) {
+        registry.register(
+            "webhook_rejected_proofs",
+            "Proofs rejected by a webhook with a client error, they aren't retried",
+            self.rejected_proofs.clone(),
+        );
+        registry.register(
+            "webhook_dropped_proofs",
+            "Proofs which weren't queued for a webhook because of a full outbox or low disk space",
+            self.dropped_proofs.clone(),
+        );
+    }
+}
+
+pub(crate) fn create_sinks(
+    sink_configs: &[ProofSinkConfig],
+    state_dir: &Path,
+    disk_space_guard: &DiskSpaceGuard,
+    metrics: &ProofSinkMetrics,
+) -> PSResult<Vec<Box<dyn ProofSink>>> {
+    sink_configs
+        .iter()
+        .map(|sink_config| -> PSResult<Box<dyn ProofSink>> {
+            match sink_config {
+                ProofSinkConfig::Webhook {
+                    url,
+                    max_retries,
+                    retry_interval,
+                    max_outbox_entries,
+                } => {
+                    let outbox_dir = state_dir.join(PROOF_OUTBOX_DIR);
+                    let sink = WebhookSink::new(
+                        url,
+                        &outbox_dir,
+                        *max_retries,
+                        *retry_interval,
+                        *max_outbox_entries,
+                        disk_space_guard.clone(),
+                        metrics.clone(),
+                    )?;
+                    Ok(Box::new(sink))
+                }
+                ProofSinkConfig::JsonLines { path } => {
+                    Ok(Box::new(JsonLinesSink::new(path.clone())))
+                }
+            }
+        })
+        .collect()
+}
@@ -0,0 +1,473 @@
+/*
+ * Copyright 2024 Fluence Labs Limited
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::ffi::OsStr;
+use std::path::Path;
+use std::path::PathBuf;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+use std::time::Duration;
+use std::time::SystemTime;
+
+use hyper::client::HttpConnector;
+use hyper::Body;
+use hyper::Client;
+use hyper::Request;
+use hyper::StatusCode;
+use hyper::Uri;
+use hyper_rustls::HttpsConnector;
+use tokio::sync::Notify;
+use tokio_util::sync::CancellationToken;
+
+use ccp_shared::proof::CCProof;
+
+use super::PSResult;
+use super::ProofSink;
+use super::ProofSinkError;
+use super::ProofSinkMetrics;
+use crate::disk_guard::DiskSpaceGuard;
+use crate::proof_storage::ensure_dir;
+use crate::status::DiskSpaceLevel;
+use crate::utility_thread::save_reliably;
+
+const EXPECT_DEFAULT_SERIALIZER: &str = "the default serde serializer shouldn't fail";
+
+/// POSTs each proof as JSON to a webhook.
+///
+/// A proof is first saved to an on-disk outbox and then delivered in background,
+/// so proofs survive both webhook outages and CCP restarts.
+/// The outbox shares the disk with the proof storage, so it's capped and isn't written
+/// when free disk space is critically low.
+pub(crate) struct WebhookSink {
+    outbox_dir: PathBuf,
+    max_outbox_entries: usize,
+    /// Number of entries in the outbox, shared with the delivery task.
+    outbox_entries: Arc<AtomicUsize>,
+    disk_space_guard: DiskSpaceGuard,
+    metrics: ProofSinkMetrics,
+    new_proof_notify: Arc<Notify>,
+    cancellation: CancellationToken,
+}
+
+struct WebhookDelivery {
+    client: Client<HttpsConnector<HttpConnector>>,
+    url: Uri,
+    outbox_dir: PathBuf,
+    outbox_entries: Arc<AtomicUsize>,
+    max_retries: u32,
+    retry_interval: Duration,
+    metrics: ProofSinkMetrics,
+}
+
+impl WebhookSink {
+    pub(crate) fn new(
+        url: &str,
+        outbox_root: &Path,
+        max_retries: u32,
+        retry_interval: Duration,
+        max_outbox_entries: usize,
+        disk_space_guard: DiskSpaceGuard,
+        metrics: ProofSinkMetrics,
+    ) -> PSResult<Self> {
+        use sha3::Digest;
+
+        let parsed_url = url
+            .parse::<Uri>()
+            .map_err(|error| ProofSinkError::InvalidUrl {
+                url: url.to_string(),
+                error,
+            })?;
+
+        // each webhook has its own outbox
+        let url_hash = hex::encode(sha3::Sha3_256::digest(url.as_bytes()));
+        let outbox_dir = outbox_root.join(&url_hash[..16]);
+        let outbox_entries = Arc::new(AtomicUsize::new(count_outbox_entries(&outbox_dir)?));
+
+        let delivery = WebhookDelivery {
+            client: webhook_client(),
+            url: parsed_url,
+            outbox_dir: outbox_dir.clone(),
+            outbox_entries: outbox_entries.clone(),
+            max_retries,
+            retry_interval,
+            metrics: metrics.clone(),
+        };
+
+        let new_proof_notify = Arc::new(Notify::new());
+        let cancellation = CancellationToken::new();
+        tokio::spawn(delivery.delivery_loop(new_proof_notify.clone(), cancellation.clone()));
+
+        Ok(Self {
+            outbox_dir,
+            max_outbox_entries,
+            outbox_entries,
+            disk_space_guard,
+            metrics,
+            new_proof_notify,
+            cancellation,
+        })
+    }
+}
+
+impl Drop for WebhookSink {
+    fn drop(&mut self) {
+        self.cancellation.cancel();
+    }
+}
+
+#[async_trait::async_trait]
+impl ProofSink for WebhookSink {
+    async fn on_new_proof(&self, proof: &CCProof) -> PSResult<()> {
+        if self.disk_space_guard.check_before_write() == DiskSpaceLevel::Critical {
+            self.metrics.dropped_proofs.inc();
+            return Err(ProofSinkError::LowDiskSpace);
+        }
+        let entries = self.outbox_entries.load(Ordering::Acquire);
+        if entries >= self.max_outbox_entries {
+            self.metrics.dropped_proofs.inc();
+            return Err(ProofSinkError::OutboxFull { entries });
+        }
+
+        ensure_dir(&self.outbox_dir).await?;
+
+        let entry_path = self
+            .outbox_dir
+            .join(outbox_entry_name(proof, SystemTime::now()));
+        let contents = serde_json::to_vec(proof).expect(EXPECT_DEFAULT_SERIALIZER);
+        // counted beforehand, so the delivery task can't uncount the entry before it's counted
+        self.outbox_entries.fetch_add(1, Ordering::AcqRel);
+        let saved = tokio::task::spawn_blocking(move || save_reliably(&entry_path, contents)).await;
+        if !matches!(saved, Ok(Ok(()))) {
+            uncount_outbox_entry(&self.outbox_entries);
+        }
+        saved??;
+
+        self.new_proof_notify.notify_one();
+        Ok(())
+    }
+}
+
+/// Entries are named after the time they were queued at, so sorting names gives the order
+/// proofs were found in, also across epochs.
+fn outbox_entry_name(proof: &CCProof, queued_at: SystemTime) -> String {
+    let queued_at = queued_at
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_nanos();
+
+    // proof idx is reset on each epoch, so global nonce makes the name unique
+    format!(
+        "{queued_at:0>20}-{}-{:0>20}",
+        proof.id.global_nonce,
+        proof.id.idx.to_string()
+    )
+}
+
+fn webhook_client() -> Client<HttpsConnector<HttpConnector>> {
+    let connector = hyper_rustls::HttpsConnectorBuilder::new()
+        .with_native_roots()
+        .https_or_http()
+        .enable_http1()
+        .build();
+    Client::builder().build(connector)
+}
+
+/// Draft files, which are being written by `save_reliably`, have a random suffix
+/// after the entry name, so they aren't entries yet.
+fn is_outbox_entry(file_name: &OsStr) -> bool {
+    file_name
+        .to_str()
+        .and_then(|name| name.rsplit('-').next())
+        .is_some_and(|idx| idx.len() == 20 && idx.bytes().all(|b| b.is_ascii_digit()))
+}
+
+fn count_outbox_entries(outbox_dir: &Path) -> std::io::Result<usize> {
+    let directory = match std::fs::read_dir(outbox_dir) {
+        Ok(directory) => directory,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(0),
+        Err(e) => return Err(e),
+    };
+
+    let mut count = 0;
+    for entry in directory {
+        let entry = entry?;
+        if entry.file_type()?.is_file() && is_outbox_entry(&entry.file_name()) {
+            count += 1;
+        }
+    }
+    Ok(count)
+}
+
+fn uncount_outbox_entry(outbox_entries: &AtomicUsize) {
+    // an entry could be saved despite an error, so it may be uncounted twice
+    let _ = outbox_entries.fetch_update(Ordering::AcqRel, Ordering::Acquire, |entries| {
+        Some(entries.saturating_sub(1))
+    });
+}
+
+/// A client error means that the proof itself isn't accepted, so it isn't retried,
+/// unless requests are just rate limited.
+fn is_rejected(error: &ProofSinkError) -> bool {
+    matches!(
+        error,
+        ProofSinkError::UnexpectedStatus(status)
+            if status.is_client_error() && *status != StatusCode::TOO_MANY_REQUESTS
+    )
+}
+
+impl WebhookDelivery {
+    async fn delivery_loop(self, new_proof_notify: Arc<Notify>, cancellation: CancellationToken) {
+        loop {
+            tokio::select! {
+                result = self.deliver_outbox() => {
+                    if let Err(error) = result {
+                        log::warn!("failed to deliver proofs to the webhook {}: {error}", self.url);
+                    }
+                },
+                _ = cancellation.cancelled() => return,
+            }
+
+            // undelivered proofs are retried periodically even if there are no new ones
+            tokio::select! {
+                _ = new_proof_notify.notified() => {},
+                _ = tokio::time::sleep(self.backoff(self.max_retries)) => {},
+                _ = cancellation.cancelled() => return,
+            }
+        }
+    }
+
+    /// Delivers proofs from the outbox in order, stops on the first undelivered one.
+    /// Proofs rejected by the webhook are dropped, so they don't block later ones.
+    async fn deliver_outbox(&self) -> PSResult<()> {
+        if !tokio::fs::try_exists(&self.outbox_dir).await? {
+            return Ok(());
+        }
+
+        let mut entries = Vec::new();
+        let mut directory = tokio::fs::read_dir(&self.outbox_dir).await?;
+        while let Some(entry) = directory.next_entry().await? {
+            if entry.file_type().await?.is_file() && is_outbox_entry(&entry.file_name()) {
+                entries.push(entry.path());
+            }
+        }
+        entries.sort_unstable();
+
+        for entry_path in entries {
+            let body = tokio::fs::read(&entry_path).await?;
+            match self.post_with_retries(body).await {
+                Ok(()) => {}
+                Err(error) if is_rejected(&error) => {
+                    log::error!(
+                        "webhook {} rejected the proof {entry_path:?}, dropping it: {error}",
+                        self.url
+                    );
+                    self.metrics.rejected_proofs.inc();
+                }
+                Err(error) => return Err(error),
+            }
+            tokio::fs::remove_file(&entry_path).await?;
+            uncount_outbox_entry(&self.outbox_entries);
+        }
+
+        Ok(())
+    }
+
+    async fn post_with_retries(&self, body: Vec<u8>) -> PSResult<()> {
+        let mut retries = 0;
+        loop {
+            match self.post(body.clone()).await {
+                Ok(()) => return Ok(()),
+                Err(error) if retries < self.max_retries && !is_rejected(&error) => {
+                    log::debug!("webhook {} request failed: {error}, retrying", self.url);
+                    tokio::time::sleep(self.backoff(retries)).await;
+                    retries += 1;
+                }
+                Err(error) => return Err(error),
+            }
+        }
+    }
+
+    /// Doubles the retry interval on each retry up to 1024 times the configured one.
+    fn backoff(&self, retries: u32) -> Duration {
+        self.retry_interval.saturating_mul(1 << retries.min(10))
+    }
+
+    async fn post(&self, body: Vec<u8>) -> PSResult<()> {
+        let request = Request::post(self.url.clone())
+            .header(hyper::header::CONTENT_TYPE, "application/json")
+            .body(Body::from(body))
+            .expect("the webhook request is built from a valid url");
+
+        let response = self.client.request(request).await?;
+        if !response.status().is_success() {
+            return Err(ProofSinkError::UnexpectedStatus(response.status()));
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::atomic::AtomicUsize;
+    use std::sync::Arc;
+    use std::sync::Mutex;
+    use std::time::Duration;
+    use std::time::SystemTime;
+
+    use axum::extract::State;
+    use axum::http::StatusCode;
+    use axum::Json;
+    use ccp_config::DiskGuard;
+    use ccp_shared::proof::CCProof;
+    use ccp_test_utils::test_values::*;
+
+    use super::*;
+    use crate::hashing_gate::HashingGate;
+
+    fn disk_space_guard(state_dir: &Path, critical_free_space_bytes: u64) -> DiskSpaceGuard {
+        let config = DiskGuard {
+            check_interval: Duration::from_secs(60),
+            warn_free_space_bytes: 0,
+            critical_free_space_bytes,
+            critical_policy: <_>::default(),
+        };
+        DiskSpaceGuard::new(state_dir.to_path_buf(), config, HashingGate::default())
+    }
+
+    fn sink(
+        outbox_root: &Path,
+        max_outbox_entries: usize,
+        critical_free_space_bytes: u64,
+    ) -> WebhookSink {
+        // nothing listens there, a retry happens only in an hour
+        WebhookSink::new(
+            "http://127.0.0.1:1/proofs",
+            outbox_root,
+            1,
+            Duration::from_secs(3600),
+            max_outbox_entries,
+            disk_space_guard(outbox_root, critical_free_space_bytes),
+            ProofSinkMetrics::default(),
+        )
+        .unwrap()
+    }
+
+    /// Accepts proofs except the ones with zero idx, which are rejected as invalid.
+    async fn handle_proof(
+        State(received): State<Arc<Mutex<Vec<CCProof>>>>,
+        Json(proof): Json<CCProof>,
+    ) -> StatusCode {
+        if proof.id.idx == "0".parse().unwrap() {
+            return StatusCode::UNPROCESSABLE_ENTITY;
+        }
+        received.lock().unwrap().push(proof);
+        StatusCode::OK
+    }
+
+    #[tokio::test]
+    async fn rejected_proofs_dont_block_delivery() {
+        let received = Arc::new(Mutex::new(vec![]));
+        let app = axum::Router::new()
+            .route("/proofs", axum::routing::post(handle_proof))
+            .with_state(received.clone());
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/proofs", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        let outbox_dir = tempdir::TempDir::new("outbox").unwrap();
+        let queued_at = SystemTime::now();
+        for idx in 0..2 {
+            let proof = generate_dummy_proof(1, idx);
+            let entry_path = outbox_dir.path().join(outbox_entry_name(
+                &proof,
+                queued_at + Duration::from_nanos(idx),
+            ));
+            std::fs::write(entry_path, serde_json::to_vec(&proof).unwrap()).unwrap();
+        }
+
+        let metrics = ProofSinkMetrics::default();
+        let delivery = WebhookDelivery {
+            client: webhook_client(),
+            url: url.parse().unwrap(),
+            outbox_dir: outbox_dir.path().to_path_buf(),
+            outbox_entries: Arc::new(AtomicUsize::new(2)),
+            max_retries: 3,
+            retry_interval: Duration::from_secs(3600),
+            metrics: metrics.clone(),
+        };
+        delivery.deliver_outbox().await.unwrap();
+
+        assert_eq!(*received.lock().unwrap(), vec![generate_dummy_proof(1, 1)]);
+        assert_eq!(metrics.rejected_proofs.get(), 1);
+        assert_eq!(delivery.outbox_entries.load(Ordering::Acquire), 0);
+        assert_eq!(count_outbox_entries(outbox_dir.path()).unwrap(), 0);
+    }
+
+    #[tokio::test]
+    async fn outbox_is_capped() {
+        let outbox_root = tempdir::TempDir::new("outbox").unwrap();
+        let sink = sink(outbox_root.path(), 2, 0);
+
+        for idx in 0..2 {
+            sink.on_new_proof(&generate_dummy_proof(1, idx))
+                .await
+                .unwrap();
+        }
+        let result = sink.on_new_proof(&generate_dummy_proof(1, 2)).await;
+
+        assert!(matches!(
+            result,
+            Err(ProofSinkError::OutboxFull { entries: 2 })
+        ));
+        assert_eq!(sink.metrics.dropped_proofs.get(), 1);
+        assert_eq!(count_outbox_entries(&sink.outbox_dir).unwrap(), 2);
+    }
+
+    #[tokio::test]
+    async fn outbox_isnt_written_on_low_disk_space() {
+        let outbox_root = tempdir::TempDir::new("outbox").unwrap();
+        let sink = sink(outbox_root.path(), 2, u64::MAX);
+
+        let result = sink.on_new_proof(&generate_dummy_proof(1, 0)).await;
+
+        assert!(matches!(result, Err(ProofSinkError::LowDiskSpace)));
+        assert_eq!(sink.metrics.dropped_proofs.get(), 1);
+        assert_eq!(count_outbox_entries(&sink.outbox_dir).unwrap(), 0);
+    }
+
+    #[test]
+    fn outbox_entries_are_ordered_chronologically() {
+        let queued_at = SystemTime::UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let entries = [
+            outbox_entry_name(&generate_dummy_proof(9, 2), queued_at),
+            outbox_entry_name(
+                &generate_dummy_proof(9, 10),
+                queued_at + Duration::from_nanos(1),
+            ),
+            // a new epoch with a smaller global nonce
+            outbox_entry_name(
+                &generate_dummy_proof(1, 0),
+                queued_at + Duration::from_secs(1),
+            ),
+        ];
+
+        let mut sorted_entries = entries.clone();
+        sorted_entries.sort_unstable();
+        assert_eq!(sorted_entries, entries);
+    }
+}
@@ -15,93 +15,232 @@
  */
 
 use ccp_shared::types::EpochParameters;
+use ccp_shared::types::GlobalNonce;
+use parking_lot::Mutex;
+use prometheus_client::metrics::counter::Counter;
 use std::path::PathBuf;
 use tokio::fs::DirEntry;
 
 use ccp_shared::proof::CCProof;
 use ccp_shared::proof::ProofIdx;
 
+use crate::proof_ack::AckStorage;
+use crate::proof_ack::ProofAckWatermark;
+use crate::proof_archive::ProofArchive;
+use crate::proof_index::ProofIndex;
+use crate::proof_subscription::ProofStream;
+use crate::stored_proof::StoredProof;
+
 #[derive(Debug)]
 pub(crate) struct ProofStorageDrainer {
     /// Path to a directory containing found proofs.
     proof_directory: PathBuf,
+    /// Unreadable proof files are moved to a subdirectory named by the epoch global nonce,
+    /// it's outside of the proof directory, so the files survive archiving for inspection.
+    quarantine_directory: PathBuf,
+    /// In-memory mirror of the proof directory.
+    proof_index: ProofIndex,
+    ack_storage: AckStorage,
+    /// The last watermark acknowledged by Nox.
+    ack_watermark: Mutex<Option<ProofAckWatermark>>,
+    proof_archive: ProofArchive,
+    /// Global nonce of the epoch which proofs are in the proof directory.
+    current_global_nonce: Mutex<Option<GlobalNonce>>,
+    /// Serializes acknowledgements with epoch changes, so an acknowledgement
+    /// of the previous epoch can't remove proofs of a new one.
+    epoch_lock: tokio::sync::Mutex<()>,
+    /// Number of proof files moved to quarantine.
+    quarantined_proofs: Counter,
 }
 
 impl ProofStorageDrainer {
-    pub fn new(proof_directory: PathBuf) -> Self {
-        Self { proof_directory }
+    pub fn new(
+        proof_directory: PathBuf,
+        quarantine_directory: PathBuf,
+        ack_watermark_path: PathBuf,
+        proof_archive: ProofArchive,
+    ) -> Self {
+        Self {
+            proof_directory,
+            quarantine_directory,
+            proof_index: ProofIndex::new(),
+            ack_storage: AckStorage::new(ack_watermark_path),
+            ack_watermark: Mutex::new(None),
+            proof_archive,
+            current_global_nonce: Mutex::new(None),
+            epoch_lock: tokio::sync::Mutex::new(()),
+            quarantined_proofs: Counter::default(),
+        }
     }
 
-    /// Removes all proofs in the proof directory, it's intended for cleanup storage
-    /// when a new epoch happened.
-    pub async fn remove_proofs(&self) -> tokio::io::Result<()> {
+    /// Returns a counter of proof files moved to quarantine, it's exposed as a metric.
+    pub fn quarantined_proofs(&self) -> Counter {
+        self.quarantined_proofs.clone()
+    }
+
+    /// Returns a handle to the proof index, new proofs should be put there
+    /// along with storing them in the proof directory.
+    pub fn proof_index(&self) -> ProofIndex {
+        self.proof_index.clone()
+    }
+
+    /// Moves proofs of the previous epoch from the proof directory to the archive,
+    /// it's intended to be called when a new epoch happened.
+    pub async fn archive_proofs(&self, new_global_nonce: GlobalNonce) -> tokio::io::Result<()> {
+        let _epoch_guard = self.epoch_lock.lock().await;
+        let prev_global_nonce = self.current_global_nonce.lock().replace(new_global_nonce);
+        if prev_global_nonce == Some(new_global_nonce) {
+            // proofs in the directory belong to the new epoch, e.g. CCP was restarted
+            return Ok(());
+        }
+
         if tokio::fs::try_exists(&self.proof_directory).await? {
-            tokio::fs::remove_dir_all(&self.proof_directory).await?;
+            match prev_global_nonce {
+                Some(global_nonce) if !self.proof_index.is_empty() => {
+                    self.proof_archive
+                        .archive(&self.proof_directory, global_nonce)
+                        .await?;
+                }
+                _ => tokio::fs::remove_dir_all(&self.proof_directory).await?,
+            }
         }
+        self.proof_index.clear();
+
         tokio::fs::create_dir(&self.proof_directory).await
     }
 
-    /// Gets proofs from the proof directory, which proof_id is strictly bigger than
-    /// the provided proof id.
-    pub async fn get_proofs_after(&self, proof_idx: ProofIdx) -> tokio::io::Result<Vec<CCProof>> {
-        let mut proofs = Vec::new();
-
-        ensure_dir(&self.proof_directory).await?;
+    /// Gets at most `limit` proofs, which proof_id is strictly bigger than
+    /// the provided proof id, ordered by proof id.
+    ///
+    /// Proofs of the current epoch are returned if global nonce isn't specified,
+    /// otherwise proofs of the specified epoch are looked up in the archive.
+    pub async fn get_proofs_after(
+        &self,
+        proof_idx: ProofIdx,
+        limit: usize,
+        global_nonce: Option<GlobalNonce>,
+    ) -> tokio::io::Result<Vec<CCProof>> {
+        let current_global_nonce = *self.current_global_nonce.lock();
+        match global_nonce {
+            Some(global_nonce) if Some(global_nonce) != current_global_nonce => {
+                self.proof_archive
+                    .get_proofs_after(global_nonce, proof_idx, limit)
+                    .await
+            }
+            _ => Ok(self.proof_index.get_after(proof_idx, limit)),
+        }
+    }
 
-        let mut directory = tokio::fs::read_dir(&self.proof_directory).await?;
-        loop {
-            match directory.next_entry().await {
-                Ok(Some(entry)) => {
-                    if !Self::is_file_suitable(&entry, proof_idx).await? {
-                        continue;
-                    }
+    /// Streams proofs of the current epoch after the provided proof idx,
+    /// including ones which will be found later.
+    pub fn subscribe_proofs(&self, from_idx: ProofIdx) -> ProofStream {
+        let current_global_nonce = *self.current_global_nonce.lock();
+        crate::proof_subscription::subscribe(self.proof_index(), current_global_nonce, from_idx)
+    }
 
-                    let file_content = tokio::fs::read(entry.path()).await?;
-                    let proof: CCProof = match serde_json::from_slice(&file_content) {
-                        Ok(proof) => proof,
-                        Err(e) => {
-                            log::warn!(
-                                "failed to parse proof file {:?}: {}, ignoring",
-                                entry.path(),
-                                e
-                            );
-                            continue;
-                        }
-                    };
+    /// Records that all proofs of the epoch up to the provided proof idx (inclusive)
+    /// are submitted on-chain and removes them from the storage.
+    /// Acknowledgements of other epochs are ignored.
+    pub async fn ack_proofs(
+        &self,
+        global_nonce: GlobalNonce,
+        proof_idx: ProofIdx,
+    ) -> tokio::io::Result<()> {
+        let _epoch_guard = self.epoch_lock.lock().await;
+        if *self.current_global_nonce.lock() != Some(global_nonce) {
+            log::warn!("ignoring acknowledgement of proofs for non-current epoch {global_nonce}");
+            return Ok(());
+        }
 
-                    proofs.push(proof);
-                }
-                Ok(None) => {
-                    return Ok(proofs);
+        let watermark = ProofAckWatermark::new(global_nonce, proof_idx);
+        {
+            let guard = self.ack_watermark.lock();
+            if let Some(prev_watermark) = *guard {
+                if prev_watermark.global_nonce == global_nonce
+                    && prev_watermark.proof_idx >= proof_idx
+                {
+                    return Ok(());
                 }
-                Err(e) => return Err(e),
             }
         }
+
+        self.ack_storage.save_watermark(watermark).await?;
+        *self.ack_watermark.lock() = Some(watermark);
+
+        for acked_proof_idx in self.proof_index.remove_up_to(proof_idx) {
+            self.remove_proof_file(acked_proof_idx).await?;
+        }
+
+        Ok(())
     }
 
+    async fn remove_proof_file(&self, proof_idx: ProofIdx) -> tokio::io::Result<()> {
+        let path = self.proof_directory.join(proof_idx.to_string());
+        match tokio::fs::remove_file(&path).await {
+            Ok(()) => Ok(()),
+            Err(e) => match e.kind() {
+                std::io::ErrorKind::NotFound => Ok(()),
+                _ => Err(e),
+            },
+        }
+    }
+
+    /// Loads proofs of the provided epoch from the proof directory into the index,
+    /// removes proofs of other epochs and already acknowledged ones.
+    /// Unreadable proof files are moved to quarantine, so they don't prevent CCP from starting.
     pub async fn validate_proofs(
         &mut self,
         epoch: &Option<EpochParameters>,
     ) -> tokio::io::Result<ProofIdx> {
         let mut max_proof_idx = None;
         ensure_dir(&self.proof_directory).await?;
+        *self.current_global_nonce.lock() = epoch.map(|e| e.global_nonce);
+
+        let ack_watermark = self
+            .ack_storage
+            .try_to_load_watermark()
+            .await?
+            .filter(|watermark| Some(watermark.global_nonce) == epoch.map(|e| e.global_nonce));
+        *self.ack_watermark.lock() = ack_watermark;
+        if let Some(watermark) = ack_watermark {
+            // indices of acknowledged proofs mustn't be reused
+            max_proof_idx = Some(watermark.proof_idx);
+        }
 
         let mut directory = tokio::fs::read_dir(&self.proof_directory).await?;
         loop {
             match directory.next_entry().await {
                 Ok(Some(entry)) => {
                     if let Some(entry_proof_id) = Self::proof_idx_from_filename(&entry).await? {
-                        let file_content = tokio::fs::read(entry.path()).await?;
-                        let proof: CCProof = serde_json::from_slice(&file_content)?;
+                        let proof = match Self::read_proof(&entry).await {
+                            Some(proof) => proof,
+                            None => {
+                                // the file may belong to the current epoch, so its idx
+                                // mustn't be handed out again
+                                max_proof_idx = Some(std::cmp::max(
+                                    max_proof_idx.unwrap_or_default(),
+                                    entry_proof_id,
+                                ));
+                                self.quarantine(&entry).await?;
+                                continue;
+                            }
+                        };
 
                         log::debug!("loaded proof {entry_proof_id}: {proof:?}");
 
                         let found_epoch: EpochParameters = proof.id.into();
-                        if &Some(found_epoch) == epoch {
+                        let is_acked = ack_watermark
+                            .map(|watermark| entry_proof_id <= watermark.proof_idx)
+                            .unwrap_or(false);
+                        if &Some(found_epoch) == epoch && is_acked {
+                            // acknowledgement was interrupted before the file was removed
+                            tokio::fs::remove_file(entry.path()).await?;
+                        } else if &Some(found_epoch) == epoch {
                             max_proof_idx = Some(std::cmp::max(
                                 max_proof_idx.unwrap_or_default(),
                                 entry_proof_id,
                             ));
+                            self.proof_index.insert(proof);
                         } else {
                             let path = entry.path();
                             log::warn!("removing a proof file with wrong epoch: {path:?}");
@@ -123,6 +262,48 @@ impl ProofStorageDrainer {
         }
     }
 
+    async fn read_proof(entry: &DirEntry) -> Option<CCProof> {
+        let path = entry.path();
+        let file_content = tokio::fs::read(&path)
+            .await
+            .inspect_err(|e| log::warn!("failed to read a proof file {path:?}: {e}"))
+            .ok()?;
+
+        StoredProof::deserialize(&file_content)
+            .inspect_err(|e| log::warn!("proof file {path:?} is corrupted: {e}"))
+            .ok()
+    }
+
+    async fn quarantine(&self, entry: &DirEntry) -> tokio::io::Result<()> {
+        let epoch_dir = match *self.current_global_nonce.lock() {
+            Some(global_nonce) => global_nonce.to_string(),
+            None => "unknown-epoch".to_string(),
+        };
+        let quarantine_dir = self.quarantine_directory.join(epoch_dir);
+        tokio::fs::create_dir_all(&quarantine_dir).await?;
+
+        // proof indices are reused across epochs and restarts,
+        // so previously quarantined files get a suffix instead of being overwritten
+        let file_name = entry.file_name();
+        let mut quarantined_path = quarantine_dir.join(&file_name);
+        let mut suffix = 0u64;
+        while tokio::fs::try_exists(&quarantined_path).await? {
+            suffix += 1;
+            let mut suffixed_name = file_name.clone();
+            suffixed_name.push(format!(".{suffix}"));
+            quarantined_path = quarantine_dir.join(suffixed_name);
+        }
+
+        log::warn!(
+            "moving the proof file {:?} to quarantine {quarantined_path:?}",
+            entry.path()
+        );
+        tokio::fs::rename(entry.path(), quarantined_path).await?;
+        self.quarantined_proofs.inc();
+
+        Ok(())
+    }
+
     async fn proof_idx_from_filename(entry: &DirEntry) -> tokio::io::Result<Option<ProofIdx>> {
         use std::str::FromStr;
 
@@ -150,13 +331,6 @@ impl ProofStorageDrainer {
             }
         }
     }
-
-    async fn is_file_suitable(entry: &DirEntry, proof_idx: ProofIdx) -> tokio::io::Result<bool> {
-        let entry_proof_idx = Self::proof_idx_from_filename(entry).await?;
-        Ok(entry_proof_idx
-            .map(|current_proof_idx| proof_idx < current_proof_idx)
-            .unwrap_or(false))
-    }
 }
 
 pub(crate) async fn ensure_dir(path: &PathBuf) -> tokio::io::Result<()> {
@@ -168,3 +342,109 @@ pub(crate) async fn ensure_dir(path: &PathBuf) -> tokio::io::Result<()> {
         },
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use ccp_test_utils::test_values::*;
+
+    use super::ProofStorageDrainer;
+    use crate::proof_archive::ProofArchive;
+    use crate::stored_proof::StoredProof;
+
+    fn drainer(state_dir: &Path) -> ProofStorageDrainer {
+        ProofStorageDrainer::new(
+            state_dir.join("proofs"),
+            state_dir.join("quarantine"),
+            state_dir.join("ack_watermark.json"),
+            ProofArchive::new(state_dir.join("archive"), 2),
+        )
+    }
+
+    async fn store_proofs(proof_directory: &Path, count: u64) {
+        tokio::fs::create_dir_all(proof_directory).await.unwrap();
+        for idx in 0..count {
+            let proof = StoredProof::serialize(generate_dummy_proof(1, idx));
+            tokio::fs::write(proof_directory.join(idx.to_string()), proof)
+                .await
+                .unwrap();
+        }
+    }
+
+    #[tokio::test]
+    async fn quarantined_proof_idx_is_not_reused() {
+        let state_dir = tempdir::TempDir::new("state").unwrap();
+        let proof_directory = state_dir.path().join("proofs");
+        store_proofs(&proof_directory, 2).await;
+        tokio::fs::write(proof_directory.join("2"), b"corrupted")
+            .await
+            .unwrap();
+
+        let mut drainer = drainer(state_dir.path());
+        let epoch = Some(generate_epoch_params(1, 1));
+        let start_proof_idx = drainer.validate_proofs(&epoch).await.unwrap();
+
+        assert_eq!(start_proof_idx, "3".parse().unwrap());
+        assert_eq!(drainer.quarantined_proofs().get(), 1);
+        assert_eq!(drainer.proof_index().get_all().len(), 2);
+    }
+
+    #[tokio::test]
+    async fn quarantined_files_are_not_overwritten() {
+        let state_dir = tempdir::TempDir::new("state").unwrap();
+        let proof_directory = state_dir.path().join("proofs");
+        let epoch = Some(generate_epoch_params(1, 1));
+
+        for content in ["first", "second"] {
+            tokio::fs::create_dir_all(&proof_directory).await.unwrap();
+            tokio::fs::write(proof_directory.join("0"), content)
+                .await
+                .unwrap();
+            drainer(state_dir.path())
+                .validate_proofs(&epoch)
+                .await
+                .unwrap();
+        }
+
+        let quarantine_dir = state_dir
+            .path()
+            .join("quarantine")
+            .join(generate_global_nonce(1).to_string());
+        let first = tokio::fs::read_to_string(quarantine_dir.join("0"))
+            .await
+            .unwrap();
+        let second = tokio::fs::read_to_string(quarantine_dir.join("0.1"))
+            .await
+            .unwrap();
+        assert_eq!(first, "first");
+        assert_eq!(second, "second");
+    }
+
+    #[tokio::test]
+    async fn quarantined_files_survive_epoch_switch() {
+        let state_dir = tempdir::TempDir::new("state").unwrap();
+        let proof_directory = state_dir.path().join("proofs");
+        tokio::fs::create_dir_all(&proof_directory).await.unwrap();
+        tokio::fs::write(proof_directory.join("0"), b"corrupted")
+            .await
+            .unwrap();
+
+        let mut drainer = drainer(state_dir.path());
+        let epoch = generate_epoch_params(1, 1);
+        drainer.validate_proofs(&Some(epoch)).await.unwrap();
+        // the index is empty, so the proof directory is removed instead of being archived
+        drainer
+            .archive_proofs(generate_global_nonce(2))
+            .await
+            .unwrap();
+
+        let quarantined_path = state_dir
+            .path()
+            .join("quarantine")
+            .join(epoch.global_nonce.to_string())
+            .join("0");
+        let content = tokio::fs::read(quarantined_path).await.unwrap();
+        assert_eq!(content, b"corrupted");
+    }
+}
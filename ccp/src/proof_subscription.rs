@@ -0,0 +1,186 @@
+/*
+ * Copyright 2024 Fluence Labs Limited
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::collections::HashSet;
+use std::collections::VecDeque;
+
+use futures::stream::BoxStream;
+use futures::StreamExt;
+use tokio::sync::broadcast;
+
+use ccp_shared::proof::CCProof;
+use ccp_shared::proof::ProofIdx;
+use ccp_shared::types::GlobalNonce;
+
+use crate::proof_index::ProofIndex;
+
+/// A stream of proofs, which first replays already stored proofs and
+/// then yields new ones as soon as they are stored by the utility thread.
+pub(crate) type ProofStream = BoxStream<'static, CCProof>;
+
+struct ProofSubscription {
+    proof_index: ProofIndex,
+    new_proofs: broadcast::Receiver<CCProof>,
+    pending: VecDeque<CCProof>,
+    /// Global nonce and idx of the last yielded proof, used to skip duplicates
+    /// after replaying and to detect epoch switches, when proof idx starts from zero again.
+    last_global_nonce: Option<GlobalNonce>,
+    last_proof_idx: ProofIdx,
+    /// Epochs don't come back, so proofs of a previously seen epoch are outdated,
+    /// e.g. ones broadcast before an epoch switch, which happened during replaying.
+    seen_global_nonces: HashSet<GlobalNonce>,
+}
+
+/// Creates a stream of proofs of the current epoch after the provided proof idx.
+pub(crate) fn subscribe(
+    proof_index: ProofIndex,
+    current_global_nonce: Option<GlobalNonce>,
+    from_idx: ProofIdx,
+) -> ProofStream {
+    // subscribe before replaying to not miss proofs stored in between
+    let new_proofs = proof_index.subscribe();
+    subscribe_with(proof_index, new_proofs, current_global_nonce, from_idx)
+}
+
+fn subscribe_with(
+    proof_index: ProofIndex,
+    new_proofs: broadcast::Receiver<CCProof>,
+    current_global_nonce: Option<GlobalNonce>,
+    from_idx: ProofIdx,
+) -> ProofStream {
+    let pending = proof_index.get_after(from_idx, usize::MAX).into();
+
+    let subscription = ProofSubscription {
+        proof_index,
+        new_proofs,
+        pending,
+        last_global_nonce: current_global_nonce,
+        last_proof_idx: from_idx,
+        seen_global_nonces: current_global_nonce.into_iter().collect(),
+    };
+
+    futures::stream::unfold(subscription, |mut subscription| async move {
+        let proof = subscription.next_proof().await?;
+        Some((proof, subscription))
+    })
+    .boxed()
+}
+
+impl ProofSubscription {
+    async fn next_proof(&mut self) -> Option<CCProof> {
+        loop {
+            if let Some(proof) = self.pending.pop_front() {
+                if self.is_new(&proof) {
+                    self.last_global_nonce = Some(proof.id.global_nonce);
+                    self.last_proof_idx = proof.id.idx;
+                    self.seen_global_nonces.insert(proof.id.global_nonce);
+                    return Some(proof);
+                }
+                continue;
+            }
+
+            match self.new_proofs.recv().await {
+                Ok(proof) => self.pending.push_back(proof),
+                Err(broadcast::error::RecvError::Lagged(skipped)) => {
+                    log::warn!(
+                        "proof subscriber lagged behind by {skipped} proofs, replaying them"
+                    );
+                    self.pending.extend(self.proof_index.get_all());
+                }
+                Err(broadcast::error::RecvError::Closed) => return None,
+            }
+        }
+    }
+
+    fn is_new(&self, proof: &CCProof) -> bool {
+        match self.last_global_nonce {
+            Some(global_nonce) if global_nonce != proof.id.global_nonce => {
+                !self.seen_global_nonces.contains(&proof.id.global_nonce)
+            }
+            _ => proof.id.idx > self.last_proof_idx,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use futures::StreamExt;
+
+    use ccp_test_utils::test_values::*;
+
+    use super::subscribe;
+    use super::subscribe_with;
+    use crate::proof_index::ProofIndex;
+
+    #[tokio::test]
+    async fn stored_proofs_are_replayed_then_new_ones_streamed() {
+        let index = ProofIndex::new();
+        for idx in 0..3 {
+            index.insert(generate_dummy_proof(1, idx));
+        }
+
+        let mut proofs = subscribe(
+            index.clone(),
+            Some(generate_global_nonce(1)),
+            "0".parse().unwrap(),
+        );
+        index.insert(generate_dummy_proof(1, 3));
+        // a new epoch starts proof indices from zero
+        index.insert(generate_dummy_proof(2, 0));
+
+        let mut received = Vec::new();
+        for _ in 0..4 {
+            received.push(proofs.next().await.unwrap());
+        }
+        assert_eq!(
+            received,
+            vec![
+                generate_dummy_proof(1, 1),
+                generate_dummy_proof(1, 2),
+                generate_dummy_proof(1, 3),
+                generate_dummy_proof(2, 0)
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn proofs_of_previous_epoch_are_dropped_after_switch() {
+        let index = ProofIndex::new();
+        let new_proofs = index.subscribe();
+        // the epoch is switched after subscribing, but before replaying
+        index.insert(generate_dummy_proof(1, 1));
+        index.clear();
+        index.insert(generate_dummy_proof(2, 0));
+        index.insert(generate_dummy_proof(2, 1));
+
+        let mut proofs = subscribe_with(
+            index.clone(),
+            new_proofs,
+            Some(generate_global_nonce(1)),
+            "0".parse().unwrap(),
+        );
+        index.insert(generate_dummy_proof(2, 2));
+
+        let mut received = Vec::new();
+        for _ in 0..2 {
+            received.push(proofs.next().await.unwrap());
+        }
+        assert_eq!(
+            received,
+            vec![generate_dummy_proof(2, 1), generate_dummy_proof(2, 2)]
+        );
+    }
+}
@@ -0,0 +1,142 @@
+/*
+ * Copyright 2024 Fluence Labs Limited
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::sync::Arc;
+
+use ccp_shared::proof::CCProof;
+use ccp_shared::proof::ProofIdx;
+use ccp_shared::types::GlobalNonce;
+use futures::stream::BoxStream;
+
+use crate::proof_storage::ProofStorageDrainer;
+use crate::prover::CCResult;
+
+/// Serves found proofs independently of the prover, so they stay readable
+/// while the prover is locked by an alignment, e.g. generating datasets.
+#[derive(Clone)]
+pub struct ProofsHandle(Arc<ProofsHandleInner>);
+
+struct ProofsHandleInner {
+    drainer: ProofStorageDrainer,
+}
+
+impl ProofsHandle {
+    pub(crate) fn new(drainer: ProofStorageDrainer) -> Self {
+        Self(Arc::new(ProofsHandleInner { drainer }))
+    }
+
+    pub(crate) fn drainer(&self) -> &ProofStorageDrainer {
+        &self.0.drainer
+    }
+
+    pub async fn get_proofs_after(
+        &self,
+        proof_idx: ProofIdx,
+        limit: usize,
+        global_nonce: Option<GlobalNonce>,
+    ) -> CCResult<Vec<CCProof>> {
+        let proofs = self
+            .0
+            .drainer
+            .get_proofs_after(proof_idx, limit, global_nonce)
+            .await?;
+        Ok(proofs)
+    }
+
+    pub fn subscribe_proofs(&self, from_idx: ProofIdx) -> BoxStream<'static, CCProof> {
+        self.0.drainer.subscribe_proofs(from_idx)
+    }
+
+    /// Acknowledgements of epochs other than the one in the proof directory are ignored.
+    pub async fn ack_proofs(&self, global_nonce: GlobalNonce, proof_idx: ProofIdx) -> CCResult<()> {
+        self.0
+            .drainer
+            .ack_proofs(global_nonce, proof_idx)
+            .await
+            .map_err(Into::into)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ccp_test_utils::test_values::*;
+
+    use super::*;
+    use crate::proof_archive::ProofArchive;
+
+    fn handle(state_dir: &tempdir::TempDir) -> ProofsHandle {
+        let drainer = ProofStorageDrainer::new(
+            state_dir.path().join("proofs"),
+            state_dir.path().join("quarantine"),
+            state_dir.path().join("proofs_ack.json"),
+            ProofArchive::new(state_dir.path().join("archive"), 1),
+        );
+        ProofsHandle::new(drainer)
+    }
+
+    fn indices(proofs: &[CCProof]) -> Vec<String> {
+        proofs.iter().map(|p| p.id.idx.to_string()).collect()
+    }
+
+    #[tokio::test]
+    async fn ack_of_previous_epoch_keeps_new_proofs() {
+        let state_dir = tempdir::TempDir::new("state").unwrap();
+        let handle = handle(&state_dir);
+        let old_global_nonce = generate_global_nonce(1);
+        let new_global_nonce = generate_global_nonce(2);
+        let proof_index = handle.drainer().proof_index();
+
+        handle
+            .drainer()
+            .archive_proofs(old_global_nonce)
+            .await
+            .unwrap();
+        for idx in 0..3 {
+            proof_index.insert(generate_dummy_proof(1, idx));
+        }
+
+        // an acknowledgement of the previous epoch arrives while the new one is being applied
+        let (archived, acked) = tokio::join!(
+            handle.drainer().archive_proofs(new_global_nonce),
+            handle.ack_proofs(old_global_nonce, "1".parse().unwrap()),
+        );
+        archived.unwrap();
+        acked.unwrap();
+        for idx in 0..3 {
+            proof_index.insert(generate_dummy_proof(2, idx));
+        }
+
+        handle
+            .ack_proofs(old_global_nonce, "1".parse().unwrap())
+            .await
+            .unwrap();
+        let proofs = handle
+            .get_proofs_after(ProofIdx::zero(), usize::MAX, None)
+            .await
+            .unwrap();
+        assert_eq!(indices(&proofs), vec!["1", "2"]);
+
+        handle
+            .ack_proofs(new_global_nonce, "1".parse().unwrap())
+            .await
+            .unwrap();
+        let proofs = handle
+            .get_proofs_after(ProofIdx::zero(), usize::MAX, None)
+            .await
+            .unwrap();
+        assert_eq!(indices(&proofs), vec!["2"]);
+    }
+}
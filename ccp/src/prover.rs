@@ -18,6 +18,7 @@
 mod tests;
 
 use futures::future;
+use futures::stream::BoxStream;
 use futures::FutureExt;
 use std::collections::HashMap;
 use std::sync::Arc;
@@ -26,29 +27,57 @@ use std::sync::Mutex;
 use ccp_config::CCPConfig;
 use ccp_msr::state::MSRState;
 use ccp_msr::{MSREnforce, MSRModeEnforcer};
+use ccp_randomx::RandomXFlags;
+use ccp_shared::alignment_plan::AlignmentPlan;
+use ccp_shared::commitment::CUCommitmentPhase;
+use ccp_shared::commitment::CommitmentGeneration;
+use ccp_shared::commitment::CommitmentProgress;
+use ccp_shared::hashrate::HashrateReport;
 use ccp_shared::nox_ccp_api::NoxCCPApi;
 use ccp_shared::proof::CCProof;
 use ccp_shared::proof::ProofIdx;
+use ccp_shared::status::ProverStatus;
 use ccp_shared::types::*;
 use ccp_utils::run_utils::run_unordered;
+use ccp_verifier::ProofVerifier;
 
 use crate::alignment_roadmap::*;
+use crate::commitment_tracker::CommitmentTracker;
+use crate::config_reload::ConfigReloader;
 use crate::cpuids_handle::CpuIdsHandle;
 use crate::cu::CUProver;
 use crate::cu::CUProverConfig;
 use crate::cu::CUResult;
+use crate::disk_guard::DiskSpaceGuard;
+use crate::disk_guard::DiskSpaceMonitor;
 use crate::errors::CCProverError;
-use crate::hashrate::prometheus::PrometheusEndpoint;
+use crate::hashing_gate::HashingGate;
+use crate::hashrate::prometheus::PrometheusEndpointSlot;
+use crate::hashrate::prometheus::PrometheusMetrics;
 use crate::hashrate::HashrateCollector;
 use crate::hashrate::HashrateHandler;
+use crate::hashrate::SlidingHashrateCollector;
+use crate::proof_archive::ProofArchive;
+use crate::proof_circuit_breaker::ProofCircuitBreaker;
+use crate::proof_sink::create_sinks;
+use crate::proof_sink::ProofSinkMetrics;
 use crate::proof_storage::ProofStorageDrainer;
+use crate::proofs_handle::ProofsHandle;
+use crate::runtime_settings::RuntimeSettings;
 use crate::state_storage::CCPState;
 use crate::state_storage::StateStorage;
 use crate::status::CCStatus;
+use crate::status::DiskSpaceLevel;
+use crate::status::ProofCircuitStatus;
 use crate::status::ToCCStatus;
+use crate::utility_thread::NewProofHandler;
+use crate::utility_thread::ProofStorage;
 use crate::utility_thread::UtilityThread;
 
 const PROOF_DIR: &str = "cc_proofs";
+const PROOF_ACK_FILE: &str = "proofs_ack.json";
+const PROOF_ARCHIVE_DIR: &str = "cc_proofs_archive";
+const PROOF_QUARANTINE_DIR: &str = "cc_proofs_quarantine";
 
 pub type CCResult<T> = Result<T, CCProverError>;
 
@@ -57,11 +86,19 @@ pub struct CCProver {
     cu_prover_config: CUProverConfig,
     status: CCStatus,
     utility_thread: UtilityThread,
-    prometheus_endpoint: Option<PrometheusEndpoint>,
-    proof_drainer: ProofStorageDrainer,
+    prometheus_endpoint: PrometheusEndpointSlot,
+    proofs: ProofsHandle,
     state_storage: StateStorage,
     msr_enforcer: MSRModeEnforcer,
     utility_core_ids_handle: CpuIdsHandle,
+    proof_circuit_breaker: ProofCircuitBreaker,
+    min_difficulty_zero_bits: u32,
+    disk_space_guard: DiskSpaceGuard,
+    disk_space_monitor: DiskSpaceMonitor,
+    hashrate_collector: Arc<Mutex<HashrateCollector>>,
+    sliding_hashrate_collector: Arc<Mutex<SlidingHashrateCollector>>,
+    commitment_tracker: CommitmentTracker,
+    config_reloader: ConfigReloader,
 }
 
 impl NoxCCPApi for CCProver {
@@ -71,38 +108,93 @@ impl NoxCCPApi for CCProver {
         &mut self,
         new_epoch: EpochParameters,
         new_allocation: CUAllocation,
-    ) -> Result<(), Self::Error> {
-        let apply_resut = self
-            .apply_cc_parameters(new_epoch, &new_allocation)
-            .await
-            .inspect_err(|e| {
-                log::error!("Failed to apply parameters: {e}.  Still trying to save state.");
-            });
-        self.save_state(new_epoch, new_allocation.clone())
+    ) -> Result<CommitmentGeneration, Self::Error> {
+        let generation = self.commitment_tracker.request();
+        self.apply_commitment(generation, new_epoch, new_allocation)
+            .await?;
+
+        Ok(generation)
+    }
+
+    async fn plan_active_commitment(
+        &self,
+        new_epoch: EpochParameters,
+        new_allocation: CUAllocation,
+    ) -> Result<AlignmentPlan, Self::Error> {
+        self.plan_commitment(new_epoch, new_allocation)
+    }
+
+    async fn on_no_active_commitment(&mut self) -> Result<CommitmentGeneration, Self::Error> {
+        let generation = self.commitment_tracker.request();
+        self.apply_no_commitment(generation).await?;
+
+        Ok(generation)
+    }
+
+    async fn get_proofs_after(
+        &self,
+        proof_idx: ProofIdx,
+        limit: usize,
+        global_nonce: Option<GlobalNonce>,
+    ) -> Result<Vec<CCProof>, Self::Error> {
+        self.proofs
+            .get_proofs_after(proof_idx, limit, global_nonce)
             .await
-            .inspect_err(|e| {
-                log::error!("Failed to save state: {e}");
-            })?;
+    }
 
-        apply_resut
+    async fn subscribe_proofs(
+        &self,
+        from_idx: ProofIdx,
+    ) -> Result<BoxStream<'static, CCProof>, Self::Error> {
+        Ok(self.proofs.subscribe_proofs(from_idx))
     }
 
-    async fn on_no_active_commitment<'threads>(&'threads mut self) -> Result<(), Self::Error> {
-        self.stop_provers_nonblocking().await?;
-        self.join_provers().await?;
+    async fn ack_proofs(
+        &self,
+        global_nonce: GlobalNonce,
+        proof_idx: ProofIdx,
+    ) -> Result<(), Self::Error> {
+        self.proofs.ack_proofs(global_nonce, proof_idx).await
+    }
 
-        self.status = CCStatus::Idle;
+    async fn get_status(&self) -> Result<ProverStatus, Self::Error> {
+        let mut cu_provers = self
+            .cu_provers
+            .values()
+            .map(CUProver::detailed_status)
+            .collect::<Vec<_>>();
+        cu_provers.sort_by_key(|cu_prover| cu_prover.pinned_core_id);
+
+        let status = ProverStatus {
+            status: self.status,
+            cu_provers,
+            utility_core_ids: self.utility_core_ids_handle.get_cores(),
+            active_commitment_pending: false,
+            paused: self.cu_prover_config.hashing_gate.is_paused(),
+            proof_circuit: self.proof_circuit_status(),
+            disk_space_level: self.disk_space_level(),
+        };
+        Ok(status)
+    }
 
-        self.save_no_state().await?;
+    async fn get_hashrate(&self) -> Result<HashrateReport, Self::Error> {
+        Ok(self.hashrate_report())
+    }
 
+    async fn get_commitment_progress(&self) -> Result<CommitmentProgress, Self::Error> {
+        Ok(self.commitment_tracker.progress())
+    }
+
+    async fn pause(&self) -> Result<(), Self::Error> {
+        log::info!("hashing is paused");
+        self.cu_prover_config.hashing_gate.pause();
         Ok(())
     }
 
-    async fn get_proofs_after(&self, proof_idx: ProofIdx) -> Result<Vec<CCProof>, Self::Error> {
-        self.proof_drainer
-            .get_proofs_after(proof_idx)
-            .await
-            .map_err(Into::into)
+    async fn resume(&self) -> Result<(), Self::Error> {
+        log::info!("hashing is resumed");
+        self.cu_prover_config.hashing_gate.resume();
+        Ok(())
     }
 
     async fn realloc_utility_cores(&self, utility_core_ids: Vec<LogicalCoreId>) {
@@ -177,9 +269,16 @@ impl CCProver {
         .await?;
 
         if let Some(prev_state) = &prev_state {
-            prover
+            // restoring the saved state counts as a commitment change as well
+            let generation = prover.commitment_tracker.request();
+            prover.commitment_tracker.start(generation);
+            let result = prover
                 .apply_cc_parameters(prev_state.epoch_params, &prev_state.cu_allocation)
-                .await?;
+                .await;
+            prover
+                .commitment_tracker
+                .finish(result.as_ref().err().map(ToString::to_string));
+            result?;
         }
 
         Ok(prover)
@@ -193,52 +292,231 @@ impl CCProver {
         utility_core_ids_handle: CpuIdsHandle,
     ) -> CCResult<Self> {
         let proof_dir = config.state_dir.join(PROOF_DIR);
-        let mut proof_drainer = ProofStorageDrainer::new(proof_dir.clone());
+        let proof_ack_path = config.state_dir.join(PROOF_ACK_FILE);
+        let proof_archive = ProofArchive::new(
+            config.state_dir.join(PROOF_ARCHIVE_DIR),
+            config.proofs.archived_epochs,
+        );
+        let mut proof_drainer = ProofStorageDrainer::new(
+            proof_dir.clone(),
+            config.state_dir.join(PROOF_QUARANTINE_DIR),
+            proof_ack_path,
+            proof_archive,
+        );
         let start_proof_idx = proof_drainer.validate_proofs(&epoch).await?;
 
         log::info!("continuing from proof index {start_proof_idx}");
 
+        let hashing_gate = HashingGate::default();
+        let disk_space_guard = DiskSpaceGuard::new(
+            config.state_dir.clone(),
+            config.disk_guard,
+            hashing_gate.clone(),
+        );
+        disk_space_guard.check();
+        let disk_space_monitor = DiskSpaceMonitor::spawn(disk_space_guard.clone());
+
         let hashrate_collector = Arc::new(Mutex::new(HashrateCollector::new()));
+        let sliding_hashrate_collector = Arc::new(Mutex::new(SlidingHashrateCollector::new()));
+        let runtime_settings =
+            RuntimeSettings::new(config.logs.report_hashrate, config.workers.hashes_per_round);
         let hashrate_handler = HashrateHandler::new(
             hashrate_collector.clone(),
+            sliding_hashrate_collector.clone(),
             config.state_dir.clone(),
-            config.logs.report_hashrate,
+            runtime_settings.clone(),
+            disk_space_guard.clone(),
+        )?;
+
+        let proof_verifier = config.proofs.self_verification.then(|| {
+            // large pages are left for datasets
+            let mut flags = config.optimizations.randomx_flags;
+            flags.remove(RandomXFlags::LARGE_PAGES);
+            ProofVerifier::new(flags)
+        });
+
+        let proof_sink_metrics = ProofSinkMetrics::default();
+        let proof_sinks = create_sinks(
+            &config.proofs.sinks,
+            &config.state_dir,
+            &disk_space_guard,
+            &proof_sink_metrics,
         )?;
+        let proof_circuit_breaker = ProofCircuitBreaker::new(&config.proofs);
 
         let prev_global_nonce = epoch.map(|epoch| epoch.global_nonce);
-        let utility_thread = UtilityThread::spawn(
+        let proofs_handler = NewProofHandler::new(
+            ProofStorage::new(
+                proof_dir,
+                proof_drainer.proof_index(),
+                disk_space_guard.clone(),
+            ),
             start_proof_idx,
-            proof_dir,
             prev_global_nonce,
+            proof_verifier,
+            proof_sinks,
+            proof_circuit_breaker.clone(),
+        );
+        let utility_thread = UtilityThread::spawn(
+            proofs_handler,
             hashrate_handler,
             config.rpc_endpoint.utility_queue_size,
         );
 
-        let prometheus_endpoint = config.prometheus_endpoint.as_ref().map(|endpoint_cfg| {
-            PrometheusEndpoint::new(
-                (endpoint_cfg.host.clone(), endpoint_cfg.port),
-                hashrate_collector,
-            )
-        });
+        let prometheus_endpoint = PrometheusEndpointSlot::new(
+            config
+                .prometheus_endpoint
+                .as_ref()
+                .map(|endpoint_cfg| (endpoint_cfg.host.clone(), endpoint_cfg.port)),
+            PrometheusMetrics {
+                hashrate_collector: hashrate_collector.clone(),
+                quarantined_proofs: proof_drainer.quarantined_proofs(),
+                proof_circuit_breaker: proof_circuit_breaker.clone(),
+                disk_space_guard: disk_space_guard.clone(),
+                proof_sink_metrics,
+            },
+        );
+        let config_reloader = ConfigReloader::new(
+            config.clone(),
+            runtime_settings.clone(),
+            prometheus_endpoint.clone(),
+            utility_core_ids_handle.clone(),
+        );
 
-        let cu_prover_config = CUProverConfig::new(config.optimizations, config.workers);
+        let commitment_tracker = CommitmentTracker::default();
+        let cu_prover_config = CUProverConfig::new(
+            config.optimizations,
+            config.workers,
+            runtime_settings,
+            hashing_gate,
+            commitment_tracker.clone(),
+        );
         let prover = Self {
             cu_provers: HashMap::new(),
             cu_prover_config,
             status: CCStatus::Idle,
             utility_thread,
             prometheus_endpoint,
-            proof_drainer,
+            proofs: ProofsHandle::new(proof_drainer),
             state_storage,
             msr_enforcer,
             utility_core_ids_handle,
+            proof_circuit_breaker,
+            min_difficulty_zero_bits: config.proofs.min_difficulty_zero_bits,
+            disk_space_guard,
+            disk_space_monitor,
+            hashrate_collector,
+            sliding_hashrate_collector,
+            commitment_tracker,
+            config_reloader,
         };
 
         Ok(prover)
     }
 
+    /// Rejects epochs with a dangerously easy difficulty, when almost every hash is a proof.
+    pub fn check_epoch(&self, epoch: &EpochParameters) -> CCResult<()> {
+        let zero_bits = epoch.difficulty.leading_zero_bits();
+        if zero_bits < self.min_difficulty_zero_bits {
+            return Err(CCProverError::AbsurdDifficulty {
+                difficulty: epoch.difficulty,
+                zero_bits,
+                min_zero_bits: self.min_difficulty_zero_bits,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Makes a roadmap for the commitment against the current CU provers
+    /// and returns it without aligning with it.
+    pub fn plan_commitment(
+        &self,
+        new_epoch: EpochParameters,
+        new_allocation: CUAllocation,
+    ) -> CCResult<AlignmentPlan> {
+        self.check_epoch(&new_epoch)?;
+
+        let roadmap = CCProverAlignmentRoadmap::make(
+            new_allocation,
+            new_epoch,
+            &self.cu_provers,
+            self.status,
+        );
+
+        Ok(roadmap.to_plan())
+    }
+
+    /// Applies the commitment change with a generation assigned by the tracker,
+    /// the state is saved even if some CU provers failed.
+    pub async fn apply_commitment(
+        &mut self,
+        generation: CommitmentGeneration,
+        new_epoch: EpochParameters,
+        new_allocation: CUAllocation,
+    ) -> CCResult<()> {
+        self.commitment_tracker.start(generation);
+        let result = self.apply_and_save(new_epoch, new_allocation).await;
+        self.commitment_tracker
+            .finish(result.as_ref().err().map(ToString::to_string));
+
+        result
+    }
+
+    pub async fn apply_no_commitment(&mut self, generation: CommitmentGeneration) -> CCResult<()> {
+        self.commitment_tracker.start(generation);
+        let result = self.stop_and_save().await;
+        self.commitment_tracker
+            .finish(result.as_ref().err().map(ToString::to_string));
+
+        result
+    }
+
+    /// Returns a handle which shows how commitment changes are applied,
+    /// it doesn't need the prover to be accessible.
+    pub fn commitment_tracker(&self) -> CommitmentTracker {
+        self.commitment_tracker.clone()
+    }
+
+    /// Returns a handle which pauses and resumes hashing,
+    /// it doesn't need the prover to be accessible.
+    pub fn hashing_gate(&self) -> HashingGate {
+        self.cu_prover_config.hashing_gate.clone()
+    }
+
+    /// Returns a handle which applies a reloaded config, it doesn't need the prover to be accessible.
+    pub fn config_reloader(&self) -> ConfigReloader {
+        self.config_reloader.clone()
+    }
+
+    /// Returns a handle which serves proofs while the prover is busy with an alignment.
+    pub fn proofs(&self) -> ProofsHandle {
+        self.proofs.clone()
+    }
+
+    pub fn proof_circuit_status(&self) -> ProofCircuitStatus {
+        self.proof_circuit_breaker.status()
+    }
+
+    pub fn disk_space_level(&self) -> DiskSpaceLevel {
+        self.disk_space_guard.level()
+    }
+
+    pub fn hashrate_report(&self) -> HashrateReport {
+        let cu_threads = self.cu_provers.values().filter_map(|cu_prover| {
+            let status = cu_prover.detailed_status();
+            status.cu_id.map(|cu_id| (cu_id, status.thread_core_ids))
+        });
+
+        let collector = self.hashrate_collector.lock().unwrap();
+        let sliding_collector = self.sliding_hashrate_collector.lock().unwrap();
+        crate::hashrate::make_report(&collector, &sliding_collector, cu_threads)
+    }
+
+    /// Stops proving jobs of all CU provers, unlike `NoxCCPApi::pause`
+    /// they have to be started again with a new commitment.
     #[allow(clippy::needless_lifetimes)]
-    pub async fn pause<'provers>(&'provers mut self) -> CCResult<()> {
+    pub async fn pause_jobs<'provers>(&'provers mut self) -> CCResult<()> {
         let closure = move |_: usize, (_, prover): (&PhysicalCoreId, &'provers mut CUProver)| {
             prover.pause().boxed()
         };
@@ -255,6 +533,39 @@ impl CCProver {
         self.shutdown().await
     }
 
+    async fn apply_and_save(
+        &mut self,
+        new_epoch: EpochParameters,
+        new_allocation: CUAllocation,
+    ) -> CCResult<()> {
+        self.check_epoch(&new_epoch)?;
+
+        let apply_resut = self
+            .apply_cc_parameters(new_epoch, &new_allocation)
+            .await
+            .inspect_err(|e| {
+                log::error!("Failed to apply parameters: {e}.  Still trying to save state.");
+            });
+        self.save_state(new_epoch, new_allocation.clone())
+            .await
+            .inspect_err(|e| {
+                log::error!("Failed to save state: {e}");
+            })?;
+
+        apply_resut
+    }
+
+    async fn stop_and_save(&mut self) -> CCResult<()> {
+        self.stop_provers_nonblocking().await?;
+        self.join_provers().await?;
+
+        self.status = CCStatus::Idle;
+
+        self.save_no_state().await?;
+
+        Ok(())
+    }
+
     async fn stop_provers_nonblocking<'provers>(&'provers self) -> CCResult<()> {
         let nonblocking_closure =
             move |_: usize, (_, prover): (&PhysicalCoreId, &'provers CUProver)| {
@@ -279,9 +590,9 @@ impl CCProver {
         // TODO
         self.utility_thread.shutdown().await?;
 
-        if let Some(prometheus_endpoint) = &mut self.prometheus_endpoint {
-            prometheus_endpoint.shutdown().await?;
-        }
+        self.prometheus_endpoint.shutdown().await?;
+
+        self.disk_space_monitor.shutdown().await?;
 
         log::info!("Shutting down prover done");
         Ok(())
@@ -350,18 +661,42 @@ impl RoadmapAlignable for CCProver {
         match pre_action {
             CUProverPreAction::NoAction => {}
             CUProverPreAction::CleanupProofCache => {
-                self.pause().await?;
-                self.proof_drainer.remove_proofs().await?;
+                self.pause_jobs().await?;
+                self.proofs
+                    .drainer()
+                    .archive_proofs(epoch.global_nonce)
+                    .await?;
             }
         }
 
         let actions_as_futures = actions
             .into_iter()
-            .map(|action| match action {
-                CUProverAction::CreateCUProver(state) => self.cu_creation(state, epoch),
-                CUProverAction::RemoveCUProver(state) => self.cu_removal(state),
-                CUProverAction::NewCCJob(state) => self.new_cc_job(state, epoch),
-                CUProverAction::NewCCJobWithRepining(state) => self.new_cc_job_repin(state, epoch),
+            .map(|action| {
+                let (core_id, cu_id) = action_target(&action);
+                self.commitment_tracker.add_cu(core_id, cu_id);
+
+                let action = match action {
+                    CUProverAction::CreateCUProver(state) => self.cu_creation(state, epoch),
+                    CUProverAction::RemoveCUProver(state) => self.cu_removal(state),
+                    CUProverAction::NewCCJob(state) => self.new_cc_job(state, epoch),
+                    CUProverAction::NewCCJobWithRepining(state) => {
+                        self.new_cc_job_repin(state, epoch)
+                    }
+                };
+
+                let commitment_tracker = self.commitment_tracker.clone();
+                action.inspect(move |result| match result {
+                    Ok(AlignmentPostAction::Nothing) => {
+                        commitment_tracker.set_phase(core_id, CUCommitmentPhase::Stopped)
+                    }
+                    Ok(AlignmentPostAction::KeepProver(_)) => {}
+                    Err(e) => commitment_tracker.set_phase(
+                        core_id,
+                        CUCommitmentPhase::Failed {
+                            error: e.to_string(),
+                        },
+                    ),
+                })
             })
             .collect::<FuturesUnordered<_>>();
 
@@ -455,6 +790,16 @@ impl CCProver {
     }
 }
 
+/// Returns a core a CU prover will be pinned to after the action, and a CU it will prove.
+fn action_target(action: &CUProverAction) -> (PhysicalCoreId, Option<CUID>) {
+    match action {
+        CUProverAction::CreateCUProver(state) => (state.new_core_id, Some(state.new_cu_id)),
+        CUProverAction::RemoveCUProver(state) => (state.current_core_id, None),
+        CUProverAction::NewCCJob(state) => (state.current_core_id, Some(state.new_cu_id)),
+        CUProverAction::NewCCJobWithRepining(state) => (state.new_core_id, Some(state.new_cu_id)),
+    }
+}
+
 fn cease_prev_msr_policy(prev_state: &CCPState) {
     let msr_enforcer = MSRModeEnforcer::from_preset(true, prev_state.msr_state.msr_preset.clone());
 
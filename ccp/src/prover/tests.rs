@@ -46,6 +46,8 @@ async fn get_prover(state_dir: impl Into<PathBuf>) -> CCProver {
         logs: <_>::default(),
         state_dir,
         workers: Workers::default(),
+        proofs: <_>::default(),
+        disk_guard: <_>::default(),
     };
 
     CCProver::new(config).await.unwrap()
@@ -60,6 +62,8 @@ async fn get_prover_from_saved_state(state_dir: impl Into<PathBuf>) -> CCProver
         logs: <_>::default(),
         state_dir,
         workers: Workers::default(),
+        proofs: <_>::default(),
+        disk_guard: <_>::default(),
     };
 
     let utility_core_ids_handle = CpuIdsHandle::new(vec![2.into()]);
@@ -114,7 +118,7 @@ async fn prover_on_active_commitment() {
     tokio::time::sleep(GEN_PROOFS_DURATION).await;
 
     let proofs = prover
-        .get_proofs_after("0".parse().unwrap())
+        .get_proofs_after("0".parse().unwrap(), usize::MAX, None)
         .await
         .expect("reading proofs");
 
@@ -157,7 +161,7 @@ async fn prover_on_no_active_commitment() {
     prover.on_no_active_commitment().await.unwrap();
 
     let proofs = prover
-        .get_proofs_after("0".parse().unwrap())
+        .get_proofs_after("0".parse().unwrap(), usize::MAX, None)
         .await
         .expect("reading proofs");
     assert!(proofs.is_empty());
@@ -181,7 +185,7 @@ async fn prover_on_active_no_active_commitment() {
     tokio::time::sleep(GEN_PROOFS_DURATION).await;
 
     let proofs_before = prover
-        .get_proofs_after("0".parse().unwrap())
+        .get_proofs_after("0".parse().unwrap(), usize::MAX, None)
         .await
         .expect("reading proofs");
     assert!(!proofs_before.is_empty());
@@ -193,7 +197,7 @@ async fn prover_on_active_no_active_commitment() {
     let expected_state = None;
 
     let proofs_after = prover
-        .get_proofs_after("0".parse().unwrap())
+        .get_proofs_after("0".parse().unwrap(), usize::MAX, None)
         .await
         .expect("reading proofs");
 
@@ -240,7 +244,7 @@ async fn prover_on_active_reduce_empty_allocation_active_commitment() {
     tokio::time::sleep(GEN_PROOFS_DURATION).await;
 
     let proofs_before = prover
-        .get_proofs_after("0".parse().unwrap())
+        .get_proofs_after("0".parse().unwrap(), usize::MAX, None)
         .await
         .expect("reading proofs");
     assert!(!proofs_before.is_empty());
@@ -253,7 +257,7 @@ async fn prover_on_active_reduce_empty_allocation_active_commitment() {
         .unwrap();
 
     let proofs_after = prover
-        .get_proofs_after("0".parse().unwrap())
+        .get_proofs_after("0".parse().unwrap(), usize::MAX, None)
         .await
         .expect("reading proofs");
     assert!(proofs_after.is_empty(), "{:?}", proofs_after);
@@ -335,7 +339,7 @@ async fn prover_on_active_extend_on_active_commitment_performance() {
     tokio::time::sleep(GEN_PROOFS_DURATION).await;
 
     let proofs_before = prover
-        .get_proofs_after("0".parse().unwrap())
+        .get_proofs_after("0".parse().unwrap(), usize::MAX, None)
         .await
         .expect("reading proofs");
     assert!(!proofs_before.is_empty());
@@ -348,7 +352,7 @@ async fn prover_on_active_extend_on_active_commitment_performance() {
     tokio::time::sleep(GEN_PROOFS_DURATION).await;
 
     let proofs_after = prover
-        .get_proofs_after("0".parse().unwrap())
+        .get_proofs_after("0".parse().unwrap(), usize::MAX, None)
         .await
         .expect("reading proofs");
     assert!(
@@ -378,7 +382,7 @@ async fn prover_on_active_change_epoch() {
     tokio::time::sleep(GEN_PROOFS_DURATION).await;
 
     let proofs_before = prover
-        .get_proofs_after("0".parse().unwrap())
+        .get_proofs_after("0".parse().unwrap(), usize::MAX, None)
         .await
         .expect("reading proofs");
     assert!(!proofs_before.is_empty());
@@ -401,7 +405,7 @@ async fn prover_on_active_change_epoch() {
     tokio::time::sleep(GEN_PROOFS_DURATION).await;
 
     let proofs_after = prover
-        .get_proofs_after("0".parse().unwrap())
+        .get_proofs_after("0".parse().unwrap(), usize::MAX, None)
         .await
         .expect("reading proofs");
 
@@ -456,7 +460,7 @@ async fn prover_restore_from_state_with_no_proofs() {
     let state = load_state(state_dir.path());
 
     let proofs = prover
-        .get_proofs_after("0".parse().unwrap())
+        .get_proofs_after("0".parse().unwrap(), usize::MAX, None)
         .await
         .expect("reading proofs");
 
@@ -574,7 +578,7 @@ async fn prover_restore_from_state_with_proofs() {
     let state = load_state(state_dir.path());
 
     let proofs = prover
-        .get_proofs_after("0".parse().unwrap())
+        .get_proofs_after("0".parse().unwrap(), usize::MAX, None)
         .await
         .expect("reading proofs");
 
@@ -0,0 +1,61 @@
+/*
+ * Copyright 2024 Fluence Labs Limited
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::sync::atomic::AtomicBool;
+use std::sync::atomic::AtomicUsize;
+use std::sync::atomic::Ordering;
+use std::sync::Arc;
+
+/// Config values which could be changed while the prover is running, e.g. on a config reload,
+/// a new value is picked up the next time it's used.
+#[derive(Clone, Debug)]
+pub struct RuntimeSettings(Arc<RuntimeSettingsInner>);
+
+#[derive(Debug)]
+struct RuntimeSettingsInner {
+    report_hashrate: AtomicBool,
+    /// It's passed to proving threads with a new job.
+    hashes_per_round: AtomicUsize,
+}
+
+impl RuntimeSettings {
+    pub fn new(report_hashrate: bool, hashes_per_round: usize) -> Self {
+        Self(Arc::new(RuntimeSettingsInner {
+            report_hashrate: AtomicBool::new(report_hashrate),
+            hashes_per_round: AtomicUsize::new(hashes_per_round),
+        }))
+    }
+
+    pub fn report_hashrate(&self) -> bool {
+        self.0.report_hashrate.load(Ordering::Relaxed)
+    }
+
+    pub fn set_report_hashrate(&self, report_hashrate: bool) {
+        self.0
+            .report_hashrate
+            .store(report_hashrate, Ordering::Relaxed);
+    }
+
+    pub fn hashes_per_round(&self) -> usize {
+        self.0.hashes_per_round.load(Ordering::Relaxed)
+    }
+
+    pub fn set_hashes_per_round(&self, hashes_per_round: usize) {
+        self.0
+            .hashes_per_round
+            .store(hashes_per_round, Ordering::Relaxed);
+    }
+}
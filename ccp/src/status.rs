@@ -14,14 +14,10 @@
  * limitations under the License.
  */
 
-use ccp_shared::types::EpochParameters;
-
-/// Represents a status of a CC prover.
-#[derive(Clone, Copy, Debug, PartialEq, Eq)]
-pub enum CCStatus {
-    Running { epoch: EpochParameters },
-    Idle,
-}
+pub use ccp_shared::status::CCStatus;
+pub use ccp_shared::status::DiskSpaceLevel;
+pub use ccp_shared::status::ProofCircuitStatus;
+pub use ccp_shared::status::ProofCircuitTripReason;
 
 pub trait ToCCStatus {
     fn status(&self) -> CCStatus;
@@ -0,0 +1,125 @@
+/*
+ * Copyright 2024 Fluence Labs Limited
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use serde::Deserialize;
+use serde::Serialize;
+use thiserror::Error as ThisError;
+
+use ccp_shared::proof::CCProof;
+
+const EXPECT_DEFAULT_SERIALIZER: &str = "the default serde serializer shouldn't fail";
+
+/// A proof as it's stored in the proof directory, the checksum allows to detect
+/// corrupted files. Proof fields are flattened, so a stored proof is still readable
+/// as a plain `CCProof`, e.g. by the verifier.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub(crate) struct StoredProof {
+    #[serde(flatten)]
+    proof: CCProof,
+    /// Hex-encoded sha3-256 of the serialized proof, could be absent
+    /// for proofs stored by previous CCP versions.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    checksum: Option<String>,
+}
+
+#[derive(ThisError, Debug)]
+pub(crate) enum StoredProofError {
+    #[error("failed to parse a stored proof: {0}")]
+    ParseError(#[from] serde_json::Error),
+
+    #[error("stored proof checksum {stored} doesn't match the computed one {computed}")]
+    ChecksumMismatch { stored: String, computed: String },
+}
+
+impl StoredProof {
+    pub(crate) fn serialize(proof: CCProof) -> String {
+        let stored_proof = Self {
+            proof,
+            checksum: Some(compute_checksum(&proof)),
+        };
+        serde_json::to_string(&stored_proof).expect(EXPECT_DEFAULT_SERIALIZER)
+    }
+
+    /// Parses a stored proof and checks its checksum, proofs without checksum are accepted as is.
+    pub(crate) fn deserialize(contents: &[u8]) -> Result<CCProof, StoredProofError> {
+        let Self { proof, checksum } = serde_json::from_slice(contents)?;
+
+        if let Some(stored) = checksum {
+            let computed = compute_checksum(&proof);
+            if stored != computed {
+                return Err(StoredProofError::ChecksumMismatch { stored, computed });
+            }
+        }
+
+        Ok(proof)
+    }
+}
+
+fn compute_checksum(proof: &CCProof) -> String {
+    use sha3::Digest;
+
+    let serialized_proof = serde_json::to_vec(proof).expect(EXPECT_DEFAULT_SERIALIZER);
+    hex::encode(sha3::Sha3_256::digest(serialized_proof))
+}
+
+#[cfg(test)]
+mod tests {
+    use ccp_shared::proof::CCProof;
+    use ccp_test_utils::test_values::*;
+
+    use super::StoredProof;
+    use super::StoredProofError;
+
+    #[test]
+    fn stored_proof_roundtrip() {
+        let serialized = StoredProof::serialize(generate_dummy_proof(1, 1));
+        let deserialized = StoredProof::deserialize(serialized.as_bytes()).unwrap();
+        assert_eq!(deserialized, generate_dummy_proof(1, 1));
+
+        // stored proofs are still readable as plain proofs
+        let plain: CCProof = serde_json::from_str(&serialized).unwrap();
+        assert_eq!(plain, generate_dummy_proof(1, 1));
+    }
+
+    #[test]
+    fn proofs_without_checksum_are_accepted() {
+        let serialized = serde_json::to_vec(&generate_dummy_proof(1, 1)).unwrap();
+        let deserialized = StoredProof::deserialize(&serialized).unwrap();
+        assert_eq!(deserialized, generate_dummy_proof(1, 1));
+    }
+
+    #[test]
+    fn corrupted_proof_is_detected() {
+        let mut tampered_proof = generate_dummy_proof(1, 1);
+        tampered_proof.cu_id = generate_cu_id(2);
+        let serialized = StoredProof::serialize(generate_dummy_proof(1, 1));
+        let tampered = serialized.replace(
+            &serde_json::to_string(&generate_dummy_proof(1, 1).cu_id).unwrap(),
+            &serde_json::to_string(&tampered_proof.cu_id).unwrap(),
+        );
+        assert_ne!(serialized, tampered);
+
+        let result = StoredProof::deserialize(tampered.as_bytes());
+        assert!(matches!(
+            result,
+            Err(StoredProofError::ChecksumMismatch { .. })
+        ));
+
+        let truncated = &serialized.as_bytes()[..serialized.len() / 2];
+        let result = StoredProof::deserialize(truncated);
+        assert!(matches!(result, Err(StoredProofError::ParseError(_))));
+    }
+}
@@ -16,6 +16,7 @@
 
 use tokio::task::JoinError;
 
+use ccp_verifier::ProofVerificationError;
 use thiserror::Error as ThisError;
 
 use crate::hashrate::HashrateError;
@@ -31,6 +32,12 @@ pub enum UtilityThreadError {
     #[error(transparent)]
     HashrateError(#[from] HashrateError),
 
+    #[error("proof was dropped by the proof circuit breaker")]
+    ProofDropped,
+
+    #[error("proof was rejected by self-verification: {0}")]
+    ProofRejected(#[source] ProofVerificationError),
+
     #[error("error occurred while trying to shutdown the utility thread")]
     ShutdownError,
 }
@@ -20,6 +20,7 @@ mod thread;
 
 pub use errors::UtilityThreadError;
 pub(crate) use proof_storage::save_reliably;
+pub(crate) use proof_storage::ProofStorage;
 pub(crate) use thread::*;
 
 pub(crate) mod message {
@@ -23,27 +23,42 @@ use std::path::PathBuf;
 
 use ccp_shared::proof::CCProof;
 
+use crate::disk_guard::DiskSpaceGuard;
+use crate::proof_index::ProofIndex;
 use crate::proof_storage::ensure_dir;
-
-const EXPECT_DEFAULT_SERIALIZER: &str = "the default serde serializer shouldn't fail";
+use crate::stored_proof::StoredProof;
 
 #[derive(Debug)]
 pub struct ProofStorage {
     /// Path to a directory containing found proofs.
     proof_directory: PathBuf,
+    /// In-memory mirror of the proof directory, shared with the proof drainer.
+    proof_index: ProofIndex,
+    disk_space_guard: DiskSpaceGuard,
 }
 
 /// Intended to store proofs in storage.
 impl ProofStorage {
-    pub fn new(proof_directory: PathBuf) -> Self {
-        Self { proof_directory }
+    pub(crate) fn new(
+        proof_directory: PathBuf,
+        proof_index: ProofIndex,
+        disk_space_guard: DiskSpaceGuard,
+    ) -> Self {
+        Self {
+            proof_directory,
+            proof_index,
+            disk_space_guard,
+        }
     }
 
     pub async fn store_new_proof(&self, proof: CCProof) -> tokio::io::Result<()> {
+        // proofs are still persisted on the critical level, since they are the most valuable
+        self.disk_space_guard.check_before_write();
         ensure_dir(&self.proof_directory).await?;
-        let proof_as_string = serde_json::to_string(&proof).expect(EXPECT_DEFAULT_SERIALIZER);
+        let proof_as_string = StoredProof::serialize(proof);
         let proof_path = self.proof_directory.join(proof.id.idx.to_string());
         tokio::task::spawn_blocking(move || save_reliably(&proof_path, proof_as_string)).await??;
+        self.proof_index.insert(proof);
         Ok(())
     }
 }
@@ -14,6 +14,7 @@
  * limitations under the License.
  */
 
+use std::sync::Arc;
 use std::time::Duration;
 use tokio::sync::mpsc;
 use tokio::time;
@@ -22,11 +23,16 @@ use ccp_shared::proof::CCProof;
 use ccp_shared::proof::CCProofId;
 use ccp_shared::proof::ProofIdx;
 use ccp_shared::types::GlobalNonce;
+use ccp_verifier::ProofVerifier;
+use parking_lot::Mutex;
 use tokio_util::sync::CancellationToken;
 
 use super::message::*;
 use super::UTResult;
+use super::UtilityThreadError;
 use crate::hashrate::HashrateHandler;
+use crate::proof_circuit_breaker::ProofCircuitBreaker;
+use crate::proof_sink::ProofSink;
 use crate::utility_thread::proof_storage::ProofStorage;
 
 const CUMULATIVE_HASHRATE_UPDATE_INTERVAL: u64 = 60;
@@ -39,9 +45,7 @@ pub(crate) struct UtilityThread {
 
 impl UtilityThread {
     pub(crate) fn spawn(
-        prev_proof_idx: ProofIdx,
-        proof_storage_dir: std::path::PathBuf,
-        prev_global_nonce: Option<GlobalNonce>,
+        proofs_handler: NewProofHandler,
         hashrate_handler: HashrateHandler,
         utility_queue_size: usize,
     ) -> Self {
@@ -49,8 +53,6 @@ impl UtilityThread {
 
         let cancellation = CancellationToken::new();
 
-        let proof_storage = ProofStorage::new(proof_storage_dir);
-        let proofs_handler = NewProofHandler::new(proof_storage, prev_proof_idx, prev_global_nonce);
         let ut_impl = UtilityThreadImpl::new(
             from_utility,
             cancellation.clone(),
@@ -128,11 +130,24 @@ impl UtilityThreadImpl {
     async fn handle_to_utility_message(&mut self, message: ToUtilityMessage) {
         match message {
             ToUtilityMessage::ProofFound { core_id, proof } => {
-                if let Err(error) = self.proofs_handler.handle_found_proof(&proof).await {
-                    log::error!("failed to save proof: {error}\nfound proof {proof}");
+                match self.proofs_handler.handle_found_proof(&proof).await {
+                    Err(UtilityThreadError::ProofDropped) => {
+                        // the circuit breaker has already reported it
+                        log::debug!("{core_id}: proof was dropped by the circuit breaker");
+                        self.hashrate_handler.proof_found(core_id);
+                    }
+                    Err(error @ UtilityThreadError::ProofRejected(_)) => {
+                        log::error!("{core_id}: {error}\nfound proof {proof}");
+                        self.hashrate_handler.proof_rejected(core_id);
+                    }
+                    result => {
+                        if let Err(error) = result {
+                            log::error!("failed to save proof: {error}\nfound proof {proof}");
+                        }
+
+                        self.hashrate_handler.proof_found(core_id);
+                    }
                 }
-
-                self.hashrate_handler.proof_found(core_id);
             }
             ToUtilityMessage::ErrorHappened { core_id, error } => {
                 log::error!("{core_id}: {error}");
@@ -160,12 +175,11 @@ impl UtilityThreadImpl {
     ) {
         use crossterm::event::Event;
         use crossterm::event::KeyCode;
-        use itertools::Itertools;
 
         if let Some(Ok(event)) = maybe_event {
             if event == Event::Key(KeyCode::Enter.into()) {
-                let sliding_hashrate = self.hashrate_handler.sliding_hashrate();
-                if sliding_hashrate.is_empty() {
+                let report = self.hashrate_handler.report();
+                if report.logical_cores.is_empty() {
                     println!("no hashrate for the last 900 secs,\nCCP is either busy with initialization or idle");
                     return;
                 }
@@ -175,16 +189,13 @@ impl UtilityThreadImpl {
                     "core id", "10 secs", "60 secs", "900 secs"
                 );
 
-                for (core_id, thread_hashrate) in sliding_hashrate
-                    .iter()
-                    .sorted_by_key(|(&core_id, _)| core_id)
-                {
+                for core in report.logical_cores {
                     println!(
                         "{0: <10} | {1: <10.2} | {2: <10.2} | {3: <10.2}",
-                        core_id,
-                        thread_hashrate.window_10.compute_hashrate(),
-                        thread_hashrate.window_60.compute_hashrate(),
-                        thread_hashrate.window_900.compute_hashrate()
+                        core.core_id,
+                        core.hashrate.windows.secs_10,
+                        core.hashrate.windows.secs_60,
+                        core.hashrate.windows.secs_900
                     );
                 }
             }
@@ -195,22 +206,32 @@ impl UtilityThreadImpl {
     async fn handle_terminal_event(&mut self, _maybe_event: Option<()>) {}
 }
 
-struct NewProofHandler {
+pub(crate) struct NewProofHandler {
     proof_idx: ProofIdx,
     last_seen_global_nonce: GlobalNonce,
     proof_storage: ProofStorage,
+    // it's shared with blocking tasks, where proofs are actually verified
+    proof_verifier: Option<Arc<Mutex<ProofVerifier>>>,
+    proof_sinks: Vec<Box<dyn ProofSink>>,
+    circuit_breaker: ProofCircuitBreaker,
 }
 
 impl NewProofHandler {
-    pub(self) fn new(
+    pub(crate) fn new(
         proof_storage: ProofStorage,
         prev_proof_idx: ProofIdx,
         last_seen_global_nonce: Option<GlobalNonce>,
+        proof_verifier: Option<ProofVerifier>,
+        proof_sinks: Vec<Box<dyn ProofSink>>,
+        circuit_breaker: ProofCircuitBreaker,
     ) -> Self {
         Self {
             proof_idx: prev_proof_idx,
             last_seen_global_nonce: last_seen_global_nonce.unwrap_or(GlobalNonce::new([0u8; 32])),
             proof_storage,
+            proof_verifier: proof_verifier.map(|verifier| Arc::new(Mutex::new(verifier))),
+            proof_sinks,
+            circuit_breaker,
         }
     }
 
@@ -218,6 +239,9 @@ impl NewProofHandler {
         log::debug!("utility_thread: new proof_received {proof:?}");
 
         self.maybe_new_epoch(proof);
+        if !self.circuit_breaker.allow(proof.cu_id) {
+            return Err(UtilityThreadError::ProofDropped);
+        }
 
         let cc_proof_id = CCProofId::new(
             proof.epoch.global_nonce,
@@ -230,16 +254,44 @@ impl NewProofHandler {
             proof.cu_id,
             proof.result_hash,
         );
+        self.verify_proof(cc_proof).await?;
         self.proof_storage.store_new_proof(cc_proof).await?;
         self.proof_idx.increment();
+        self.send_to_sinks(&cc_proof).await;
 
         Ok(())
     }
 
+    /// Sinks failures don't affect proof handling, since the proof is already stored.
+    async fn send_to_sinks(&self, cc_proof: &CCProof) {
+        for sink in &self.proof_sinks {
+            if let Err(error) = sink.on_new_proof(cc_proof).await {
+                log::error!(
+                    "failed to send proof {} to a sink: {error}",
+                    cc_proof.id.idx
+                );
+            }
+        }
+    }
+
+    /// Re-hashes the proof in the light mode, if self-verification is enabled,
+    /// it's intended to catch silent corruptions of a dataset or CPU.
+    async fn verify_proof(&self, cc_proof: CCProof) -> UTResult<()> {
+        let verifier = match &self.proof_verifier {
+            Some(verifier) => verifier.clone(),
+            None => return Ok(()),
+        };
+
+        tokio::task::spawn_blocking(move || verifier.lock().verify(&cc_proof))
+            .await?
+            .map_err(UtilityThreadError::ProofRejected)
+    }
+
     fn maybe_new_epoch(&mut self, proof: &RawProof) {
         if self.is_new_epoch(proof) {
             self.last_seen_global_nonce = proof.epoch.global_nonce;
             self.proof_idx = ProofIdx::zero();
+            self.circuit_breaker.reset();
         }
     }
 
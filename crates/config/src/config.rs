@@ -16,12 +16,21 @@
 
 use ccp_randomx::RandomXFlags;
 use ccp_shared::types::LogicalCoreId;
+use schemars::JsonSchema;
+use serde::Deserialize;
+use serde::Serialize;
 
+use crate::defaults::default_archived_epochs;
 use crate::defaults::default_facade_queue_size;
 use crate::defaults::default_log_level;
+use crate::defaults::default_max_proofs_per_cu_per_minute;
+use crate::defaults::default_max_proofs_per_epoch;
+use crate::defaults::default_min_difficulty_zero_bits;
 use crate::defaults::default_msr_enabled;
 use crate::defaults::default_report_hashrate;
+use crate::defaults::default_self_verification;
 use crate::defaults::default_utility_queue_size;
+use crate::unresolved_config::UnresolvedDiskGuard;
 use crate::unresolved_config::UnresolvedWorkers;
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -33,14 +42,74 @@ pub struct CCPConfig {
     pub state_dir: std::path::PathBuf,
     pub workers: Workers,
     pub tokio: Tokio,
+    pub proofs: Proofs,
+    pub disk_guard: DiskGuard,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct RpcEndpoint {
-    pub host: String,
-    pub port: u16,
+    pub transport: RpcTransport,
     pub utility_queue_size: usize,
     pub facade_queue_size: usize,
+    /// Without it, anyone who can reach the endpoint can call any method.
+    pub auth: Option<RpcAuth>,
+}
+
+/// Defines how the JSON-RPC endpoint is exposed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RpcTransport {
+    /// HTTP and WebSocket on the same TCP port.
+    HttpAndWs {
+        host: String,
+        port: u16,
+    },
+    Http {
+        host: String,
+        port: u16,
+    },
+    /// WebSocket only, it's also needed for subscriptions.
+    Ws {
+        host: String,
+        port: u16,
+    },
+    /// HTTP and WebSocket on a Unix domain socket, intended for same-host deployments.
+    Unix {
+        path: std::path::PathBuf,
+        permissions: u32,
+    },
+    /// Newline-delimited JSON-RPC on stdin/stdout, intended for running as a child process.
+    Stdio,
+}
+
+/// Selects one of [`RpcTransport`] in the config.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum RpcTransportKind {
+    #[default]
+    HttpAndWs,
+    Http,
+    Ws,
+    Unix,
+    Stdio,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RpcAuth {
+    pub scheme: RpcAuthScheme,
+    /// File with a secret which grants access to all methods.
+    pub secret_path: std::path::PathBuf,
+    /// File with a secret which grants access only to methods that don't change the prover state.
+    pub read_only_secret_path: Option<std::path::PathBuf>,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum RpcAuthScheme {
+    /// The secret is sent as is in the `Authorization: Bearer` header.
+    Bearer,
+    /// The secret is used to sign the method, path, timestamp, nonce and body of each request
+    /// with HMAC-SHA3-256.
+    Hmac,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
@@ -93,13 +162,77 @@ pub struct Tokio {
     pub utility_cores_ids: Vec<LogicalCoreId>,
 }
 
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Proofs {
+    /// Re-hash each found proof in the light mode before storing it.
+    pub self_verification: bool,
+
+    /// How many previous epochs to keep proofs for, 0 means removing them on epoch switch.
+    pub archived_epochs: usize,
+
+    /// Where found proofs are additionally sent after they are stored.
+    pub sinks: Vec<ProofSinkConfig>,
+
+    /// Proofs aren't persisted anymore in the current epoch, if a CU finds more proofs per minute.
+    pub max_proofs_per_cu_per_minute: u64,
+
+    /// Proofs aren't persisted anymore in the current epoch, if more proofs are found.
+    pub max_proofs_per_epoch: u64,
+
+    /// Epochs with a difficulty having less leading zero bits are rejected.
+    pub min_difficulty_zero_bits: u32,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ProofSinkConfig {
+    /// POSTs each proof as JSON to the provided url.
+    Webhook {
+        url: String,
+        /// How many times to retry a failed request before leaving a proof in the outbox.
+        max_retries: u32,
+        /// Initial interval between retries, it's doubled after each retry.
+        retry_interval: std::time::Duration,
+        /// Undelivered proofs are kept in the outbox up to this number, newer ones are dropped.
+        max_outbox_entries: usize,
+    },
+    /// Appends each proof as a JSON line to the provided file.
+    JsonLines { path: std::path::PathBuf },
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct DiskGuard {
+    /// How often free space of the state directory filesystem is checked.
+    pub check_interval: std::time::Duration,
+    /// A warning is logged if there is less free space.
+    pub warn_free_space_bytes: u64,
+    /// The critical policy is applied if there is less free space.
+    pub critical_free_space_bytes: u64,
+    pub critical_policy: CriticalDiskPolicy,
+}
+
+/// Defines what CCP does when free disk space falls below the critical level.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub enum CriticalDiskPolicy {
+    /// Only report the condition, proofs are still persisted.
+    #[default]
+    KeepHashing,
+    /// Pause hashing on all provers until there is enough free space, caches and datasets are kept.
+    PauseProvers,
+    /// Stop writing the sliding hashrate CSV until there is enough free space.
+    StopSlidingCsv,
+}
+
 impl Default for RpcEndpoint {
     fn default() -> Self {
         Self {
-            host: "127.0.0.1".to_string(),
-            port: 9383,
+            transport: RpcTransport::HttpAndWs {
+                host: "127.0.0.1".to_string(),
+                port: 9383,
+            },
             utility_queue_size: default_utility_queue_size(),
             facade_queue_size: default_facade_queue_size(),
+            auth: None,
         }
     }
 }
@@ -128,3 +261,24 @@ impl Default for Workers {
         UnresolvedWorkers::default().resolve()
     }
 }
+
+impl Default for Proofs {
+    fn default() -> Self {
+        Self {
+            self_verification: default_self_verification(),
+            archived_epochs: default_archived_epochs(),
+            sinks: vec![],
+            max_proofs_per_cu_per_minute: default_max_proofs_per_cu_per_minute(),
+            max_proofs_per_epoch: default_max_proofs_per_epoch(),
+            min_difficulty_zero_bits: default_min_difficulty_zero_bits(),
+        }
+    }
+}
+
+impl Default for DiskGuard {
+    fn default() -> Self {
+        UnresolvedDiskGuard::default()
+            .resolve()
+            .expect("the default disk guard config is valid")
+    }
+}
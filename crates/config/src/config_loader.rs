@@ -14,19 +14,326 @@
  * limitations under the License.
  */
 
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::Path;
+use std::path::PathBuf;
+use std::str::FromStr;
+
 use config::Config;
-use config::File;
-use config::FileFormat;
+use eyre::eyre;
+use eyre::WrapErr;
 
+use crate::schema::config_keys;
 use crate::unresolved_config::UnresolvedCCPConfig;
 use crate::CCPConfig;
 
+/// Env vars with this prefix and at least one `__` separator override config values.
+pub const CONFIG_ENV_PREFIX: &str = "CCP_";
+const ENV_KEY_SEPARATOR: &str = "__";
+const CONFIG_EXTENSION: &str = "toml";
+
+/// Loads a config from a TOML file, values could be overridden by `CCP_`-prefixed env vars.
 pub fn load_config(path: &str) -> eyre::Result<CCPConfig> {
-    let config_source = File::with_name(path)
-        .required(true)
-        .format(FileFormat::Toml);
-    let config = Config::builder().add_source(config_source).build()?;
+    let loaded = ConfigLoader::new(path)
+        .with_env_vars(std::env::vars())
+        .load()?;
+    Ok(loaded.config)
+}
+
+/// Loads a config from layered sources, each of them overrides the previous one:
+/// a TOML file, `CCP_`-prefixed env vars, and `key=value` overrides from the command line.
+///
+/// The file extension could be omitted, e.g. `Config` is loaded from `Config.toml`
+/// if there is no file named `Config`.
+///
+/// An env var name is turned into a key by dropping the prefix, splitting it by `__`
+/// and converting to kebab case, e.g. `CCP_OPTIMIZATIONS__MSR_ENABLED` sets
+/// `optimizations.msr-enabled`. Values are parsed as TOML values, so a list is written
+/// as `[1, 2]`, anything not parseable is taken as a string. Env vars, which names
+/// don't match any config key, are ignored and reported in [`LoadedConfig::ignored_env_vars`],
+/// since the prefix could be used by other software.
+///
+/// Unknown keys are rejected unless they are explicitly allowed, so typos don't go unnoticed.
+#[derive(Clone, Debug)]
+pub struct ConfigLoader {
+    path: PathBuf,
+    env_vars: Vec<(String, String)>,
+    overrides: Vec<ConfigOverride>,
+    allow_unknown_keys: bool,
+}
+
+/// A config value set as `key=value`, where the key is dotted, e.g. `rpc-endpoint.port`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConfigOverride {
+    pub key: String,
+    pub value: String,
+}
+
+/// Tells which source a config value is taken from.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ConfigOrigin {
+    Default,
+    File,
+    Env { var: String },
+    Cli,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConfigValue {
+    pub value: toml::Value,
+    pub origin: ConfigOrigin,
+}
+
+/// A resolved config along with the values it's resolved from,
+/// it's displayed as a list of `key = value` lines annotated with their origins.
+#[derive(Clone, Debug)]
+pub struct LoadedConfig {
+    pub config: CCPConfig,
+    /// Values by dotted keys, defaults are included, unset optional values are not.
+    pub values: BTreeMap<String, ConfigValue>,
+    /// Dotted keys, which were ignored, it could be non-empty only if they are allowed.
+    pub unknown_keys: Vec<String>,
+    /// Names of `CCP_`-prefixed env vars, which don't match any config key.
+    pub ignored_env_vars: Vec<String>,
+}
+
+impl ConfigLoader {
+    pub fn new(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            env_vars: vec![],
+            overrides: vec![],
+            allow_unknown_keys: false,
+        }
+    }
+
+    /// Env vars are taken as is, so `std::env::vars()` should be passed to use the process ones.
+    pub fn with_env_vars(mut self, env_vars: impl IntoIterator<Item = (String, String)>) -> Self {
+        self.env_vars = env_vars.into_iter().collect();
+        self
+    }
+
+    pub fn with_overrides(mut self, overrides: impl IntoIterator<Item = ConfigOverride>) -> Self {
+        self.overrides = overrides.into_iter().collect();
+        self
+    }
+
+    /// Unknown keys are ignored and reported in [`LoadedConfig::unknown_keys`] instead of failing.
+    pub fn with_unknown_keys_allowed(mut self, allow_unknown_keys: bool) -> Self {
+        self.allow_unknown_keys = allow_unknown_keys;
+        self
+    }
+
+    pub fn load(&self) -> eyre::Result<LoadedConfig> {
+        let path = resolve_path(&self.path);
+        let content = std::fs::read_to_string(&path)
+            .wrap_err_with(|| format!("failed to read config {}", path.display()))?;
+        let mut table: toml::Table = toml::from_str(&content)
+            .wrap_err_with(|| format!("failed to parse config {}", path.display()))?;
+
+        let mut origins = BTreeMap::new();
+        collect_leaves(&table, "", &mut |key, _| {
+            origins.insert(key, ConfigOrigin::File);
+        });
+
+        // sorted to make the outcome independent of the env order
+        let mut env_vars = self.env_vars.iter().collect::<Vec<_>>();
+        env_vars.sort();
+        let known_keys = config_keys();
+        let mut ignored_env_vars = vec![];
+        for (var, value) in env_vars {
+            let Some(key) = env_var_key(var) else {
+                continue;
+            };
+            if !known_keys.contains(&key.join(".")) {
+                ignored_env_vars.push(var.clone());
+                continue;
+            }
+            insert_value(&mut table, &key, parse_value(value))
+                .wrap_err_with(|| format!("failed to apply env var {var}"))?;
+            origins.insert(key.join("."), ConfigOrigin::Env { var: var.clone() });
+        }
+
+        for ConfigOverride { key, value } in &self.overrides {
+            let key = key.split('.').map(normalize_key).collect::<Vec<_>>();
+            insert_value(&mut table, &key, parse_value(value))
+                .wrap_err_with(|| format!("failed to apply override of {}", key.join(".")))?;
+            origins.insert(key.join("."), ConfigOrigin::Cli);
+        }
+
+        let mut unknown_keys = vec![];
+        let unresolved: UnresolvedCCPConfig =
+            serde_ignored::deserialize(Config::try_from(&table)?, |key| {
+                unknown_keys.push(key.to_string())
+            })?;
+        // config sections are hash maps, so keys are reported in a random order
+        unknown_keys.sort();
+        if !unknown_keys.is_empty() && !self.allow_unknown_keys {
+            return Err(eyre!("unknown config keys: {}", unknown_keys.join(", ")));
+        }
+
+        let values = describe(&unresolved, &origins)?;
+        let config = unresolved.resolve(&path)?;
+
+        Ok(LoadedConfig {
+            config,
+            values,
+            unknown_keys,
+            ignored_env_vars,
+        })
+    }
+}
+
+impl FromStr for ConfigOverride {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (key, value) = s
+            .split_once('=')
+            .ok_or_else(|| format!("expected key=value, got {s:?}"))?;
+        let key = key.trim();
+        if key.is_empty() || key.split('.').any(str::is_empty) {
+            return Err(format!("invalid config key {key:?}"));
+        }
+
+        Ok(Self {
+            key: key.to_string(),
+            value: value.to_string(),
+        })
+    }
+}
+
+impl fmt::Display for ConfigOrigin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigOrigin::Default => write!(f, "default"),
+            ConfigOrigin::File => write!(f, "config file"),
+            ConfigOrigin::Env { var } => write!(f, "env {var}"),
+            ConfigOrigin::Cli => write!(f, "--set"),
+        }
+    }
+}
+
+impl fmt::Display for LoadedConfig {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (key, ConfigValue { value, origin }) in &self.values {
+            writeln!(f, "{key} = {value}  # {origin}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Mirrors `config::File::with_name`: the path is taken as is if there is such a file,
+/// otherwise the toml extension is tried.
+fn resolve_path(path: &Path) -> PathBuf {
+    if path.is_file() {
+        return path.to_path_buf();
+    }
+
+    let path_with_extension = path.with_extension(CONFIG_EXTENSION);
+    if path_with_extension.is_file() {
+        path_with_extension
+    } else {
+        path.to_path_buf()
+    }
+}
+
+/// Config keys are kebab case, but some snake case aliases are accepted too.
+fn normalize_key(key: &str) -> String {
+    key.trim().replace('_', "-")
+}
+
+fn env_var_key(var: &str) -> Option<Vec<String>> {
+    let key = var.strip_prefix(CONFIG_ENV_PREFIX)?;
+    if !key.contains(ENV_KEY_SEPARATOR) {
+        // e.g. CCP_LOG, which isn't a config value
+        return None;
+    }
+
+    key.split(ENV_KEY_SEPARATOR)
+        .map(|segment| (!segment.is_empty()).then(|| normalize_key(&segment.to_lowercase())))
+        .collect()
+}
+
+fn parse_value(raw: &str) -> toml::Value {
+    toml::from_str::<toml::Table>(&format!("value = {raw}"))
+        .ok()
+        .and_then(|mut table| table.remove("value"))
+        .unwrap_or_else(|| toml::Value::String(raw.to_string()))
+}
+
+/// Sets a value by a normalized key, an existing key of the table is reused
+/// if it's written differently, e.g. in snake case.
+fn insert_value(table: &mut toml::Table, key: &[String], value: toml::Value) -> eyre::Result<()> {
+    let (last, parents) = key.split_last().ok_or_else(|| eyre!("empty config key"))?;
+
+    let mut table = table;
+    for segment in parents {
+        let existing_key = table_key(table, segment);
+        table = table
+            .entry(existing_key)
+            .or_insert_with(|| toml::Value::Table(toml::Table::new()))
+            .as_table_mut()
+            .ok_or_else(|| eyre!("{segment} isn't a config section"))?;
+    }
+    let existing_key = table_key(table, last);
+    table.insert(existing_key, value);
+
+    Ok(())
+}
+
+fn table_key(table: &toml::Table, normalized_key: &str) -> String {
+    table
+        .keys()
+        .find(|key| normalize_key(key) == normalized_key)
+        .cloned()
+        .unwrap_or_else(|| normalized_key.to_string())
+}
+
+fn collect_leaves<'a>(
+    table: &'a toml::Table,
+    prefix: &str,
+    on_leaf: &mut impl FnMut(String, &'a toml::Value),
+) {
+    for (key, value) in table {
+        let key = format!("{prefix}{}", normalize_key(key));
+        match value {
+            toml::Value::Table(nested) => collect_leaves(nested, &format!("{key}."), on_leaf),
+            _ => on_leaf(key, value),
+        }
+    }
+}
+
+/// A value is set by the source, which set it or one of its parent sections last.
+fn origin_of(key: &str, origins: &BTreeMap<String, ConfigOrigin>) -> ConfigOrigin {
+    let mut key = key;
+    loop {
+        if let Some(origin) = origins.get(key) {
+            return origin.clone();
+        }
+        match key.rsplit_once('.') {
+            Some((parent, _)) => key = parent,
+            None => return ConfigOrigin::Default,
+        }
+    }
+}
+
+fn describe(
+    unresolved: &UnresolvedCCPConfig,
+    origins: &BTreeMap<String, ConfigOrigin>,
+) -> eyre::Result<BTreeMap<String, ConfigValue>> {
+    let table = toml::Table::try_from(unresolved)?;
+
+    let mut values = BTreeMap::new();
+    collect_leaves(&table, "", &mut |key, value| {
+        let origin = origin_of(&key, origins);
+        let value = ConfigValue {
+            value: value.clone(),
+            origin,
+        };
+        values.insert(key, value);
+    });
 
-    let config: UnresolvedCCPConfig = config.try_deserialize()?;
-    config.resolve(path)
+    Ok(values)
 }
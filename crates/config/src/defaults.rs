@@ -25,6 +25,22 @@ const DEFAULT_SYNC_TO_ASYNC_QUEUE_SIZE: usize = 1;
 const DEFAULT_UTILITY_QUEUE_SIZE: usize = 100;
 const DEFAULT_FACADE_QUEUE_SIZE: usize = 100;
 
+const DEFAULT_UNIX_SOCKET_PERMISSIONS: u32 = 0o660;
+
+const DEFAULT_ARCHIVED_EPOCHS: usize = 2;
+const DEFAULT_MAX_PROOFS_PER_CU_PER_MINUTE: u64 = 600;
+const DEFAULT_MAX_PROOFS_PER_EPOCH: u64 = 100_000;
+const DEFAULT_MIN_DIFFICULTY_ZERO_BITS: u32 = 8;
+
+const DEFAULT_DISK_CHECK_INTERVAL_SECS: u64 = 60;
+pub(crate) const MIN_DISK_CHECK_INTERVAL_SECS: u64 = 1;
+const DEFAULT_WARN_FREE_SPACE_MB: u64 = 1024;
+const DEFAULT_CRITICAL_FREE_SPACE_MB: u64 = 256;
+
+const DEFAULT_WEBHOOK_MAX_RETRIES: u32 = 5;
+const DEFAULT_WEBHOOK_RETRY_INTERVAL_MS: u64 = 1000;
+const DEFAULT_WEBHOOK_MAX_OUTBOX_ENTRIES: usize = 100_000;
+
 pub(crate) fn default_log_level() -> LogLevel {
     LogLevel::Error
 }
@@ -41,6 +57,50 @@ pub(crate) fn default_msr_enabled() -> bool {
     false
 }
 
+pub(crate) fn default_self_verification() -> bool {
+    false
+}
+
+pub(crate) fn default_archived_epochs() -> usize {
+    DEFAULT_ARCHIVED_EPOCHS
+}
+
+pub(crate) fn default_max_proofs_per_cu_per_minute() -> u64 {
+    DEFAULT_MAX_PROOFS_PER_CU_PER_MINUTE
+}
+
+pub(crate) fn default_max_proofs_per_epoch() -> u64 {
+    DEFAULT_MAX_PROOFS_PER_EPOCH
+}
+
+pub(crate) fn default_min_difficulty_zero_bits() -> u32 {
+    DEFAULT_MIN_DIFFICULTY_ZERO_BITS
+}
+
+pub(crate) fn default_webhook_max_retries() -> u32 {
+    DEFAULT_WEBHOOK_MAX_RETRIES
+}
+
+pub(crate) fn default_webhook_retry_interval_ms() -> u64 {
+    DEFAULT_WEBHOOK_RETRY_INTERVAL_MS
+}
+
+pub(crate) fn default_webhook_max_outbox_entries() -> usize {
+    DEFAULT_WEBHOOK_MAX_OUTBOX_ENTRIES
+}
+
+pub(crate) fn default_disk_check_interval_secs() -> u64 {
+    DEFAULT_DISK_CHECK_INTERVAL_SECS
+}
+
+pub(crate) fn default_warn_free_space_mb() -> u64 {
+    DEFAULT_WARN_FREE_SPACE_MB
+}
+
+pub(crate) fn default_critical_free_space_mb() -> u64 {
+    DEFAULT_CRITICAL_FREE_SPACE_MB
+}
+
 pub(crate) fn default_hashes_per_round() -> usize {
     DEFAULT_HASHES_PER_ROUND
 }
@@ -53,6 +113,10 @@ pub(crate) fn default_sync_to_async_queue_size() -> usize {
     DEFAULT_SYNC_TO_ASYNC_QUEUE_SIZE
 }
 
+pub(crate) fn default_unix_socket_permissions() -> u32 {
+    DEFAULT_UNIX_SOCKET_PERMISSIONS
+}
+
 pub(crate) fn default_utility_queue_size() -> usize {
     DEFAULT_UTILITY_QUEUE_SIZE
 }
@@ -0,0 +1,84 @@
+/*
+ * Copyright 2024 Fluence Labs Limited
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::path::PathBuf;
+
+use crate::unresolved_config::Argon2Impl;
+use crate::RpcAuthScheme;
+
+// examples of values, which aren't set by default,
+// they're shown in the config schema and in the generated default config
+
+const EXAMPLE_RPC_HOST: &str = "127.0.0.1";
+const EXAMPLE_RPC_PORT: u16 = 9383;
+const EXAMPLE_PROMETHEUS_HOST: &str = "127.0.0.1";
+const EXAMPLE_PROMETHEUS_PORT: u16 = 9384;
+
+const EXAMPLE_THREADS_PER_CORE: usize = 2;
+const EXAMPLE_WORKER_THREADS: usize = 2;
+const EXAMPLE_MAX_BLOCKING_THREADS: usize = 15;
+
+pub(crate) fn example_rpc_host() -> String {
+    EXAMPLE_RPC_HOST.to_string()
+}
+
+pub(crate) fn example_rpc_port() -> u16 {
+    EXAMPLE_RPC_PORT
+}
+
+pub(crate) fn example_unix_socket_path() -> PathBuf {
+    PathBuf::from("/var/run/ccp/rpc.sock")
+}
+
+pub(crate) fn example_rpc_auth_scheme() -> RpcAuthScheme {
+    RpcAuthScheme::Bearer
+}
+
+pub(crate) fn example_secret_path() -> PathBuf {
+    PathBuf::from("/run/secrets/ccp-rpc-secret")
+}
+
+pub(crate) fn example_read_only_secret_path() -> PathBuf {
+    PathBuf::from("/run/secrets/ccp-rpc-read-only-secret")
+}
+
+pub(crate) fn example_prometheus_host() -> String {
+    EXAMPLE_PROMETHEUS_HOST.to_string()
+}
+
+pub(crate) fn example_prometheus_port() -> u16 {
+    EXAMPLE_PROMETHEUS_PORT
+}
+
+pub(crate) fn example_flag() -> bool {
+    true
+}
+
+pub(crate) fn example_argon2() -> Argon2Impl {
+    Argon2Impl::Default
+}
+
+pub(crate) fn example_threads_per_core() -> usize {
+    EXAMPLE_THREADS_PER_CORE
+}
+
+pub(crate) fn example_worker_threads() -> usize {
+    EXAMPLE_WORKER_THREADS
+}
+
+pub(crate) fn example_max_blocking_threads() -> usize {
+    EXAMPLE_MAX_BLOCKING_THREADS
+}
@@ -29,9 +29,19 @@
 mod config;
 mod config_loader;
 mod defaults;
+mod examples;
+mod schema;
 mod tests;
 mod unresolved_config;
 
 pub use ccp_randomx::RandomXFlags;
 pub use config::*;
 pub use config_loader::load_config;
+pub use config_loader::ConfigLoader;
+pub use config_loader::ConfigOrigin;
+pub use config_loader::ConfigOverride;
+pub use config_loader::ConfigValue;
+pub use config_loader::LoadedConfig;
+pub use config_loader::CONFIG_ENV_PREFIX;
+pub use schema::config_json_schema;
+pub use schema::default_config_toml;
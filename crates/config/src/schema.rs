@@ -0,0 +1,250 @@
+/*
+ * Copyright 2024 Fluence Labs Limited
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::collections::BTreeSet;
+use std::fmt::Write as _;
+
+use schemars::gen::SchemaGenerator;
+use schemars::gen::SchemaSettings;
+use schemars::schema::RootSchema;
+use schemars::schema::Schema;
+use schemars::schema::SchemaObject;
+use schemars::JsonSchema;
+
+use crate::examples::example_rpc_host;
+use crate::examples::example_rpc_port;
+use crate::unresolved_config::UnresolvedCCPConfig;
+
+const COMMENT_WIDTH: usize = 90;
+const OCTAL_FORMAT: &str = "octal";
+
+/// Returns a JSON schema of the TOML config, pretty printed.
+pub fn config_json_schema() -> String {
+    serde_json::to_string_pretty(&config_schema()).expect("a schema is serializable")
+}
+
+/// Returns a TOML config with all default values and commented out examples of values,
+/// which aren't set by default; every value is annotated with its description.
+pub fn default_config_toml() -> String {
+    let schema = config_schema();
+    let values = default_config_values();
+
+    let mut config = String::from(
+        "# CCP config with default values, commented out values aren't set by default.\n",
+    );
+    write_table(&mut config, "", &schema.schema, Some(&values));
+    config
+}
+
+pub(crate) fn config_schema() -> RootSchema {
+    SchemaSettings::draft07()
+        .with(|settings| {
+            // there is no null in TOML, an optional value is just omitted
+            settings.option_add_null_type = false;
+            settings.inline_subschemas = true;
+        })
+        .into_generator()
+        .into_root_schema_for::<UnresolvedCCPConfig>()
+}
+
+/// Schema of an integer, which is written in octal in the generated config, e.g. file permissions.
+pub(crate) fn octal_u32_schema(gen: &mut SchemaGenerator) -> Schema {
+    let mut schema = u32::json_schema(gen).into_object();
+    schema.format = Some(OCTAL_FORMAT.to_string());
+    schema.into()
+}
+
+/// Returns dotted keys of all config sections and values.
+pub(crate) fn config_keys() -> BTreeSet<String> {
+    let mut keys = BTreeSet::new();
+    collect_keys(&config_schema().schema, "", &mut keys);
+    keys
+}
+
+fn collect_keys(schema: &SchemaObject, prefix: &str, keys: &mut BTreeSet<String>) {
+    let Some(object) = &schema.object else {
+        return;
+    };
+
+    for (key, property) in &object.properties {
+        let key = format!("{prefix}{key}");
+        if let Schema::Object(property) = property {
+            collect_keys(property, &format!("{key}."), keys);
+        }
+        keys.insert(key);
+    }
+}
+
+fn default_config_values() -> toml::Table {
+    // the only values needed to resolve the config, everything else is taken from serde defaults
+    let mut rpc_endpoint = toml::Table::new();
+    rpc_endpoint.insert("host".to_string(), example_rpc_host().into());
+    rpc_endpoint.insert("port".to_string(), i64::from(example_rpc_port()).into());
+    let mut required_values = toml::Table::new();
+    required_values.insert("rpc-endpoint".to_string(), rpc_endpoint.into());
+
+    let config: UnresolvedCCPConfig = toml::Value::Table(required_values)
+        .try_into()
+        .expect("the default config is valid");
+    toml::Table::try_from(config).expect("the default config is serializable")
+}
+
+/// Writes values of a table and then its nested tables as sections,
+/// if there are no values, the whole table is commented out.
+fn write_table(out: &mut String, path: &str, schema: &SchemaObject, values: Option<&toml::Table>) {
+    let Some(object) = &schema.object else {
+        return;
+    };
+
+    let mut sections = vec![];
+    let mut is_first_value = true;
+    for (key, property) in &object.properties {
+        let Schema::Object(property) = property else {
+            continue;
+        };
+        if property.object.is_some() {
+            sections.push((key, property));
+            continue;
+        }
+
+        if !is_first_value {
+            out.push('\n');
+        }
+        is_first_value = false;
+        write_description(out, property);
+
+        let value = values.and_then(|values| values.get(key));
+        match (value, values.is_some()) {
+            (Some(value), _) => writeln!(out, "{key} = {}", render(property, value)),
+            (None, true) => match example(property) {
+                Some(example) => writeln!(out, "# {key} = {example}"),
+                None => Ok(()),
+            },
+            // a commented out table shows what's needed to enable it
+            (None, false) => match example(property).or_else(|| default(property)) {
+                Some(example) => writeln!(out, "# {key} = {example}"),
+                None => Ok(()),
+            },
+        }
+        .expect("writing to a string doesn't fail");
+    }
+
+    for (key, section) in sections {
+        let section_path = match path {
+            "" => key.clone(),
+            _ => format!("{path}.{key}"),
+        };
+        let section_values = values
+            .and_then(|values| values.get(key))
+            .and_then(toml::Value::as_table);
+
+        out.push('\n');
+        write_description(out, section);
+        let prefix = if section_values.is_some() { "" } else { "# " };
+        writeln!(out, "{prefix}[{section_path}]").expect("writing to a string doesn't fail");
+        write_table(out, &section_path, section, section_values);
+    }
+}
+
+fn write_description(out: &mut String, schema: &SchemaObject) {
+    let description = schema
+        .metadata
+        .as_ref()
+        .and_then(|metadata| metadata.description.as_deref());
+    if let Some(description) = description {
+        for line in description.lines() {
+            write_comment(out, line);
+        }
+    }
+
+    let possible_values = possible_values(schema);
+    if !possible_values.is_empty() {
+        write_comment(
+            out,
+            &format!("possible values: {}", possible_values.join(", ")),
+        );
+    }
+}
+
+fn write_comment(out: &mut String, text: &str) {
+    let mut line = String::from("#");
+    for word in text.split_whitespace() {
+        if line.len() + 1 + word.len() > COMMENT_WIDTH && line != "#" {
+            out.push_str(&line);
+            out.push('\n');
+            line = String::from("#");
+        }
+        line.push(' ');
+        line.push_str(word);
+    }
+    out.push_str(&line);
+    out.push('\n');
+}
+
+/// Values of an enum, variants with doc comments are described by separate subschemas.
+fn possible_values(schema: &SchemaObject) -> Vec<String> {
+    let variants = schema
+        .subschemas
+        .as_ref()
+        .and_then(|subschemas| subschemas.one_of.as_ref());
+    let values = match variants {
+        Some(variants) => variants
+            .iter()
+            .filter_map(|variant| match variant {
+                Schema::Object(variant) => variant.enum_values.as_ref(),
+                Schema::Bool(_) => None,
+            })
+            .flatten()
+            .collect::<Vec<_>>(),
+        None => schema.enum_values.iter().flatten().collect(),
+    };
+
+    values
+        .into_iter()
+        .filter_map(|value| to_toml(schema, value))
+        .collect()
+}
+
+fn example(schema: &SchemaObject) -> Option<String> {
+    let metadata = schema.metadata.as_ref()?;
+    metadata
+        .examples
+        .first()
+        .and_then(|value| to_toml(schema, value))
+}
+
+fn default(schema: &SchemaObject) -> Option<String> {
+    let metadata = schema.metadata.as_ref()?;
+    metadata
+        .default
+        .as_ref()
+        .and_then(|value| to_toml(schema, value))
+}
+
+fn to_toml(schema: &SchemaObject, value: &serde_json::Value) -> Option<String> {
+    toml::Value::try_from(value)
+        .ok()
+        .map(|value| render(schema, &value))
+}
+
+fn render(schema: &SchemaObject, value: &toml::Value) -> String {
+    match value {
+        toml::Value::Integer(value) if schema.format.as_deref() == Some(OCTAL_FORMAT) => {
+            format!("0o{value:o}")
+        }
+        _ => value.to_string(),
+    }
+}
@@ -16,3 +16,9 @@
 
 #[cfg(test)]
 mod tests_;
+
+#[cfg(test)]
+mod overrides;
+
+#[cfg(test)]
+mod schema;
@@ -0,0 +1,153 @@
+/*
+ * Copyright 2024 Fluence Labs Limited
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::path::PathBuf;
+
+use crate::ConfigLoader;
+use crate::ConfigOrigin;
+use crate::ConfigOverride;
+use crate::RpcTransport;
+
+fn test_config_path() -> PathBuf {
+    let mut manifest_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    manifest_path.push("src/tests/test.toml");
+    manifest_path
+}
+
+fn env_vars(vars: &[(&str, &str)]) -> Vec<(String, String)> {
+    vars.iter()
+        .map(|(var, value)| (var.to_string(), value.to_string()))
+        .collect()
+}
+
+#[test]
+fn env_and_cli_override_file() {
+    let env = env_vars(&[
+        ("CCP_RPC_ENDPOINT__PORT", "1000"),
+        ("CCP_TOKIO__UTILITY_THREAD_IDS", "[3, 4]"),
+        ("CCP_LOG", "debug"),
+    ]);
+    let overrides = ["rpc-endpoint.port=2000", "optimizations.msr-enabled=false"]
+        .into_iter()
+        .map(|s| s.parse::<ConfigOverride>().unwrap());
+
+    let loaded = ConfigLoader::new(test_config_path())
+        .with_env_vars(env)
+        .with_overrides(overrides)
+        .load()
+        .unwrap();
+
+    assert_eq!(
+        loaded.config.rpc_endpoint.transport,
+        RpcTransport::HttpAndWs {
+            host: "127.0.0.1".to_string(),
+            port: 2000,
+        }
+    );
+    assert_eq!(
+        loaded.config.tokio.utility_cores_ids,
+        vec![3.into(), 4.into()]
+    );
+    assert!(!loaded.config.optimizations.msr_enabled);
+
+    let origin = |key: &str| loaded.values[key].origin.clone();
+    assert_eq!(origin("rpc-endpoint.port"), ConfigOrigin::Cli);
+    assert_eq!(
+        origin("tokio.utility-thread-ids"),
+        ConfigOrigin::Env {
+            var: "CCP_TOKIO__UTILITY_THREAD_IDS".to_string()
+        }
+    );
+    assert_eq!(origin("rpc-endpoint.host"), ConfigOrigin::File);
+    assert_eq!(origin("workers.hashes-per-round"), ConfigOrigin::Default);
+}
+
+#[test]
+fn printed_config_has_origins() {
+    let loaded = ConfigLoader::new(test_config_path())
+        .with_env_vars(env_vars(&[("CCP_LOGS__LOG_LEVEL", "debug")]))
+        .load()
+        .unwrap();
+
+    let printed = loaded.to_string();
+    assert!(printed.contains("logs.log-level = \"debug\"  # env CCP_LOGS__LOG_LEVEL\n"));
+    assert!(printed.contains("rpc-endpoint.host = \"127.0.0.1\"  # config file\n"));
+}
+
+#[test]
+fn malformed_override_is_rejected() {
+    assert!("rpc-endpoint.port".parse::<ConfigOverride>().is_err());
+    assert!("rpc-endpoint..port=1".parse::<ConfigOverride>().is_err());
+    assert!("=1".parse::<ConfigOverride>().is_err());
+}
+
+#[test]
+fn zero_disk_check_interval_is_rejected() {
+    let overrides = ["disk-guard.check-interval-secs=0"
+        .parse::<ConfigOverride>()
+        .unwrap()];
+    let error = ConfigLoader::new(test_config_path())
+        .with_overrides(overrides)
+        .load()
+        .unwrap_err();
+    assert!(error
+        .to_string()
+        .contains("disk-guard.check-interval-secs must be at least 1"));
+
+    let overrides = ["disk-guard.check-interval-secs=1"
+        .parse::<ConfigOverride>()
+        .unwrap()];
+    let loaded = ConfigLoader::new(test_config_path())
+        .with_overrides(overrides)
+        .load()
+        .unwrap();
+    assert_eq!(
+        loaded.config.disk_guard.check_interval,
+        std::time::Duration::from_secs(1)
+    );
+}
+
+#[test]
+fn config_extension_is_optional() {
+    let path = test_config_path().with_extension("");
+    let loaded = ConfigLoader::new(path).load().unwrap();
+    assert!(loaded.config.optimizations.msr_enabled);
+}
+
+#[test]
+fn unrelated_env_vars_are_ignored() {
+    let env = env_vars(&[
+        ("CCP_LOGS__LOG_LEVEL", "debug"),
+        ("CCP_OTHER_APP__TOKEN", "secret"),
+        ("CCP_LOGS__LOG_LEVL", "trace"),
+    ]);
+    let loaded = ConfigLoader::new(test_config_path())
+        .with_env_vars(env)
+        .load()
+        .unwrap();
+
+    assert_eq!(
+        loaded.ignored_env_vars,
+        vec!["CCP_LOGS__LOG_LEVL", "CCP_OTHER_APP__TOKEN"]
+    );
+    assert!(loaded.unknown_keys.is_empty());
+    assert_eq!(
+        loaded.values["logs.log-level"].origin,
+        ConfigOrigin::Env {
+            var: "CCP_LOGS__LOG_LEVEL".to_string()
+        }
+    );
+}
@@ -0,0 +1,134 @@
+/*
+ * Copyright 2024 Fluence Labs Limited
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::path::PathBuf;
+
+use tracing_subscriber::filter::LevelFilter;
+
+use crate::config_json_schema;
+use crate::default_config_toml;
+use crate::ConfigLoader;
+use crate::ConfigOverride;
+use crate::PrometheusEndpoint;
+use crate::RpcEndpoint;
+use crate::RpcTransport;
+use crate::Workers;
+
+fn test_config_path() -> PathBuf {
+    let mut manifest_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    manifest_path.push("src/tests/test.toml");
+    manifest_path
+}
+
+#[test]
+fn default_config_is_loadable() {
+    let dir = tempfile::tempdir().unwrap();
+    let config_path = dir.path().join("Config.toml");
+    std::fs::write(&config_path, default_config_toml()).unwrap();
+
+    let loaded = ConfigLoader::new(&config_path).load().unwrap();
+
+    assert_eq!(loaded.config.rpc_endpoint, RpcEndpoint::default());
+    assert_eq!(loaded.config.workers, Workers::default());
+    assert_eq!(loaded.config.prometheus_endpoint, None);
+    assert!(loaded.unknown_keys.is_empty());
+}
+
+#[test]
+fn default_config_is_commented() {
+    let config = default_config_toml();
+
+    assert!(config.contains("\n# Tune CPU registers for RandomX"));
+    assert!(config.contains("\nmsr-enabled = false\n"));
+    assert!(config.contains("\n# large-pages = true\n"));
+    assert!(config.contains("\n# [prometheus-endpoint]\n"));
+    assert!(config.contains("\n# possible values: \"keep-hashing\", \"pause-provers\""));
+}
+
+#[test]
+fn docker_config_is_generated() {
+    let mut config_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    config_path.push("../../docker/Config.default.toml");
+
+    let docker_config = std::fs::read_to_string(config_path).unwrap();
+
+    assert!(
+        docker_config == default_config_toml(),
+        "docker/Config.default.toml is outdated, regenerate it with \
+         `cargo run -p ccp-main -- print-default-config > docker/Config.default.toml`"
+    );
+}
+
+#[test]
+fn docker_config_is_loadable() {
+    let mut config_path = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
+    config_path.push("../../docker/Config.toml");
+
+    let loaded = ConfigLoader::new(&config_path).load().unwrap();
+
+    let expected_transport = RpcTransport::HttpAndWs {
+        host: "0.0.0.0".to_string(),
+        port: 9383,
+    };
+    assert_eq!(loaded.config.rpc_endpoint.transport, expected_transport);
+    let expected_prometheus = PrometheusEndpoint {
+        host: "0.0.0.0".to_string(),
+        port: 9384,
+    };
+    assert_eq!(loaded.config.prometheus_endpoint, Some(expected_prometheus));
+    assert_eq!(loaded.config.logs.log_level, LevelFilter::INFO);
+}
+
+#[test]
+fn unknown_keys_are_rejected_unless_allowed() {
+    let overrides = || {
+        ["optimizations.msr=true"]
+            .into_iter()
+            .map(|s| s.parse::<ConfigOverride>().unwrap())
+    };
+    let env = vec![("CCP_WORKERS__HASHES".to_string(), "1".to_string())];
+
+    let error = ConfigLoader::new(test_config_path())
+        .with_env_vars(env.clone())
+        .with_overrides(overrides())
+        .load()
+        .unwrap_err();
+    assert_eq!(error.to_string(), "unknown config keys: optimizations.msr");
+
+    let loaded = ConfigLoader::new(test_config_path())
+        .with_env_vars(env)
+        .with_overrides(overrides())
+        .with_unknown_keys_allowed(true)
+        .load()
+        .unwrap();
+    assert_eq!(loaded.unknown_keys, vec!["optimizations.msr"]);
+    // env vars are shared with other software, so they are reported separately
+    assert_eq!(loaded.ignored_env_vars, vec!["CCP_WORKERS__HASHES"]);
+    assert_eq!(loaded.config.workers, Workers::default());
+}
+
+#[test]
+fn schema_has_all_keys() {
+    let schema: serde_json::Value = serde_json::from_str(&config_json_schema()).unwrap();
+
+    let properties = &schema["properties"];
+    assert!(
+        properties["rpc-endpoint"]["properties"]["auth"]["properties"]["secret-path"].is_object()
+    );
+    assert!(properties["optimizations"]["properties"]["large-pages"].is_object());
+    assert!(properties["optimizations"]["properties"]["msr-enabled"].is_object());
+    assert_eq!(schema["required"], serde_json::json!(["rpc-endpoint"]));
+}
@@ -21,9 +21,12 @@ use ccp_randomx::RandomXFlags;
 use crate::config_loader::load_config;
 use crate::unresolved_config::UnresolvedWorkers;
 use crate::CCPConfig;
+use crate::DiskGuard;
 use crate::Logs;
 use crate::Optimizations;
+use crate::Proofs;
 use crate::RpcEndpoint;
+use crate::RpcTransport;
 use crate::ThreadsPerCoreAllocationPolicy;
 
 #[test]
@@ -34,9 +37,11 @@ fn parse_basic_config() {
     let actual_config = load_config(manifest_path.as_os_str().to_str().unwrap()).unwrap();
 
     let rpc_endpoint = RpcEndpoint {
-        host: "127.0.0.1".to_string(),
-        port: 9383,
-        utility_cores_ids: vec![1.into(), 2.into()],
+        transport: RpcTransport::HttpAndWs {
+            host: "127.0.0.1".to_string(),
+            port: 9383,
+        },
+        ..RpcEndpoint::default()
     };
 
     let mut randomx_flags = RandomXFlags::default();
@@ -65,6 +70,8 @@ fn parse_basic_config() {
         logs,
         state_dir: "../test".into(),
         workers: Workers::default(),
+        proofs: Proofs::default(),
+        disk_guard: DiskGuard::default(),
     };
 
     assert_eq!(actual_config, expected_config);
@@ -78,9 +85,11 @@ fn parse_config_without_optimiziations() {
     let actual_config = load_config(manifest_path.as_os_str().to_str().unwrap()).unwrap();
 
     let rpc_endpoint = RpcEndpoint {
-        host: "127.0.0.1".to_string(),
-        port: 9383,
-        utility_cores_ids: vec![1.into(), 2.into()],
+        transport: RpcTransport::HttpAndWs {
+            host: "127.0.0.1".to_string(),
+            port: 9383,
+        },
+        ..RpcEndpoint::default()
     };
 
     let randomx_flags = RandomXFlags::recommended_full_mem();
@@ -101,6 +110,8 @@ fn parse_config_without_optimiziations() {
         logs,
         state_dir: "../test".into(),
         workers: Workers::default(),
+        proofs: Proofs::default(),
+        disk_guard: DiskGuard::default(),
     };
 
     assert_eq!(actual_config, expected_config);
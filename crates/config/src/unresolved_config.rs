@@ -15,27 +15,45 @@
  */
 
 use std::path::Path;
+use std::path::PathBuf;
 
 use eyre::eyre;
+use schemars::JsonSchema;
 use serde::Deserialize;
 use serde::Serialize;
 
+use super::defaults::default_archived_epochs;
 use super::defaults::default_async_to_sync_queue_size;
+use super::defaults::default_critical_free_space_mb;
+use super::defaults::default_disk_check_interval_secs;
 use super::defaults::default_facade_queue_size;
 use super::defaults::default_hashes_per_round;
 use super::defaults::default_log_level;
+use super::defaults::default_max_proofs_per_cu_per_minute;
+use super::defaults::default_max_proofs_per_epoch;
+use super::defaults::default_min_difficulty_zero_bits;
 use super::defaults::default_msr_enabled;
 use super::defaults::default_report_hashrate;
+use super::defaults::default_self_verification;
 use super::defaults::default_state_path;
 use super::defaults::default_sync_to_async_queue_size;
+use super::defaults::default_unix_socket_permissions;
 use super::defaults::default_utility_queue_size;
+use super::defaults::default_warn_free_space_mb;
+use super::defaults::default_webhook_max_outbox_entries;
+use super::defaults::default_webhook_max_retries;
+use super::defaults::default_webhook_retry_interval_ms;
+use super::defaults::MIN_DISK_CHECK_INTERVAL_SECS;
+use super::examples::*;
+use super::schema::octal_u32_schema;
 
 use crate::*;
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "kebab-case")]
 pub struct UnresolvedCCPConfig {
     pub rpc_endpoint: UnresolvedRpcEndpoint,
+    /// Prometheus metrics are served over HTTP, if the endpoint is set.
     pub prometheus_endpoint: Option<UnresolvedPrometheusEndpoint>,
     #[serde(default)]
     pub optimizations: UnresolvedOptimizations,
@@ -47,64 +65,123 @@ pub struct UnresolvedCCPConfig {
     pub workers: UnresolvedWorkers,
     #[serde(default)]
     pub tokio: UnresolvedTokio,
+    #[serde(default)]
+    pub proofs: UnresolvedProofs,
+    #[serde(default)]
+    pub disk_guard: UnresolvedDiskGuard,
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+/// JSON-RPC endpoint, which is used by Nox to manage CCP.
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "kebab-case")]
 pub struct UnresolvedRpcEndpoint {
-    pub host: String,
-    pub port: u16,
+    /// How the endpoint is served.
+    #[serde(default)]
+    pub transport: RpcTransportKind,
+    /// Required for TCP based transports.
+    #[schemars(example = "example_rpc_host")]
+    pub host: Option<String>,
+    /// Required for TCP based transports.
+    #[schemars(example = "example_rpc_port")]
+    pub port: Option<u16>,
+    /// Required for the unix transport, relative path is resolved relative to the config.
+    #[schemars(example = "example_unix_socket_path")]
+    pub unix_socket_path: Option<PathBuf>,
+    /// Permissions of the unix socket file, could be written in octal, e.g. 0o660.
+    #[serde(default = "default_unix_socket_permissions")]
+    #[schemars(schema_with = "octal_u32_schema")]
+    pub unix_socket_permissions: u32,
+    /// Queue size from async worker task to utility task.
     #[serde(default = "default_utility_queue_size")]
     pub utility_queue_size: usize,
+    /// Queue size from RPC endpoint to utility task.
     #[serde(default = "default_facade_queue_size")]
     pub facade_queue_size: usize,
+    #[serde(default)]
+    pub auth: Option<UnresolvedRpcAuth>,
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+/// Requires credentials from RPC clients, secrets are read from files.
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub struct UnresolvedRpcAuth {
+    /// How a client proves that it knows the secret.
+    #[schemars(example = "example_rpc_auth_scheme")]
+    pub scheme: RpcAuthScheme,
+    /// File with a secret which grants access to all methods,
+    /// relative paths are resolved relative to the config.
+    #[schemars(example = "example_secret_path")]
+    pub secret_path: PathBuf,
+    /// File with a secret which grants access only to get_proofs_after, get_status,
+    /// get_hashrate and subscribe_proofs.
+    #[schemars(example = "example_read_only_secret_path")]
+    pub read_only_secret_path: Option<PathBuf>,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "kebab-case")]
 pub struct UnresolvedPrometheusEndpoint {
+    /// Host to serve metrics on.
+    #[schemars(example = "example_prometheus_host")]
     pub host: String,
+    /// Port to serve metrics on.
+    #[schemars(example = "example_prometheus_port")]
     pub port: u16,
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+/// RandomX flags which aren't set are chosen by RandomX as recommended for the CPU.
+// randomx values aren't flattened from a separate struct,
+// since serde doesn't report unknown keys of flattened structs
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "kebab-case")]
 pub struct UnresolvedOptimizations {
-    #[serde(flatten)]
-    pub randomx: UnresolvedRandomX,
+    /// Allocate datasets in huge pages, they should be reserved beforehand.
+    #[schemars(example = "example_flag")]
+    pub large_pages: Option<bool>,
+    /// Use hardware accelerated AES.
+    #[schemars(example = "example_flag")]
+    pub hard_aes: Option<bool>,
+    /// Compile RandomX programs to machine code.
+    #[schemars(example = "example_flag")]
+    pub jit: Option<bool>,
+    /// Don't keep JIT compiled code both writable and executable.
+    #[schemars(example = "example_flag")]
+    pub secure: Option<bool>,
+    /// Argon2 implementation used to initialize caches, "default" lets RandomX choose it.
+    #[schemars(example = "example_argon2")]
+    pub argon2: Option<Argon2Impl>,
 
+    /// Tune CPU registers for RandomX, requires write access to /dev/cpu/*/msr.
     #[serde(default = "default_msr_enabled")]
     pub msr_enabled: bool,
 
+    /// How many threads hash on one physical core, the optimal count is chosen if it's not set.
+    #[schemars(example = "example_threads_per_core")]
     pub threads_per_core: Option<usize>,
 }
 
 impl Default for UnresolvedOptimizations {
     fn default() -> Self {
         Self {
-            randomx: Default::default(),
+            large_pages: None,
+            hard_aes: None,
+            jit: None,
+            secure: None,
+            argon2: None,
             msr_enabled: default_msr_enabled(),
-            threads_per_core: Default::default(),
+            threads_per_core: None,
         }
     }
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize, Default)]
-#[serde(rename_all = "kebab-case")]
-pub struct UnresolvedRandomX {
-    pub large_pages: Option<bool>,
-    pub hard_aes: Option<bool>,
-    pub jit: Option<bool>,
-    pub secure: Option<bool>,
-    pub argon2: Option<Argon2Impl>,
-}
-
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "kebab-case")]
 pub struct UnresolvedLogs {
+    /// Log hashrate of each CU periodically.
     #[serde(default = "default_report_hashrate")]
     pub report_hashrate: bool,
 
+    /// The CCP_LOG env var takes precedence over it.
     #[serde(default = "default_log_level")]
     pub log_level: LogLevel,
 }
@@ -118,8 +195,10 @@ impl Default for UnresolvedLogs {
     }
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
 pub struct State {
+    /// Directory with persisted state and proofs, relative path is resolved relative
+    /// to the config, absolute path works as is.
     #[serde(default = "default_state_path")]
     pub path: std::path::PathBuf,
 }
@@ -132,26 +211,132 @@ impl Default for State {
     }
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "kebab-case")]
 pub struct UnresolvedWorkers {
+    /// How large is a hash chunk to process, after each chunk threads react to interruptions.
     #[serde(default = "default_hashes_per_round")]
     pub hashes_per_round: usize,
+    /// Async to sync queue size.
     #[serde(default = "default_async_to_sync_queue_size")]
     pub async_to_sync_queue_size: usize,
+    /// Sync to async queue size.
     #[serde(default = "default_sync_to_async_queue_size")]
     pub sync_to_async_queue_size: usize,
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize, Default)]
+#[derive(Clone, Debug, Serialize, Deserialize, Default, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
 pub struct UnresolvedTokio {
+    // snake case keys were the only accepted ones before, they're still supported
+    /// Tokio worker thread count, tokio chooses it if it's not set.
+    #[serde(alias = "worker_threads")]
+    #[schemars(example = "example_worker_threads")]
     pub worker_threads: Option<usize>,
+    /// Max tokio blocking thread count, tokio chooses it if it's not set.
+    #[serde(alias = "max_blocking_threads")]
+    #[schemars(example = "example_max_blocking_threads")]
     pub max_blocking_threads: Option<usize>,
-    #[serde(default)]
+    /// Logical cores for tokio threads, empty list means all cores.
+    #[serde(default, alias = "utility_thread_ids")]
     pub utility_thread_ids: Vec<u32>,
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub struct UnresolvedProofs {
+    /// Re-hash each found proof in the light mode before storing it,
+    /// rejected proofs are counted in the ccp_rejected_proofs metric.
+    #[serde(default = "default_self_verification")]
+    pub self_verification: bool,
+
+    /// Proofs of how many previous epochs are kept in the archive and could be queried
+    /// by ccp_get_proofs_after with the global nonce of the epoch.
+    #[serde(default = "default_archived_epochs")]
+    pub archived_epochs: usize,
+
+    /// Found proofs could be additionally sent to a webhook or exported to a JSON-lines file,
+    /// a sink is set like `{ type = "webhook", url = "http://127.0.0.1:8080/proofs" }`.
+    #[serde(default)]
+    pub sinks: Vec<UnresolvedProofSinkConfig>,
+
+    /// Proofs stop being persisted until the next epoch, if they are found too often,
+    /// it protects the disk from a dangerously easy difficulty.
+    #[serde(default = "default_max_proofs_per_cu_per_minute")]
+    pub max_proofs_per_cu_per_minute: u64,
+
+    /// Like max-proofs-per-cu-per-minute, but for all proofs of an epoch.
+    #[serde(default = "default_max_proofs_per_epoch")]
+    pub max_proofs_per_epoch: u64,
+
+    /// on_active_commitment is rejected, if the difficulty has less leading zero bits.
+    #[serde(default = "default_min_difficulty_zero_bits")]
+    pub min_difficulty_zero_bits: u32,
+}
+
+/// Free space of the state directory filesystem is checked periodically and before writes.
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(rename_all = "kebab-case")]
+pub struct UnresolvedDiskGuard {
+    /// How often free space is checked, at least once a second.
+    #[serde(default = "default_disk_check_interval_secs")]
+    #[schemars(range(min = 1))]
+    pub check_interval_secs: u64,
+    /// A warning is logged below this level.
+    #[serde(default = "default_warn_free_space_mb")]
+    pub warn_free_space_mb: u64,
+    /// critical-policy is applied below this level.
+    #[serde(default = "default_critical_free_space_mb")]
+    pub critical_free_space_mb: u64,
+    /// What to do when free space is below the critical level.
+    #[serde(default)]
+    pub critical_policy: CriticalDiskPolicy,
+}
+
+impl Default for UnresolvedDiskGuard {
+    fn default() -> Self {
+        Self {
+            check_interval_secs: default_disk_check_interval_secs(),
+            warn_free_space_mb: default_warn_free_space_mb(),
+            critical_free_space_mb: default_critical_free_space_mb(),
+            critical_policy: CriticalDiskPolicy::default(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
+#[serde(tag = "type", rename_all = "kebab-case")]
+pub enum UnresolvedProofSinkConfig {
+    /// Proofs are posted as JSON, failed requests are retried.
+    #[serde(rename_all = "kebab-case")]
+    Webhook {
+        url: String,
+        #[serde(default = "default_webhook_max_retries")]
+        max_retries: u32,
+        #[serde(default = "default_webhook_retry_interval_ms")]
+        retry_interval_ms: u64,
+        /// New proofs aren't queued for delivery, while the outbox has this many entries.
+        #[serde(default = "default_webhook_max_outbox_entries")]
+        max_outbox_entries: usize,
+    },
+    /// Proofs are appended to a file one JSON per line.
+    JsonLines { path: std::path::PathBuf },
+}
+
+impl Default for UnresolvedProofs {
+    fn default() -> Self {
+        Self {
+            self_verification: default_self_verification(),
+            archived_epochs: default_archived_epochs(),
+            sinks: vec![],
+            max_proofs_per_cu_per_minute: default_max_proofs_per_cu_per_minute(),
+            max_proofs_per_epoch: default_max_proofs_per_epoch(),
+            min_difficulty_zero_bits: default_min_difficulty_zero_bits(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum Argon2Impl {
     AVX2,
@@ -159,7 +344,7 @@ pub enum Argon2Impl {
     Default,
 }
 
-#[derive(Clone, Debug, Serialize, Deserialize)]
+#[derive(Clone, Debug, Serialize, Deserialize, JsonSchema)]
 #[serde(rename_all = "lowercase")]
 pub enum LogLevel {
     Off,
@@ -179,12 +364,14 @@ impl UnresolvedCCPConfig {
             )
         })?;
 
-        let rpc_endpoint = self.rpc_endpoint.resolve();
+        let rpc_endpoint = self.rpc_endpoint.resolve(config_dir)?;
         let prometheus_endpoint = self.prometheus_endpoint.map(|cfg| cfg.resolve());
         let optimization = self.optimizations.resolve()?;
         let logs = self.logs.resolve();
         let workers = self.workers.resolve();
         let tokio = self.tokio.resolve();
+        let proofs = self.proofs.resolve(config_dir);
+        let disk_guard = self.disk_guard.resolve()?;
 
         let config = CCPConfig {
             rpc_endpoint,
@@ -194,18 +381,73 @@ impl UnresolvedCCPConfig {
             state_dir: config_dir.join(self.state.path),
             workers,
             tokio,
+            proofs,
+            disk_guard,
         };
         Ok(config)
     }
 }
 
 impl UnresolvedRpcEndpoint {
-    pub fn resolve(self) -> RpcEndpoint {
-        RpcEndpoint {
-            host: self.host,
-            port: self.port,
+    pub fn resolve(self, config_dir: &Path) -> eyre::Result<RpcEndpoint> {
+        let tcp_address = || -> eyre::Result<(String, u16)> {
+            match (self.host.clone(), self.port) {
+                (Some(host), Some(port)) => Ok((host, port)),
+                _ => Err(eyre!(
+                    "rpc-endpoint host and port are required for the {:?} transport",
+                    self.transport
+                )),
+            }
+        };
+
+        let transport = match self.transport {
+            RpcTransportKind::HttpAndWs => {
+                let (host, port) = tcp_address()?;
+                RpcTransport::HttpAndWs { host, port }
+            }
+            RpcTransportKind::Http => {
+                let (host, port) = tcp_address()?;
+                RpcTransport::Http { host, port }
+            }
+            RpcTransportKind::Ws => {
+                let (host, port) = tcp_address()?;
+                RpcTransport::Ws { host, port }
+            }
+            RpcTransportKind::Unix => {
+                let path = self.unix_socket_path.ok_or_else(|| {
+                    eyre!("rpc-endpoint unix-socket-path is required for the unix transport")
+                })?;
+                RpcTransport::Unix {
+                    path: config_dir.join(path),
+                    permissions: self.unix_socket_permissions,
+                }
+            }
+            RpcTransportKind::Stdio => RpcTransport::Stdio,
+        };
+
+        if self.auth.is_some() && transport == RpcTransport::Stdio {
+            return Err(eyre!(
+                "rpc-endpoint auth isn't supported for the stdio transport"
+            ));
+        }
+        let auth = self.auth.map(|auth| auth.resolve(config_dir));
+
+        let endpoint = RpcEndpoint {
+            transport,
             utility_queue_size: self.utility_queue_size,
             facade_queue_size: self.facade_queue_size,
+            auth,
+        };
+        Ok(endpoint)
+    }
+}
+
+impl UnresolvedRpcAuth {
+    pub fn resolve(self, config_dir: &Path) -> RpcAuth {
+        RpcAuth {
+            scheme: self.scheme,
+            secret_path: config_dir.join(self.secret_path),
+            read_only_secret_path: self.read_only_secret_path.map(|path| config_dir.join(path)),
         }
     }
 }
@@ -221,7 +463,7 @@ impl UnresolvedPrometheusEndpoint {
 
 impl UnresolvedOptimizations {
     pub fn resolve(self) -> eyre::Result<Optimizations> {
-        let randomx_flags = self.randomx.resolve();
+        let randomx_flags = self.randomx_flags();
         let msr_config = self.msr_enabled;
         let threads_per_core_policy = match self.threads_per_core {
             Some(threads_count) => ThreadsPerCoreAllocationPolicy::Exact {
@@ -237,10 +479,8 @@ impl UnresolvedOptimizations {
         };
         Ok(opt)
     }
-}
 
-impl UnresolvedRandomX {
-    pub fn resolve(self) -> RandomXFlags {
+    fn randomx_flags(&self) -> RandomXFlags {
         let mut randomx_flags = RandomXFlags::recommended_full_mem();
 
         if let Some(value) = self.large_pages {
@@ -259,7 +499,7 @@ impl UnresolvedRandomX {
             randomx_flags.set(RandomXFlags::FLAG_SECURE, value);
         }
 
-        match self.argon2 {
+        match &self.argon2 {
             Some(Argon2Impl::AVX2) => randomx_flags.set(RandomXFlags::FLAG_ARGON2_AVX2, true),
             Some(Argon2Impl::SSSE3) => randomx_flags.set(RandomXFlags::FLAG_ARGON2_SSSE3, true),
             Some(Argon2Impl::Default) => randomx_flags.set(RandomXFlags::FLAG_ARGON2, true),
@@ -314,6 +554,65 @@ impl Default for UnresolvedWorkers {
     }
 }
 
+impl UnresolvedProofs {
+    pub fn resolve(self, config_dir: &Path) -> Proofs {
+        Proofs {
+            self_verification: self.self_verification,
+            archived_epochs: self.archived_epochs,
+            sinks: self
+                .sinks
+                .into_iter()
+                .map(|sink| sink.resolve(config_dir))
+                .collect(),
+            max_proofs_per_cu_per_minute: self.max_proofs_per_cu_per_minute,
+            max_proofs_per_epoch: self.max_proofs_per_epoch,
+            min_difficulty_zero_bits: self.min_difficulty_zero_bits,
+        }
+    }
+}
+
+impl UnresolvedDiskGuard {
+    pub fn resolve(self) -> eyre::Result<DiskGuard> {
+        const BYTES_IN_MB: u64 = 1024 * 1024;
+
+        if self.check_interval_secs < MIN_DISK_CHECK_INTERVAL_SECS {
+            return Err(eyre!(
+                "disk-guard.check-interval-secs must be at least {MIN_DISK_CHECK_INTERVAL_SECS}, got {}",
+                self.check_interval_secs
+            ));
+        }
+
+        let disk_guard = DiskGuard {
+            check_interval: std::time::Duration::from_secs(self.check_interval_secs),
+            warn_free_space_bytes: self.warn_free_space_mb.saturating_mul(BYTES_IN_MB),
+            critical_free_space_bytes: self.critical_free_space_mb.saturating_mul(BYTES_IN_MB),
+            critical_policy: self.critical_policy,
+        };
+        Ok(disk_guard)
+    }
+}
+
+impl UnresolvedProofSinkConfig {
+    pub fn resolve(self, config_dir: &Path) -> ProofSinkConfig {
+        match self {
+            Self::Webhook {
+                url,
+                max_retries,
+                retry_interval_ms,
+                max_outbox_entries,
+            } => ProofSinkConfig::Webhook {
+                url,
+                max_retries,
+                retry_interval: std::time::Duration::from_millis(retry_interval_ms),
+                max_outbox_entries,
+            },
+            Self::JsonLines { path } => ProofSinkConfig::JsonLines {
+                path: config_dir.join(path),
+            },
+        }
+    }
+}
+
 impl UnresolvedTokio {
     pub fn resolve(self) -> Tokio {
         let utility_thread_ids = self
@@ -23,6 +23,9 @@ use crate::flags::RandomXFlags;
 use crate::try_alloc;
 use crate::RResult;
 
+/// RANDOMX_DATASET_ITEM_SIZE of the RandomX configuration.
+const DATASET_ITEM_SIZE: u64 = 64;
+
 #[derive(Debug)]
 pub struct Dataset {
     inner: Arc<DatasetInner>,
@@ -84,6 +87,12 @@ impl Dataset {
         unsafe { randomx_dataset_item_count() }
     }
 
+    /// Return a number of bytes a dataset takes in memory.
+    pub fn memory_size() -> u64 {
+        let items_count = unsafe { randomx_dataset_item_count() };
+        items_count * DATASET_ITEM_SIZE
+    }
+
     /// Initialize dataset with the provided cache.
     pub fn initialize(&mut self, cache: &impl CacheRawAPI, start_item: u64, items_count: u64) {
         unsafe { randomx_init_dataset(self.raw(), cache.raw(), start_item, items_count) };
@@ -33,6 +33,10 @@ pub struct RandomXVM<T> {
     _state: T,
 }
 
+// A VM mustn't be used from several threads simultaneously, but it could be moved
+// between threads, it doesn't rely on any thread-local state.
+unsafe impl<T: Send> Send for RandomXVM<T> {}
+
 impl<T> RandomXVM<T>
 where
     T: CacheRawAPI,
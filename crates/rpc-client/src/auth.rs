@@ -0,0 +1,343 @@
+/*
+ * Copyright 2024 Fluence Labs Limited
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::path::Path;
+use std::sync::Arc;
+use std::task::Context;
+use std::task::Poll;
+use std::time::Duration;
+use std::time::SystemTime;
+
+use futures::future::BoxFuture;
+use hmac::Hmac;
+use hmac::Mac;
+use hyper::header::HeaderValue;
+use hyper::header::AUTHORIZATION;
+use hyper::Body;
+use hyper::Request;
+use hyper::Response;
+use jsonrpsee::http_client::transport::Error as TransportError;
+use rand::RngCore;
+use sha3::Sha3_256;
+use tower::Layer;
+use tower::Service;
+
+/// `Authorization` header scheme of a shared bearer token.
+pub const BEARER_SCHEME: &str = "Bearer";
+
+/// `Authorization` header scheme of a signed request,
+/// the value is `<unix timestamp>:<hex nonce>:<hex signature>`.
+pub const HMAC_SCHEME: &str = "CCP-HMAC-SHA3-256";
+
+/// How far a signed timestamp may diverge from the server clock,
+/// the server remembers nonces for this long to reject replayed requests.
+pub const HMAC_MAX_CLOCK_SKEW: Duration = Duration::from_secs(300);
+
+const HMAC_NONCE_SIZE: usize = 16;
+
+type HmacSha3_256 = Hmac<Sha3_256>;
+
+/// Credentials presented to a CCP RPC endpoint with authentication enabled.
+#[derive(Clone)]
+pub enum RpcCredentials {
+    /// Sent as is in every request.
+    Bearer(String),
+    /// Signs each request with a fresh nonce, the secret itself is never sent.
+    Hmac(Vec<u8>),
+}
+
+impl RpcCredentials {
+    pub fn bearer_from_file(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        let secret = read_secret(path.as_ref())?;
+        let token = String::from_utf8(secret)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+        Ok(Self::Bearer(token))
+    }
+
+    pub fn hmac_from_file(path: impl AsRef<Path>) -> std::io::Result<Self> {
+        read_secret(path.as_ref()).map(Self::Hmac)
+    }
+
+    /// Returns a value of the `Authorization` header for a request with the provided
+    /// HTTP method, path and body.
+    pub fn authorization(&self, method: &str, path: &str, body: &[u8]) -> String {
+        match self {
+            Self::Bearer(token) => format!("{BEARER_SCHEME} {token}"),
+            Self::Hmac(secret) => SignedRequest {
+                method,
+                path,
+                timestamp: unix_timestamp(),
+                nonce: &generate_nonce(),
+                body,
+            }
+            .authorization(secret),
+        }
+    }
+}
+
+impl std::fmt::Debug for RpcCredentials {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Bearer(_) => f.write_str("Bearer(..)"),
+            Self::Hmac(_) => f.write_str("Hmac(..)"),
+        }
+    }
+}
+
+/// Adds the `Authorization` header to each HTTP request of the client.
+#[derive(Clone, Debug)]
+pub struct HttpAuthLayer {
+    credentials: Arc<RpcCredentials>,
+}
+
+impl HttpAuthLayer {
+    pub fn new(credentials: RpcCredentials) -> Self {
+        Self {
+            credentials: Arc::new(credentials),
+        }
+    }
+}
+
+impl<S> Layer<S> for HttpAuthLayer {
+    type Service = HttpAuthService<S>;
+
+    fn layer(&self, inner: S) -> Self::Service {
+        HttpAuthService {
+            inner,
+            credentials: self.credentials.clone(),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct HttpAuthService<S> {
+    inner: S,
+    credentials: Arc<RpcCredentials>,
+}
+
+impl<S> Service<Request<Body>> for HttpAuthService<S>
+where
+    S: Service<Request<Body>, Response = Response<Body>, Error = TransportError>
+        + Clone
+        + Send
+        + 'static,
+    S::Future: Send,
+{
+    type Response = Response<Body>;
+    type Error = TransportError;
+    type Future = BoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        self.inner.poll_ready(cx)
+    }
+
+    fn call(&mut self, request: Request<Body>) -> Self::Future {
+        // the service polled ready is taken, and a fresh clone is left in its place
+        let clone = self.inner.clone();
+        let mut inner = std::mem::replace(&mut self.inner, clone);
+        let credentials = self.credentials.clone();
+
+        Box::pin(async move {
+            let (mut parts, body) = request.into_parts();
+            // HMAC signs the body, so it has to be buffered
+            let body = hyper::body::to_bytes(body)
+                .await
+                .map_err(|e| TransportError::Http(Box::new(e)))?;
+            let authorization =
+                credentials.authorization(parts.method.as_str(), parts.uri.path(), &body);
+            let authorization = HeaderValue::from_str(&authorization)
+                .map_err(|e| TransportError::Http(Box::new(e)))?;
+            parts.headers.insert(AUTHORIZATION, authorization);
+
+            inner
+                .call(Request::from_parts(parts, Body::from(body)))
+                .await
+        })
+    }
+}
+
+/// Reads a secret from a file ignoring surrounding whitespaces, so that a trailing newline
+/// left by an editor doesn't become a part of it.
+pub fn read_secret(path: &Path) -> std::io::Result<Vec<u8>> {
+    let content = std::fs::read(path)?;
+    let start = content
+        .iter()
+        .position(|byte| !byte.is_ascii_whitespace())
+        .unwrap_or(content.len());
+    let end = content
+        .iter()
+        .rposition(|byte| !byte.is_ascii_whitespace())
+        .map_or(start, |last| last + 1);
+    let secret = &content[start..end];
+    if secret.is_empty() {
+        return Err(std::io::Error::new(
+            std::io::ErrorKind::InvalidData,
+            format!("secret file {} is empty", path.display()),
+        ));
+    }
+
+    Ok(secret.to_vec())
+}
+
+/// The parts of an HTTP request covered by an HMAC signature.
+#[derive(Clone, Copy, Debug)]
+pub struct SignedRequest<'a> {
+    pub method: &'a str,
+    pub path: &'a str,
+    pub timestamp: u64,
+    /// Makes each signature unique, so the server can reject a replayed one.
+    pub nonce: &'a str,
+    pub body: &'a [u8],
+}
+
+impl SignedRequest<'_> {
+    pub fn authorization(&self, secret: &[u8]) -> String {
+        format!(
+            "{HMAC_SCHEME} {}:{}:{}",
+            self.timestamp,
+            self.nonce,
+            hex::encode(self.signature(secret))
+        )
+    }
+
+    pub fn signature(&self, secret: &[u8]) -> [u8; 32] {
+        self.mac(secret).finalize().into_bytes().into()
+    }
+
+    /// Checks a signature in constant time.
+    pub fn verify(&self, secret: &[u8], signature: &[u8]) -> bool {
+        self.mac(secret).verify_slice(signature).is_ok()
+    }
+
+    /// Signs `<method>\n<path>\n<timestamp>\n<nonce>\n<body>`.
+    fn mac(&self, secret: &[u8]) -> HmacSha3_256 {
+        let timestamp = self.timestamp.to_string();
+        hmac_sha3_256(
+            secret,
+            &[
+                self.method.as_bytes(),
+                self.path.as_bytes(),
+                timestamp.as_bytes(),
+                self.nonce.as_bytes(),
+                self.body,
+            ]
+            .join(&b'\n'),
+        )
+    }
+}
+
+fn hmac_sha3_256(key: &[u8], message: &[u8]) -> HmacSha3_256 {
+    let mut mac = HmacSha3_256::new_from_slice(key).expect("HMAC accepts keys of any size");
+    mac.update(message);
+    mac
+}
+
+pub fn generate_nonce() -> String {
+    let mut nonce = [0u8; HMAC_NONCE_SIZE];
+    rand::thread_rng().fill_bytes(&mut nonce);
+    hex::encode(nonce)
+}
+
+pub fn unix_timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .map(|duration| duration.as_secs())
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn signed_request(timestamp: u64) -> SignedRequest<'static> {
+        SignedRequest {
+            method: "POST",
+            path: "/",
+            timestamp,
+            nonce: "00112233445566778899aabbccddeeff",
+            body: b"{}",
+        }
+    }
+
+    #[test]
+    fn hmac_sha3_256_matches_reference() {
+        let mac = hmac_sha3_256(b"key", b"The quick brown fox jumps over the lazy dog");
+        assert_eq!(
+            hex::encode(mac.finalize().into_bytes()),
+            "8c6e0683409427f8931711b10ca92a506eb1fafa48fadd66d76126f47ac2c333"
+        );
+    }
+
+    #[test]
+    fn hmac_hashes_long_keys() {
+        let mac = hmac_sha3_256(&[b'k'; 200], b"1700000000\n{}");
+        assert_eq!(
+            hex::encode(mac.finalize().into_bytes()),
+            "d910e2b5fd7734ed803b33be68c496245029220d5e8697339ee2d9d39e40cfc3"
+        );
+    }
+
+    #[test]
+    fn signature_covers_all_request_parts() {
+        let request = signed_request(42);
+        let signature = request.signature(b"secret");
+        assert!(request.verify(b"secret", &signature));
+        assert!(!request.verify(b"other", &signature));
+
+        let changed = [
+            SignedRequest {
+                method: "GET",
+                ..request
+            },
+            SignedRequest {
+                path: "/other",
+                ..request
+            },
+            SignedRequest {
+                timestamp: 43,
+                ..request
+            },
+            SignedRequest {
+                nonce: "ffeeddccbbaa99887766554433221100",
+                ..request
+            },
+            SignedRequest {
+                body: b"[]",
+                ..request
+            },
+        ];
+        for changed in changed {
+            assert!(!changed.verify(b"secret", &signature), "{changed:?}");
+        }
+    }
+
+    #[test]
+    fn hmac_authorization_format() {
+        let request = signed_request(42);
+        let expected = format!(
+            "{HMAC_SCHEME} 42:00112233445566778899aabbccddeeff:{}",
+            hex::encode(request.signature(b"secret"))
+        );
+        assert_eq!(request.authorization(b"secret"), expected);
+    }
+
+    #[test]
+    fn nonces_are_unique() {
+        let nonce = generate_nonce();
+        assert_eq!(nonce.len(), 2 * HMAC_NONCE_SIZE);
+        assert_ne!(nonce, generate_nonce());
+    }
+}
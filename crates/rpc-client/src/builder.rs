@@ -0,0 +1,293 @@
+/*
+ * Copyright 2024 Fluence Labs Limited
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::future::Future;
+use std::path::Path;
+use std::time::Duration;
+
+use jsonrpsee::async_client::Client;
+use jsonrpsee::core::ClientError;
+use jsonrpsee::http_client::HttpClientBuilder;
+use jsonrpsee::ws_client::HeaderMap;
+use jsonrpsee::ws_client::HeaderValue;
+use jsonrpsee::ws_client::WsClientBuilder;
+
+use crate::auth::HttpAuthLayer;
+use crate::auth::RpcCredentials;
+use crate::error::CCPError;
+use crate::error::CCPErrorCode;
+use crate::AuthenticatedHttpClient;
+use crate::CCPRpcHttpClient;
+
+// the same as the jsonrpsee default
+const DEFAULT_REQUEST_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Defines how idempotent calls, e.g. `get_proofs_after` or `get_status`, are retried
+/// on transport failures, timeouts and busy prover errors.
+/// Calls changing the prover state are never retried.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RetryPolicy {
+    pub max_retries: u32,
+    /// A delay before the first retry, it's doubled for each next one.
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+}
+
+impl RetryPolicy {
+    pub fn none() -> Self {
+        Self {
+            max_retries: 0,
+            initial_backoff: Duration::ZERO,
+            max_backoff: Duration::ZERO,
+        }
+    }
+
+    pub fn exponential(max_retries: u32, initial_backoff: Duration, max_backoff: Duration) -> Self {
+        Self {
+            max_retries,
+            initial_backoff,
+            max_backoff,
+        }
+    }
+
+    fn backoff(&self, retry: u32) -> Duration {
+        let factor = 2u32.saturating_pow(retry);
+        self.initial_backoff
+            .saturating_mul(factor)
+            .min(self.max_backoff)
+    }
+
+    pub(crate) async fn retry<T, F, Fut>(&self, mut call: F) -> Result<T, ClientError>
+    where
+        F: FnMut() -> Fut,
+        Fut: Future<Output = Result<T, ClientError>>,
+    {
+        let mut retry = 0;
+        loop {
+            match call().await {
+                Err(error) if retry < self.max_retries && is_retriable(&error) => {
+                    tokio::time::sleep(self.backoff(retry)).await;
+                    retry += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self::none()
+    }
+}
+
+fn is_retriable(error: &ClientError) -> bool {
+    match error {
+        ClientError::Transport(_) | ClientError::RequestTimeout => true,
+        ClientError::Call(_) => {
+            CCPError::from_client_error(error).is_some_and(|error| error.code == CCPErrorCode::Busy)
+        }
+        _ => false,
+    }
+}
+
+/// Builds a [`CCPRpcHttpClient`] with a request timeout and a retry policy,
+/// the transport is defined by a build method.
+#[derive(Clone, Debug)]
+pub struct CCPRpcClientBuilder {
+    request_timeout: Duration,
+    retry_policy: RetryPolicy,
+}
+
+impl Default for CCPRpcClientBuilder {
+    fn default() -> Self {
+        Self {
+            request_timeout: DEFAULT_REQUEST_TIMEOUT,
+            retry_policy: RetryPolicy::none(),
+        }
+    }
+}
+
+impl CCPRpcClientBuilder {
+    /// A timeout of each attempt of a call.
+    pub fn request_timeout(mut self, request_timeout: Duration) -> Self {
+        self.request_timeout = request_timeout;
+        self
+    }
+
+    pub fn retry_policy(mut self, retry_policy: RetryPolicy) -> Self {
+        self.retry_policy = retry_policy;
+        self
+    }
+
+    pub fn build_http(self, endpoint_url: String) -> Result<CCPRpcHttpClient, ClientError> {
+        let inner = HttpClientBuilder::default()
+            .request_timeout(self.request_timeout)
+            .build(endpoint_url)?;
+
+        Ok(self.client(inner))
+    }
+
+    pub fn build_http_authenticated(
+        self,
+        endpoint_url: String,
+        credentials: RpcCredentials,
+    ) -> Result<CCPRpcHttpClient<AuthenticatedHttpClient>, ClientError> {
+        let middleware = tower::ServiceBuilder::new().layer(HttpAuthLayer::new(credentials));
+        let inner = HttpClientBuilder::default()
+            .request_timeout(self.request_timeout)
+            .set_http_middleware(middleware)
+            .build(endpoint_url)?;
+
+        Ok(self.client(inner))
+    }
+
+    /// Connects to a WebSocket endpoint, e.g. `ws://127.0.0.1:9383`,
+    /// credentials are checked once on connection.
+    pub async fn build_ws(
+        self,
+        endpoint_url: String,
+        credentials: Option<&RpcCredentials>,
+    ) -> Result<CCPRpcHttpClient<Client>, ClientError> {
+        let inner = ws_client_builder(credentials, &endpoint_url)?
+            .request_timeout(self.request_timeout)
+            .build(endpoint_url)
+            .await?;
+
+        Ok(self.client(inner))
+    }
+
+    /// Connects to an endpoint on a Unix domain socket, WebSocket is used over it.
+    pub async fn build_unix(
+        self,
+        socket_path: impl AsRef<Path>,
+        credentials: Option<&RpcCredentials>,
+    ) -> Result<CCPRpcHttpClient<Client>, ClientError> {
+        let stream = tokio::net::UnixStream::connect(socket_path)
+            .await
+            .map_err(|e| ClientError::Transport(e.into()))?;
+        // the host isn't used, since the connection is already established
+        let endpoint_url = "ws://localhost";
+        let inner = ws_client_builder(credentials, endpoint_url)?
+            .request_timeout(self.request_timeout)
+            .build_with_stream(endpoint_url, stream)
+            .await?;
+
+        Ok(self.client(inner))
+    }
+
+    fn client<C>(&self, inner: C) -> CCPRpcHttpClient<C> {
+        CCPRpcHttpClient {
+            inner,
+            retry_policy: self.retry_policy,
+        }
+    }
+}
+
+/// Credentials are checked on the handshake, it's a body-less GET request to the endpoint path.
+pub(crate) fn ws_client_builder(
+    credentials: Option<&RpcCredentials>,
+    endpoint_url: &str,
+) -> Result<WsClientBuilder, ClientError> {
+    let mut headers = HeaderMap::new();
+    if let Some(credentials) = credentials {
+        let endpoint_url = endpoint_url
+            .parse::<hyper::Uri>()
+            .map_err(|e| ClientError::Transport(e.into()))?;
+        let authorization = credentials.authorization("GET", endpoint_url.path(), &[]);
+        let authorization =
+            HeaderValue::from_str(&authorization).map_err(|e| ClientError::Transport(e.into()))?;
+        headers.insert("authorization", authorization);
+    }
+
+    Ok(WsClientBuilder::default().set_headers(headers))
+}
+
+/// A result of a health probe of a CCP endpoint.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Health {
+    Healthy {
+        latency: Duration,
+    },
+    /// The endpoint responds, but the prover is applying a commitment change.
+    Busy {
+        latency: Duration,
+    },
+    Unhealthy {
+        error: String,
+    },
+}
+
+impl Health {
+    pub fn is_reachable(&self) -> bool {
+        !matches!(self, Self::Unhealthy { .. })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn backoff_is_doubled_and_capped() {
+        let policy =
+            RetryPolicy::exponential(10, Duration::from_millis(100), Duration::from_secs(1));
+
+        assert_eq!(policy.backoff(0), Duration::from_millis(100));
+        assert_eq!(policy.backoff(2), Duration::from_millis(400));
+        assert_eq!(policy.backoff(4), Duration::from_secs(1));
+        assert_eq!(policy.backoff(40), Duration::from_secs(1));
+    }
+
+    #[tokio::test]
+    async fn only_retriable_errors_are_retried() {
+        let policy = RetryPolicy::exponential(2, Duration::ZERO, Duration::ZERO);
+
+        let mut attempts = 0;
+        let result: Result<(), _> = policy
+            .retry(|| {
+                attempts += 1;
+                async { Err(ClientError::RequestTimeout) }
+            })
+            .await;
+        assert!(matches!(result, Err(ClientError::RequestTimeout)));
+        assert_eq!(attempts, 3);
+
+        let mut attempts = 0;
+        let result: Result<(), _> = policy
+            .retry(|| {
+                attempts += 1;
+                async { Err(ClientError::Call(CCPError::internal("failed").into())) }
+            })
+            .await;
+        assert!(result.is_err());
+        assert_eq!(attempts, 1);
+
+        let mut attempts = 0;
+        let result = policy
+            .retry(|| {
+                attempts += 1;
+                let result = if attempts == 1 {
+                    Err(ClientError::Call(CCPError::busy("busy").into()))
+                } else {
+                    Ok(attempts)
+                };
+                async move { result }
+            })
+            .await;
+        assert_eq!(result.unwrap(), 2);
+    }
+}
@@ -0,0 +1,221 @@
+/*
+ * Copyright 2024 Fluence Labs Limited
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use jsonrpsee::core::ClientError;
+use jsonrpsee::types::ErrorObjectOwned;
+use serde::Deserialize;
+use serde::Serialize;
+use thiserror::Error as ThisError;
+
+use ccp_shared::types::PhysicalCoreId;
+
+/// Codes of errors returned by CCP methods, they don't overlap with codes
+/// reserved by the JSON-RPC specification.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(into = "i32", try_from = "i32")]
+#[repr(i32)]
+pub enum CCPErrorCode {
+    /// An unclassified failure, the message describes it.
+    Internal = 1,
+    /// A parameter is malformed or rejected, e.g. a non-hex nonce or an absurd difficulty,
+    /// `data` names the parameter if it's known.
+    InvalidParams = 2,
+    /// The credentials don't allow calling the method.
+    Forbidden = 3,
+    /// A core isn't found in the CPU topology, `data` contains its id if it's known.
+    CoreNotFound = 4,
+    /// Memory for a RandomX cache or dataset can't be allocated.
+    DatasetAllocationFailed = 5,
+    /// MSR registers can't be read or written.
+    MsrFailure = 6,
+    /// The state directory or proof storage isn't accessible.
+    StorageIo = 7,
+    /// The prover is applying a commitment change, the call could be retried later.
+    Busy = 8,
+    /// Several CU provers failed, `data` contains an error per CU prover.
+    CUProversFailed = 9,
+    /// The feature behind the method isn't enabled.
+    Unavailable = 10,
+}
+
+/// Machine-readable details of an error.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum CCPErrorData {
+    Core {
+        core_id: PhysicalCoreId,
+    },
+    InvalidParam {
+        param: String,
+    },
+    #[serde(rename = "cu_prover_errors")]
+    CUProverErrors {
+        errors: Vec<CUProverErrorData>,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CUProverErrorData {
+    pub code: CCPErrorCode,
+    pub message: String,
+    pub core_id: Option<PhysicalCoreId>,
+}
+
+/// An error of a CCP method, it's sent as the JSON-RPC error object.
+#[derive(ThisError, Clone, Debug, PartialEq, Eq)]
+#[error("{message}")]
+pub struct CCPError {
+    pub code: CCPErrorCode,
+    pub message: String,
+    pub data: Option<CCPErrorData>,
+}
+
+impl CCPErrorCode {
+    pub fn code(self) -> i32 {
+        self as i32
+    }
+
+    pub fn from_code(code: i32) -> Option<Self> {
+        use CCPErrorCode::*;
+
+        [
+            Internal,
+            InvalidParams,
+            Forbidden,
+            CoreNotFound,
+            DatasetAllocationFailed,
+            MsrFailure,
+            StorageIo,
+            Busy,
+            CUProversFailed,
+            Unavailable,
+        ]
+        .into_iter()
+        .find(|known| known.code() == code)
+    }
+}
+
+impl From<CCPErrorCode> for i32 {
+    fn from(code: CCPErrorCode) -> Self {
+        code.code()
+    }
+}
+
+impl TryFrom<i32> for CCPErrorCode {
+    type Error = String;
+
+    fn try_from(code: i32) -> Result<Self, Self::Error> {
+        Self::from_code(code).ok_or_else(|| format!("unknown CCP error code {code}"))
+    }
+}
+
+impl CCPError {
+    pub fn new(code: CCPErrorCode, message: impl ToString) -> Self {
+        Self {
+            code,
+            message: message.to_string(),
+            data: None,
+        }
+    }
+
+    pub fn with_data(mut self, data: CCPErrorData) -> Self {
+        self.data = Some(data);
+        self
+    }
+
+    pub fn internal(message: impl ToString) -> Self {
+        Self::new(CCPErrorCode::Internal, message)
+    }
+
+    pub fn invalid_param(param: impl ToString, message: impl ToString) -> Self {
+        let data = CCPErrorData::InvalidParam {
+            param: param.to_string(),
+        };
+        Self::new(CCPErrorCode::InvalidParams, message).with_data(data)
+    }
+
+    pub fn busy(message: impl ToString) -> Self {
+        Self::new(CCPErrorCode::Busy, message)
+    }
+
+    /// Returns a CCP error if the client error is a CCP method error,
+    /// errors with unknown codes, e.g. of the JSON-RPC protocol, are skipped.
+    pub fn from_client_error(error: &ClientError) -> Option<Self> {
+        match error {
+            ClientError::Call(error) => Self::from_error_object(error),
+            _ => None,
+        }
+    }
+
+    pub fn from_error_object(error: &ErrorObjectOwned) -> Option<Self> {
+        let code = CCPErrorCode::from_code(error.code())?;
+        // data of an unknown shape is skipped, the code and the message are still useful
+        let data = error
+            .data()
+            .and_then(|data| serde_json::from_str(data.get()).ok());
+
+        Some(Self {
+            code,
+            message: error.message().to_string(),
+            data,
+        })
+    }
+}
+
+impl From<CCPError> for ErrorObjectOwned {
+    fn from(error: CCPError) -> Self {
+        ErrorObjectOwned::owned(error.code.code(), error.message, error.data)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn error_object_roundtrip() {
+        let error = CCPError::new(CCPErrorCode::CUProversFailed, "CU provers failed").with_data(
+            CCPErrorData::CUProverErrors {
+                errors: vec![CUProverErrorData {
+                    code: CCPErrorCode::CoreNotFound,
+                    message: "physical core with 3 id not found".to_string(),
+                    core_id: Some(3.into()),
+                }],
+            },
+        );
+
+        let object = ErrorObjectOwned::from(error.clone());
+        assert_eq!(object.code(), 9);
+        let data = serde_json::to_value(object.data().unwrap()).unwrap();
+        assert_eq!(
+            data,
+            serde_json::json!({
+                "type": "cu_prover_errors",
+                "errors": [{"code": 4, "message": "physical core with 3 id not found", "core_id": 3}],
+            })
+        );
+
+        let parsed = CCPError::from_client_error(&ClientError::Call(object));
+        assert_eq!(parsed, Some(error));
+    }
+
+    #[test]
+    fn unknown_codes_are_skipped() {
+        let object = ErrorObjectOwned::owned::<()>(-32601, "Method not found", None);
+        assert_eq!(CCPError::from_error_object(&object), None);
+        assert_eq!(CCPErrorCode::from_code(0), None);
+    }
+}
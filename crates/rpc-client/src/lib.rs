@@ -26,66 +26,239 @@
     unreachable_patterns
 )]
 
+pub mod auth;
+mod builder;
+pub mod error;
+mod line_transport;
+mod log_filter;
+#[cfg(feature = "mock")]
+pub mod mock;
 mod or_hex;
 
 use std::collections::HashMap;
+use std::path::Path;
+use std::time::Instant;
 
 use ccp_shared::proof::ProofIdx;
 use ccp_shared::types::LogicalCoreId;
+use jsonrpsee::async_client::Client;
+use jsonrpsee::core::client::ClientT;
+use jsonrpsee::core::client::Subscription;
 use jsonrpsee::core::ClientError;
+use jsonrpsee::core::SubscriptionResult;
+use jsonrpsee::http_client::transport::HttpBackend;
+use jsonrpsee::http_client::HttpClient;
 use jsonrpsee::proc_macros::rpc;
 use jsonrpsee::types::ErrorObjectOwned;
+use tokio::io::AsyncRead;
+use tokio::io::AsyncWrite;
 
+use ccp_shared::alignment_plan::AlignmentPlan;
+use ccp_shared::commitment::CommitmentGeneration;
+use ccp_shared::commitment::CommitmentProgress;
+use ccp_shared::hashrate::HashrateReport;
 use ccp_shared::proof::CCProof;
+use ccp_shared::status::ProverStatus;
 use ccp_shared::types::Difficulty;
 use ccp_shared::types::GlobalNonce;
 use ccp_shared::types::PhysicalCoreId;
 use ccp_shared::types::CUID;
 
+use crate::auth::HttpAuthService;
+use crate::auth::RpcCredentials;
+use crate::builder::ws_client_builder;
+use crate::error::CCPError;
+use crate::error::CCPErrorCode;
+
+pub use crate::builder::CCPRpcClientBuilder;
+pub use crate::builder::Health;
+pub use crate::builder::RetryPolicy;
+pub use crate::log_filter::LogFilterStatus;
 pub use crate::or_hex::OrHex;
 
+/// An HTTP client which authenticates each request.
+pub type AuthenticatedHttpClient = HttpClient<HttpAuthService<HttpBackend>>;
+
 // n.b.: the rpc macro also defines CcpRpcClient type which is a working async JSON RPC client.
 #[rpc(server, client, namespace = "ccp")]
 pub trait CCPRpc {
+    /// Returns a generation of the commitment change, see `get_commitment_progress`.
     #[method(name = "on_active_commitment", param_kind = map)]
     async fn on_active_commitment(
         &self,
         global_nonce: OrHex<GlobalNonce>,
         difficulty: OrHex<Difficulty>,
         cu_allocation: HashMap<PhysicalCoreId, OrHex<CUID>>,
-    ) -> Result<(), ErrorObjectOwned>;
+    ) -> Result<CommitmentGeneration, ErrorObjectOwned>;
 
+    /// Returns actions `on_active_commitment` would take with the same parameters,
+    /// nothing is changed.
+    #[method(name = "plan_active_commitment", param_kind = map)]
+    async fn plan_active_commitment(
+        &self,
+        global_nonce: OrHex<GlobalNonce>,
+        difficulty: OrHex<Difficulty>,
+        cu_allocation: HashMap<PhysicalCoreId, OrHex<CUID>>,
+    ) -> Result<AlignmentPlan, ErrorObjectOwned>;
+
+    /// Returns a generation of the commitment change, see `get_commitment_progress`.
     #[method(name = "on_no_active_commitment")]
-    async fn on_no_active_commitment(&self) -> Result<(), ErrorObjectOwned>;
+    async fn on_no_active_commitment(&self) -> Result<CommitmentGeneration, ErrorObjectOwned>;
+
+    /// Returns how far CCP is in applying accepted commitment changes.
+    #[method(name = "get_commitment_progress")]
+    async fn get_commitment_progress(&self) -> Result<CommitmentProgress, ErrorObjectOwned>;
 
     #[method(name = "get_proofs_after")]
     async fn get_proofs_after(
         &self,
         proof_idx: ProofIdx,
         limit: usize,
+        global_nonce: Option<OrHex<GlobalNonce>>,
     ) -> Result<Vec<CCProof>, ErrorObjectOwned>;
 
+    #[method(name = "ack_proofs", param_kind = map)]
+    async fn ack_proofs(
+        &self,
+        global_nonce: OrHex<GlobalNonce>,
+        proof_idx: ProofIdx,
+    ) -> Result<(), ErrorObjectOwned>;
+
+    /// Returns a snapshot of the prover state.
+    #[method(name = "get_status")]
+    async fn get_status(&self) -> Result<ProverStatus, ErrorObjectOwned>;
+
+    /// Returns cumulative and sliding hashrate per logical core and per CU.
+    #[method(name = "get_hashrate")]
+    async fn get_hashrate(&self) -> Result<HashrateReport, ErrorObjectOwned>;
+
+    /// Suspends hashing, caches and datasets are kept to resume it without re-initialization.
+    #[method(name = "pause")]
+    async fn pause(&self) -> Result<(), ErrorObjectOwned>;
+
+    #[method(name = "resume")]
+    async fn resume(&self) -> Result<(), ErrorObjectOwned>;
+
+    #[method(name = "get_log_filter")]
+    async fn get_log_filter(&self) -> Result<LogFilterStatus, ErrorObjectOwned>;
+
+    /// Replaces the log filter with the directive, or with the configured one if it's absent.
+    /// A replaced filter is reverted to the configured one after `revert_after_secs`, if set,
+    /// it must not exceed a week.
+    #[method(name = "set_log_filter", param_kind = map)]
+    async fn set_log_filter(
+        &self,
+        directive: Option<String>,
+        revert_after_secs: Option<u64>,
+    ) -> Result<LogFilterStatus, ErrorObjectOwned>;
+
     #[method(name = "realloc_utility_cores", param_kind = map)]
     async fn realloc_utility_cores(&self, utility_core_ids: Vec<LogicalCoreId>);
 }
 
-pub struct CCPRpcHttpClient {
-    inner: jsonrpsee::http_client::HttpClient,
+// Subscriptions require a client with subscription support (i.e. WebSocket),
+// so they are defined separately to keep CCPRpc usable over plain HTTP.
+#[rpc(server, client, namespace = "ccp")]
+pub trait CCPSubscriptionRpc {
+    /// Replays stored proofs of the current epoch after `from_idx`,
+    /// then streams each new proof as soon as it's stored.
+    #[subscription(name = "subscribe_proofs" => "proof", unsubscribe = "unsubscribe_proofs", item = CCProof)]
+    async fn subscribe_proofs(&self, from_idx: ProofIdx) -> SubscriptionResult;
+}
+
+/// A client of the CCP JSON-RPC, the transport is defined by a constructor:
+/// HTTP by default, WebSocket, a Unix domain socket or pipes of a CCP child process.
+/// Use [`CCPRpcClientBuilder`] to set a request timeout and retries.
+pub struct CCPRpcHttpClient<C = HttpClient> {
+    inner: C,
+    retry_policy: RetryPolicy,
 }
 
-impl CCPRpcHttpClient {
+impl CCPRpcHttpClient<HttpClient> {
     pub async fn new(endpoint_url: String) -> Result<Self, ClientError> {
-        let inner = jsonrpsee::http_client::HttpClientBuilder::default().build(endpoint_url)?;
+        CCPRpcClientBuilder::default().build_http(endpoint_url)
+    }
+}
 
-        Ok(Self { inner })
+impl CCPRpcHttpClient<AuthenticatedHttpClient> {
+    /// Connects to an HTTP endpoint with authentication enabled.
+    pub async fn new_authenticated(
+        endpoint_url: String,
+        credentials: RpcCredentials,
+    ) -> Result<Self, ClientError> {
+        CCPRpcClientBuilder::default().build_http_authenticated(endpoint_url, credentials)
+    }
+}
+
+impl CCPRpcHttpClient<Client> {
+    /// Connects to a WebSocket endpoint, e.g. `ws://127.0.0.1:9383`.
+    pub async fn new_ws(endpoint_url: String) -> Result<Self, ClientError> {
+        CCPRpcClientBuilder::default()
+            .build_ws(endpoint_url, None)
+            .await
+    }
+
+    /// Connects to a WebSocket endpoint with authentication enabled,
+    /// credentials are checked once on connection.
+    pub async fn new_ws_authenticated(
+        endpoint_url: String,
+        credentials: RpcCredentials,
+    ) -> Result<Self, ClientError> {
+        CCPRpcClientBuilder::default()
+            .build_ws(endpoint_url, Some(&credentials))
+            .await
     }
 
+    /// Connects to an endpoint on a Unix domain socket, WebSocket is used over it.
+    pub async fn new_unix(socket_path: impl AsRef<Path>) -> Result<Self, ClientError> {
+        CCPRpcClientBuilder::default()
+            .build_unix(socket_path, None)
+            .await
+    }
+
+    pub async fn new_unix_authenticated(
+        socket_path: impl AsRef<Path>,
+        credentials: RpcCredentials,
+    ) -> Result<Self, ClientError> {
+        CCPRpcClientBuilder::default()
+            .build_unix(socket_path, Some(&credentials))
+            .await
+    }
+
+    /// Talks to a CCP child process run with the stdio transport, e.g.
+    /// `new_stdio(child.stdin.take().unwrap(), child.stdout.take().unwrap())`.
+    pub fn new_stdio(
+        to_ccp: impl AsyncWrite + Unpin + Send + 'static,
+        from_ccp: impl AsyncRead + Unpin + Send + 'static,
+    ) -> Self {
+        let (sender, receiver) = line_transport::line_transport(to_ccp, from_ccp);
+        let inner =
+            jsonrpsee::async_client::ClientBuilder::default().build_with_tokio(sender, receiver);
+
+        Self {
+            inner,
+            retry_policy: RetryPolicy::none(),
+        }
+    }
+
+    pub async fn subscribe_proofs(
+        &self,
+        from_idx: ProofIdx,
+    ) -> Result<Subscription<CCProof>, ClientError> {
+        CCPSubscriptionRpcClient::subscribe_proofs(&self.inner, from_idx).await
+    }
+}
+
+impl<C> CCPRpcHttpClient<C>
+where
+    C: ClientT + Send + Sync,
+{
     pub async fn on_active_commitment(
         &self,
         global_nonce: GlobalNonce,
         difficulty: Difficulty,
         cu_allocation: HashMap<PhysicalCoreId, CUID>,
-    ) -> Result<(), ClientError> {
+    ) -> Result<CommitmentGeneration, ClientError> {
         let cu_allocation = cu_allocation
             .into_iter()
             .map(|(k, v)| (k, v.into()))
@@ -99,16 +272,130 @@ impl CCPRpcHttpClient {
         .await
     }
 
-    pub async fn on_no_active_commitment(&self) -> Result<(), ClientError> {
+    pub async fn plan_active_commitment(
+        &self,
+        global_nonce: GlobalNonce,
+        difficulty: Difficulty,
+        cu_allocation: HashMap<PhysicalCoreId, CUID>,
+    ) -> Result<AlignmentPlan, ClientError> {
+        let cu_allocation: HashMap<_, OrHex<CUID>> = cu_allocation
+            .into_iter()
+            .map(|(k, v)| (k, v.into()))
+            .collect();
+        self.retry_policy
+            .retry(|| {
+                CCPRpcClient::plan_active_commitment(
+                    &self.inner,
+                    global_nonce.into(),
+                    difficulty.into(),
+                    cu_allocation.clone(),
+                )
+            })
+            .await
+    }
+
+    pub async fn on_no_active_commitment(&self) -> Result<CommitmentGeneration, ClientError> {
         CCPRpcClient::on_no_active_commitment(&self.inner).await
     }
 
+    pub async fn get_commitment_progress(&self) -> Result<CommitmentProgress, ClientError> {
+        self.retry_policy
+            .retry(|| CCPRpcClient::get_commitment_progress(&self.inner))
+            .await
+    }
+
     pub async fn get_proofs_after(
         &self,
         proof_idx: ProofIdx,
         limit: usize,
     ) -> Result<Vec<CCProof>, ClientError> {
-        CCPRpcClient::get_proofs_after(&self.inner, proof_idx, limit).await
+        self.retry_policy
+            .retry(|| CCPRpcClient::get_proofs_after(&self.inner, proof_idx, limit, None))
+            .await
+    }
+
+    /// Gets proofs of the epoch with the provided global nonce, which could be
+    /// either the current epoch or an archived one.
+    pub async fn get_epoch_proofs_after(
+        &self,
+        global_nonce: GlobalNonce,
+        proof_idx: ProofIdx,
+        limit: usize,
+    ) -> Result<Vec<CCProof>, ClientError> {
+        self.retry_policy
+            .retry(|| {
+                CCPRpcClient::get_proofs_after(
+                    &self.inner,
+                    proof_idx,
+                    limit,
+                    Some(global_nonce.into()),
+                )
+            })
+            .await
+    }
+
+    pub async fn ack_proofs(
+        &self,
+        global_nonce: GlobalNonce,
+        proof_idx: ProofIdx,
+    ) -> Result<(), ClientError> {
+        self.retry_policy
+            .retry(|| CCPRpcClient::ack_proofs(&self.inner, global_nonce.into(), proof_idx))
+            .await
+    }
+
+    pub async fn get_status(&self) -> Result<ProverStatus, ClientError> {
+        self.retry_policy
+            .retry(|| CCPRpcClient::get_status(&self.inner))
+            .await
+    }
+
+    /// Calls `get_status` once, without retries, to check that the endpoint responds.
+    pub async fn probe_health(&self) -> Health {
+        let started = Instant::now();
+        let result = CCPRpcClient::get_status(&self.inner).await;
+        let latency = started.elapsed();
+
+        match result {
+            Ok(_) => Health::Healthy { latency },
+            Err(error) => match CCPError::from_client_error(&error) {
+                Some(CCPError {
+                    code: CCPErrorCode::Busy,
+                    ..
+                }) => Health::Busy { latency },
+                _ => Health::Unhealthy {
+                    error: error.to_string(),
+                },
+            },
+        }
+    }
+
+    pub async fn get_hashrate(&self) -> Result<HashrateReport, ClientError> {
+        self.retry_policy
+            .retry(|| CCPRpcClient::get_hashrate(&self.inner))
+            .await
+    }
+
+    pub async fn pause(&self) -> Result<(), ClientError> {
+        CCPRpcClient::pause(&self.inner).await
+    }
+
+    pub async fn resume(&self) -> Result<(), ClientError> {
+        CCPRpcClient::resume(&self.inner).await
+    }
+
+    pub async fn get_log_filter(&self) -> Result<LogFilterStatus, ClientError> {
+        self.retry_policy
+            .retry(|| CCPRpcClient::get_log_filter(&self.inner))
+            .await
+    }
+
+    pub async fn set_log_filter(
+        &self,
+        directive: Option<String>,
+        revert_after_secs: Option<u64>,
+    ) -> Result<LogFilterStatus, ClientError> {
+        CCPRpcClient::set_log_filter(&self.inner, directive, revert_after_secs).await
     }
 
     pub async fn realloc_utility_cores(
@@ -118,3 +405,38 @@ impl CCPRpcHttpClient {
         CCPRpcClient::realloc_utility_cores(&self.inner, utility_core_ids).await
     }
 }
+
+/// A WebSocket client for proof subscriptions, the server serves both HTTP and WebSocket
+/// on the same address, so the endpoint is the same as for [`CCPRpcHttpClient`]
+/// but with the `ws://` scheme.
+pub struct CCPProofSubscriber {
+    inner: jsonrpsee::ws_client::WsClient,
+}
+
+impl CCPProofSubscriber {
+    pub async fn new(endpoint_url: String) -> Result<Self, ClientError> {
+        let inner = ws_client_builder(None, &endpoint_url)?
+            .build(endpoint_url)
+            .await?;
+
+        Ok(Self { inner })
+    }
+
+    pub async fn new_authenticated(
+        endpoint_url: String,
+        credentials: RpcCredentials,
+    ) -> Result<Self, ClientError> {
+        let inner = ws_client_builder(Some(&credentials), &endpoint_url)?
+            .build(endpoint_url)
+            .await?;
+
+        Ok(Self { inner })
+    }
+
+    pub async fn subscribe_proofs(
+        &self,
+        from_idx: ProofIdx,
+    ) -> Result<Subscription<CCProof>, ClientError> {
+        CCPSubscriptionRpcClient::subscribe_proofs(&self.inner, from_idx).await
+    }
+}
@@ -0,0 +1,128 @@
+/*
+ * Copyright 2024 Fluence Labs Limited
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use jsonrpsee::core::async_trait;
+use jsonrpsee::core::client::ReceivedMessage;
+use jsonrpsee::core::client::TransportReceiverT;
+use jsonrpsee::core::client::TransportSenderT;
+use tokio::io::AsyncBufReadExt;
+use tokio::io::AsyncRead;
+use tokio::io::AsyncWrite;
+use tokio::io::AsyncWriteExt;
+use tokio::io::BufReader;
+use tokio::io::Lines;
+
+/// Newline-delimited JSON-RPC transport, as served by the CCP stdio transport.
+pub(crate) fn line_transport<W, R>(writer: W, reader: R) -> (LineSender<W>, LineReceiver<R>)
+where
+    W: AsyncWrite + Unpin + Send + 'static,
+    R: AsyncRead + Unpin + Send + 'static,
+{
+    let sender = LineSender { writer };
+    let receiver = LineReceiver {
+        lines: BufReader::new(reader).lines(),
+    };
+    (sender, receiver)
+}
+
+pub(crate) struct LineSender<W> {
+    writer: W,
+}
+
+pub(crate) struct LineReceiver<R> {
+    lines: Lines<BufReader<R>>,
+}
+
+#[async_trait]
+impl<W> TransportSenderT for LineSender<W>
+where
+    W: AsyncWrite + Unpin + Send + 'static,
+{
+    type Error = std::io::Error;
+
+    async fn send(&mut self, message: String) -> Result<(), Self::Error> {
+        self.writer.write_all(message.as_bytes()).await?;
+        self.writer.write_all(b"\n").await?;
+        self.writer.flush().await
+    }
+}
+
+#[async_trait]
+impl<R> TransportReceiverT for LineReceiver<R>
+where
+    R: AsyncRead + Unpin + Send + 'static,
+{
+    type Error = std::io::Error;
+
+    async fn receive(&mut self) -> Result<ReceivedMessage, Self::Error> {
+        match self.lines.next_line().await? {
+            Some(line) => Ok(ReceivedMessage::Text(line)),
+            None => Err(std::io::Error::new(
+                std::io::ErrorKind::UnexpectedEof,
+                "the JSON-RPC server closed its output",
+            )),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use jsonrpsee::core::client::ClientT;
+    use jsonrpsee::rpc_params;
+    use serde_json::json;
+    use serde_json::Value;
+    use tokio::io::AsyncBufReadExt;
+    use tokio::io::AsyncWriteExt;
+    use tokio::io::BufReader;
+    use tokio::net::UnixStream;
+
+    use super::line_transport;
+
+    #[tokio::test]
+    async fn requests_and_responses_are_lines() {
+        let (client_side, server_side) = UnixStream::pair().unwrap();
+        let (client_reader, client_writer) = client_side.into_split();
+        let (sender, receiver) = line_transport(client_writer, client_reader);
+        let client =
+            jsonrpsee::async_client::ClientBuilder::default().build_with_tokio(sender, receiver);
+
+        let server = tokio::spawn(async move {
+            let (server_reader, mut server_writer) = server_side.into_split();
+            let mut requests = BufReader::new(server_reader).lines();
+            let request = requests.next_line().await.unwrap().unwrap();
+            let request: Value = serde_json::from_str(&request).unwrap();
+            assert_eq!(request["method"], "echo");
+
+            let response = json!({
+                "jsonrpc": "2.0",
+                "result": request["params"][0],
+                "id": request["id"],
+            });
+            server_writer
+                .write_all(format!("{response}\n").as_bytes())
+                .await
+                .unwrap();
+        });
+
+        let response: String = client.request("echo", rpc_params!["hello"]).await.unwrap();
+        assert_eq!(response, "hello");
+        server.await.unwrap();
+
+        // the server closed its output
+        let result: Result<String, _> = client.request("echo", rpc_params!["hello"]).await;
+        assert!(result.is_err());
+    }
+}
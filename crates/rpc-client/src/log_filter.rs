@@ -0,0 +1,30 @@
+/*
+ * Copyright 2024 Fluence Labs Limited
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use serde::Deserialize;
+use serde::Serialize;
+
+/// Shows the log filter of a running CCP.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LogFilterStatus {
+    /// Directives of the filter in effect, in the same format as the `CCP_LOG` env var.
+    pub directive: String,
+    /// Directives set by the config and the `CCP_LOG` env var at startup,
+    /// a changed filter is reverted to them.
+    pub configured_directive: String,
+    /// Unix timestamp in seconds when the filter will be reverted to the configured one.
+    pub revert_at: Option<u64>,
+}
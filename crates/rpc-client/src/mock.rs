@@ -0,0 +1,393 @@
+/*
+ * Copyright 2024 Fluence Labs Limited
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+//! An in-process CCP server for tests of CCP clients, e.g. Nox,
+//! it's available with the `mock` feature.
+//!
+//! ```ignore
+//! let mock = MockCCPRpc::new();
+//! mock.push_response("on_active_commitment", Ok(1));
+//! let server = mock.start().await?;
+//! let client = CCPRpcHttpClient::new(server.url()).await?;
+//! ```
+
+use std::collections::HashMap;
+use std::collections::VecDeque;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use std::sync::Mutex;
+
+use jsonrpsee::core::async_trait;
+use jsonrpsee::server::Server;
+use jsonrpsee::server::ServerHandle;
+use jsonrpsee::types::ErrorObjectOwned;
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::json;
+use serde_json::Value;
+
+use ccp_shared::alignment_plan::AlignmentPlan;
+use ccp_shared::commitment::CommitmentGeneration;
+use ccp_shared::commitment::CommitmentProgress;
+use ccp_shared::hashrate::HashrateReport;
+use ccp_shared::proof::CCProof;
+use ccp_shared::proof::ProofIdx;
+use ccp_shared::status::ProverStatus;
+use ccp_shared::types::Difficulty;
+use ccp_shared::types::GlobalNonce;
+use ccp_shared::types::LogicalCoreId;
+use ccp_shared::types::PhysicalCoreId;
+use ccp_shared::types::CUID;
+
+use crate::error::CCPError;
+use crate::CCPRpcServer;
+use crate::LogFilterStatus;
+use crate::OrHex;
+
+type MockResponse = Result<Value, CCPError>;
+
+/// Responds to `CCPRpc` methods with scripted responses and records calls,
+/// subscriptions aren't supported.
+///
+/// Methods are named without the namespace, e.g. `get_status`.
+#[derive(Clone, Default)]
+pub struct MockCCPRpc {
+    state: Arc<Mutex<MockState>>,
+}
+
+#[derive(Default)]
+struct MockState {
+    scripted: HashMap<String, VecDeque<MockResponse>>,
+    fallback: HashMap<String, MockResponse>,
+    calls: Vec<RecordedCall>,
+}
+
+/// A call received by the mock, params are named as in the method definition.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RecordedCall {
+    pub method: String,
+    pub params: Value,
+}
+
+/// A running mock server, it's stopped on drop.
+pub struct MockCCPServer {
+    local_addr: SocketAddr,
+    handle: ServerHandle,
+}
+
+impl MockCCPRpc {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Responds to the next call of the method, pushed responses are returned in order.
+    pub fn push_response<T: Serialize>(&self, method: &str, response: Result<T, CCPError>) {
+        let response = to_mock_response(method, response);
+        self.state
+            .lock()
+            .unwrap()
+            .scripted
+            .entry(method.to_string())
+            .or_default()
+            .push_back(response);
+    }
+
+    /// Responds to calls of the method when there are no pushed responses left.
+    pub fn set_response<T: Serialize>(&self, method: &str, response: Result<T, CCPError>) {
+        let response = to_mock_response(method, response);
+        self.state
+            .lock()
+            .unwrap()
+            .fallback
+            .insert(method.to_string(), response);
+    }
+
+    pub fn calls(&self) -> Vec<RecordedCall> {
+        self.state.lock().unwrap().calls.clone()
+    }
+
+    pub fn calls_of(&self, method: &str) -> Vec<RecordedCall> {
+        self.state
+            .lock()
+            .unwrap()
+            .calls
+            .iter()
+            .filter(|call| call.method == method)
+            .cloned()
+            .collect()
+    }
+
+    /// Serves HTTP and WebSocket on a random local port.
+    pub async fn start(&self) -> std::io::Result<MockCCPServer> {
+        let server = Server::builder().build("127.0.0.1:0").await?;
+        let local_addr = server.local_addr()?;
+        let handle = server.start(self.clone().into_rpc());
+
+        Ok(MockCCPServer { local_addr, handle })
+    }
+
+    fn respond<T: DeserializeOwned>(&self, method: &str, params: Value) -> Result<T, CCPError> {
+        let mut state = self.state.lock().unwrap();
+        state.calls.push(RecordedCall {
+            method: method.to_string(),
+            params,
+        });
+
+        let response = state
+            .scripted
+            .get_mut(method)
+            .and_then(VecDeque::pop_front)
+            .or_else(|| state.fallback.get(method).cloned())
+            .unwrap_or_else(|| {
+                Err(CCPError::internal(format!(
+                    "no response is scripted for {method}"
+                )))
+            })?;
+
+        serde_json::from_value(response).map_err(|e| {
+            CCPError::internal(format!(
+                "the scripted response for {method} doesn't match its result: {e}"
+            ))
+        })
+    }
+}
+
+fn to_mock_response<T: Serialize>(method: &str, response: Result<T, CCPError>) -> MockResponse {
+    response.map(|value| {
+        serde_json::to_value(value)
+            .unwrap_or_else(|e| panic!("the response for {method} isn't serializable: {e}"))
+    })
+}
+
+impl MockCCPServer {
+    pub fn local_addr(&self) -> SocketAddr {
+        self.local_addr
+    }
+
+    pub fn url(&self) -> String {
+        format!("http://{}", self.local_addr)
+    }
+
+    pub fn ws_url(&self) -> String {
+        format!("ws://{}", self.local_addr)
+    }
+}
+
+impl Drop for MockCCPServer {
+    fn drop(&mut self) {
+        // it fails only if the server is already stopped
+        let _ = self.handle.stop();
+    }
+}
+
+#[async_trait]
+impl CCPRpcServer for MockCCPRpc {
+    async fn on_active_commitment(
+        &self,
+        global_nonce: OrHex<GlobalNonce>,
+        difficulty: OrHex<Difficulty>,
+        cu_allocation: HashMap<PhysicalCoreId, OrHex<CUID>>,
+    ) -> Result<CommitmentGeneration, ErrorObjectOwned> {
+        let params = json!({
+            "global_nonce": global_nonce,
+            "difficulty": difficulty,
+            "cu_allocation": cu_allocation,
+        });
+        Ok(self.respond("on_active_commitment", params)?)
+    }
+
+    async fn plan_active_commitment(
+        &self,
+        global_nonce: OrHex<GlobalNonce>,
+        difficulty: OrHex<Difficulty>,
+        cu_allocation: HashMap<PhysicalCoreId, OrHex<CUID>>,
+    ) -> Result<AlignmentPlan, ErrorObjectOwned> {
+        let params = json!({
+            "global_nonce": global_nonce,
+            "difficulty": difficulty,
+            "cu_allocation": cu_allocation,
+        });
+        Ok(self.respond("plan_active_commitment", params)?)
+    }
+
+    async fn on_no_active_commitment(&self) -> Result<CommitmentGeneration, ErrorObjectOwned> {
+        Ok(self.respond("on_no_active_commitment", json!({}))?)
+    }
+
+    async fn get_commitment_progress(&self) -> Result<CommitmentProgress, ErrorObjectOwned> {
+        Ok(self.respond("get_commitment_progress", json!({}))?)
+    }
+
+    async fn get_proofs_after(
+        &self,
+        proof_idx: ProofIdx,
+        limit: usize,
+        global_nonce: Option<OrHex<GlobalNonce>>,
+    ) -> Result<Vec<CCProof>, ErrorObjectOwned> {
+        let params = json!({
+            "proof_idx": proof_idx,
+            "limit": limit,
+            "global_nonce": global_nonce,
+        });
+        Ok(self.respond("get_proofs_after", params)?)
+    }
+
+    async fn ack_proofs(
+        &self,
+        global_nonce: OrHex<GlobalNonce>,
+        proof_idx: ProofIdx,
+    ) -> Result<(), ErrorObjectOwned> {
+        let params = json!({
+            "global_nonce": global_nonce,
+            "proof_idx": proof_idx,
+        });
+        Ok(self.respond("ack_proofs", params)?)
+    }
+
+    async fn get_status(&self) -> Result<ProverStatus, ErrorObjectOwned> {
+        Ok(self.respond("get_status", json!({}))?)
+    }
+
+    async fn get_hashrate(&self) -> Result<HashrateReport, ErrorObjectOwned> {
+        Ok(self.respond("get_hashrate", json!({}))?)
+    }
+
+    async fn pause(&self) -> Result<(), ErrorObjectOwned> {
+        Ok(self.respond("pause", json!({}))?)
+    }
+
+    async fn resume(&self) -> Result<(), ErrorObjectOwned> {
+        Ok(self.respond("resume", json!({}))?)
+    }
+
+    async fn get_log_filter(&self) -> Result<LogFilterStatus, ErrorObjectOwned> {
+        Ok(self.respond("get_log_filter", json!({}))?)
+    }
+
+    async fn set_log_filter(
+        &self,
+        directive: Option<String>,
+        revert_after_secs: Option<u64>,
+    ) -> Result<LogFilterStatus, ErrorObjectOwned> {
+        let params = json!({
+            "directive": directive,
+            "revert_after_secs": revert_after_secs,
+        });
+        Ok(self.respond("set_log_filter", params)?)
+    }
+
+    async fn realloc_utility_cores(&self, utility_core_ids: Vec<LogicalCoreId>) {
+        let params = json!({ "utility_core_ids": utility_core_ids });
+        // the method has no result, so only the call is recorded
+        let _ = self.respond::<()>("realloc_utility_cores", params);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::error::CCPErrorCode;
+    use crate::CCPRpcClientBuilder;
+    use crate::CCPRpcHttpClient;
+    use crate::Health;
+    use crate::RetryPolicy;
+
+    fn log_filter_status(directive: &str) -> LogFilterStatus {
+        LogFilterStatus {
+            directive: directive.to_string(),
+            configured_directive: "info".to_string(),
+            revert_at: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn responses_are_scripted_and_calls_recorded() {
+        let mock = MockCCPRpc::new();
+        mock.push_response("set_log_filter", Ok(log_filter_status("debug")));
+        mock.set_response("set_log_filter", Ok(log_filter_status("info")));
+        let server = mock.start().await.unwrap();
+        let client = CCPRpcHttpClient::new(server.url()).await.unwrap();
+
+        let status = client
+            .set_log_filter(Some("debug".to_string()), Some(60))
+            .await
+            .unwrap();
+        assert_eq!(status, log_filter_status("debug"));
+        let status = client.set_log_filter(None, None).await.unwrap();
+        assert_eq!(status, log_filter_status("info"));
+
+        let calls = mock.calls_of("set_log_filter");
+        assert_eq!(calls.len(), 2);
+        assert_eq!(
+            calls[0].params,
+            json!({"directive": "debug", "revert_after_secs": 60})
+        );
+
+        let error = client.get_hashrate().await.unwrap_err();
+        let error = CCPError::from_client_error(&error).unwrap();
+        assert_eq!(error.code, CCPErrorCode::Internal);
+    }
+
+    #[tokio::test]
+    async fn busy_calls_are_retried() {
+        let mock = MockCCPRpc::new();
+        mock.push_response::<()>("get_log_filter", Err(CCPError::busy("busy")));
+        mock.push_response("get_log_filter", Ok(log_filter_status("info")));
+        mock.push_response::<()>("pause", Err(CCPError::busy("busy")));
+        let server = mock.start().await.unwrap();
+        let client = CCPRpcClientBuilder::default()
+            .retry_policy(RetryPolicy::exponential(
+                3,
+                Duration::from_millis(1),
+                Duration::from_millis(10),
+            ))
+            .build_http(server.url())
+            .unwrap();
+
+        let status = client.get_log_filter().await.unwrap();
+        assert_eq!(status, log_filter_status("info"));
+        assert_eq!(mock.calls_of("get_log_filter").len(), 2);
+
+        // calls changing the prover state aren't retried
+        assert!(client.pause().await.is_err());
+        assert_eq!(mock.calls_of("pause").len(), 1);
+    }
+
+    #[tokio::test]
+    async fn health_is_probed() {
+        let mock = MockCCPRpc::new();
+        mock.push_response::<()>("get_status", Err(CCPError::busy("busy")));
+        let server = mock.start().await.unwrap();
+        let client = CCPRpcHttpClient::new(server.url()).await.unwrap();
+
+        assert!(matches!(client.probe_health().await, Health::Busy { .. }));
+        // there is no scripted status, so the mock fails
+        assert!(!matches!(
+            client.probe_health().await,
+            Health::Healthy { .. }
+        ));
+
+        let url = server.url();
+        std::mem::drop(server);
+        let client = CCPRpcClientBuilder::default()
+            .request_timeout(Duration::from_secs(1))
+            .build_http(url)
+            .unwrap();
+        assert!(!client.probe_health().await.is_reachable());
+    }
+}
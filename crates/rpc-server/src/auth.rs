@@ -0,0 +1,478 @@
+/*
+ * Copyright 2024 Fluence Labs Limited
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::collections::HashMap;
+use std::path::Path;
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::sync::MutexGuard;
+use std::sync::PoisonError;
+
+use ccp_rpc_client::auth::read_secret;
+use ccp_rpc_client::auth::unix_timestamp;
+use ccp_rpc_client::auth::SignedRequest;
+use ccp_rpc_client::auth::BEARER_SCHEME;
+use ccp_rpc_client::auth::HMAC_MAX_CLOCK_SKEW;
+use ccp_rpc_client::auth::HMAC_SCHEME;
+use ccp_rpc_client::error::CCPError;
+use ccp_rpc_client::error::CCPErrorCode;
+use hyper::body::HttpBody;
+use hyper::header::AUTHORIZATION;
+use hyper::header::WWW_AUTHENTICATE;
+use hyper::Body;
+use hyper::Request;
+use hyper::Response;
+use hyper::StatusCode;
+use jsonrpsee::types::ErrorObjectOwned;
+use jsonrpsee::Methods;
+use jsonrpsee::RpcModule;
+use subtle::ConstantTimeEq;
+use thiserror::Error as ThisError;
+
+/// Methods which don't change the prover state, they are available with the read-only secret.
+const READ_ONLY_METHODS: &[&str] = &[
+    "ccp_get_proofs_after",
+    "ccp_get_status",
+    "ccp_get_hashrate",
+    "ccp_get_commitment_progress",
+    "ccp_get_log_filter",
+    "ccp_plan_active_commitment",
+    "ccp_subscribe_proofs",
+    "ccp_unsubscribe_proofs",
+];
+
+// the largest request is on_active_commitment, a CU allocation takes ~100 bytes per core
+const MAX_SIGNED_BODY_SIZE: usize = 256 * 1024;
+// nonces are generated as 32 hex digits
+const MAX_NONCE_LEN: usize = 64;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum RpcAuthScheme {
+    Bearer,
+    Hmac,
+}
+
+/// Authentication of the RPC endpoint, see [`ccp_rpc_client::auth`] for the client side.
+#[derive(Clone)]
+pub struct RpcAuth {
+    scheme: RpcAuthScheme,
+    secret: Vec<u8>,
+    read_only_secret: Option<Vec<u8>>,
+}
+
+impl RpcAuth {
+    pub fn new(scheme: RpcAuthScheme, secret: Vec<u8>, read_only_secret: Option<Vec<u8>>) -> Self {
+        Self {
+            scheme,
+            secret,
+            read_only_secret,
+        }
+    }
+
+    pub fn from_files(
+        scheme: RpcAuthScheme,
+        secret_path: &Path,
+        read_only_secret_path: Option<&Path>,
+    ) -> std::io::Result<Self> {
+        let secret = read_secret(secret_path)?;
+        let read_only_secret = read_only_secret_path.map(read_secret).transpose()?;
+        Ok(Self::new(scheme, secret, read_only_secret))
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub(crate) enum AccessLevel {
+    ReadOnly,
+    Control,
+}
+
+#[derive(ThisError, Debug)]
+pub(crate) enum AuthError {
+    #[error("authorization header is missing")]
+    Missing,
+
+    #[error("authorization header is malformed")]
+    Malformed,
+
+    #[error("signature timestamp is outside of the allowed clock skew")]
+    Expired,
+
+    #[error("credentials are invalid")]
+    Invalid,
+
+    #[error("signature nonce was already used")]
+    Replayed,
+
+    #[error("request body is too large")]
+    BodyTooLarge,
+
+    #[error("failed to read request body: {0}")]
+    BodyRead(#[from] hyper::Error),
+}
+
+/// Parsed `<timestamp>:<nonce>:<hex signature>` credentials of the HMAC scheme.
+struct HmacCredentials<'a> {
+    timestamp: u64,
+    nonce: &'a str,
+    signature: Vec<u8>,
+}
+
+/// Checks credentials of each HTTP request, including WebSocket handshakes.
+pub(crate) struct Authorizer {
+    auth: RpcAuth,
+    read_only_methods: Methods,
+    /// Nonces of accepted HMAC signatures with their timestamps,
+    /// they are kept while the timestamps are within the clock skew.
+    seen_nonces: Mutex<HashMap<String, u64>>,
+}
+
+impl Authorizer {
+    pub(crate) fn new(auth: RpcAuth, methods: &Methods) -> Arc<Self> {
+        Arc::new(Self {
+            auth,
+            read_only_methods: read_only_methods(methods),
+            seen_nonces: Mutex::new(HashMap::new()),
+        })
+    }
+
+    pub(crate) fn read_only_methods(&self) -> Methods {
+        self.read_only_methods.clone()
+    }
+
+    /// Returns the access level and the request to pass further,
+    /// its body is buffered for the HMAC scheme.
+    pub(crate) async fn authorize(
+        &self,
+        request: Request<Body>,
+    ) -> Result<(AccessLevel, Request<Body>), AuthError> {
+        let authorization = request
+            .headers()
+            .get(AUTHORIZATION)
+            .ok_or(AuthError::Missing)?
+            .to_str()
+            .map_err(|_| AuthError::Malformed)?
+            .to_string();
+
+        match self.auth.scheme {
+            RpcAuthScheme::Bearer => {
+                let level = self.bearer_access_level(&authorization)?;
+                Ok((level, request))
+            }
+            RpcAuthScheme::Hmac => {
+                let now = unix_timestamp();
+                // a body is buffered only for fresh credentials
+                let credentials = self.hmac_credentials(&authorization, now)?;
+                let (parts, body) = request.into_parts();
+                let body = read_body(body).await?;
+                let level = self.hmac_access_level(
+                    &credentials,
+                    parts.method.as_str(),
+                    parts.uri.path(),
+                    &body,
+                    now,
+                )?;
+                Ok((level, Request::from_parts(parts, Body::from(body))))
+            }
+        }
+    }
+
+    pub(crate) fn unauthorized(&self, error: &AuthError) -> Response<Body> {
+        let status = match error {
+            AuthError::BodyTooLarge => StatusCode::PAYLOAD_TOO_LARGE,
+            AuthError::BodyRead(_) => StatusCode::BAD_REQUEST,
+            _ => StatusCode::UNAUTHORIZED,
+        };
+        let scheme = match self.auth.scheme {
+            RpcAuthScheme::Bearer => BEARER_SCHEME,
+            RpcAuthScheme::Hmac => HMAC_SCHEME,
+        };
+
+        let mut response = Response::new(Body::from(error.to_string()));
+        *response.status_mut() = status;
+        response.headers_mut().insert(
+            WWW_AUTHENTICATE,
+            hyper::header::HeaderValue::from_static(scheme),
+        );
+        response
+    }
+
+    fn bearer_access_level(&self, authorization: &str) -> Result<AccessLevel, AuthError> {
+        let token = authorization
+            .strip_prefix(BEARER_SCHEME)
+            .and_then(|rest| rest.strip_prefix(' '))
+            .ok_or(AuthError::Malformed)?;
+
+        self.access_level(|secret| secret.ct_eq(token.as_bytes()).into())
+    }
+
+    /// Parses the header and checks everything, what doesn't need the body.
+    fn hmac_credentials<'a>(
+        &self,
+        authorization: &'a str,
+        now: u64,
+    ) -> Result<HmacCredentials<'a>, AuthError> {
+        let mut parts = authorization
+            .strip_prefix(HMAC_SCHEME)
+            .and_then(|rest| rest.strip_prefix(' '))
+            .ok_or(AuthError::Malformed)?
+            .split(':');
+        let (Some(timestamp), Some(nonce), Some(signature), None) =
+            (parts.next(), parts.next(), parts.next(), parts.next())
+        else {
+            return Err(AuthError::Malformed);
+        };
+        let timestamp: u64 = timestamp.parse().map_err(|_| AuthError::Malformed)?;
+        if nonce.is_empty()
+            || nonce.len() > MAX_NONCE_LEN
+            || !nonce.bytes().all(|b| b.is_ascii_alphanumeric())
+        {
+            return Err(AuthError::Malformed);
+        }
+        let signature = hex::decode(signature).map_err(|_| AuthError::Malformed)?;
+
+        if !is_fresh(timestamp, now) {
+            return Err(AuthError::Expired);
+        }
+        let seen_timestamp = self.lock_seen_nonces().get(nonce).copied();
+        if matches!(seen_timestamp, Some(seen_timestamp) if is_fresh(seen_timestamp, now)) {
+            return Err(AuthError::Replayed);
+        }
+
+        Ok(HmacCredentials {
+            timestamp,
+            nonce,
+            signature,
+        })
+    }
+
+    fn hmac_access_level(
+        &self,
+        credentials: &HmacCredentials<'_>,
+        method: &str,
+        path: &str,
+        body: &[u8],
+        now: u64,
+    ) -> Result<AccessLevel, AuthError> {
+        let request = SignedRequest {
+            method,
+            path,
+            timestamp: credentials.timestamp,
+            nonce: credentials.nonce,
+            body,
+        };
+        let level = self.access_level(|secret| request.verify(secret, &credentials.signature))?;
+
+        // only nonces of valid signatures are remembered, so they can't be flooded;
+        // a concurrent request with the same nonce could pass the check above
+        if !self.remember_nonce(credentials.nonce, credentials.timestamp, now) {
+            return Err(AuthError::Replayed);
+        }
+
+        Ok(level)
+    }
+
+    /// Returns false if the nonce was already seen.
+    fn remember_nonce(&self, nonce: &str, timestamp: u64, now: u64) -> bool {
+        let mut seen_nonces = self.lock_seen_nonces();
+        // requests with these nonces are rejected as expired anyway
+        seen_nonces.retain(|_, seen_timestamp| is_fresh(*seen_timestamp, now));
+
+        seen_nonces.insert(nonce.to_string(), timestamp).is_none()
+    }
+
+    fn lock_seen_nonces(&self) -> MutexGuard<'_, HashMap<String, u64>> {
+        self.seen_nonces
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    fn access_level(&self, matches: impl Fn(&[u8]) -> bool) -> Result<AccessLevel, AuthError> {
+        if matches(&self.auth.secret) {
+            return Ok(AccessLevel::Control);
+        }
+
+        match &self.auth.read_only_secret {
+            Some(secret) if matches(secret) => Ok(AccessLevel::ReadOnly),
+            _ => Err(AuthError::Invalid),
+        }
+    }
+}
+
+fn is_fresh(timestamp: u64, now: u64) -> bool {
+    now.abs_diff(timestamp) <= HMAC_MAX_CLOCK_SKEW.as_secs()
+}
+
+async fn read_body(mut body: Body) -> Result<Vec<u8>, AuthError> {
+    let mut buffer = Vec::new();
+    while let Some(chunk) = body.data().await {
+        let chunk = chunk?;
+        if buffer.len() + chunk.len() > MAX_SIGNED_BODY_SIZE {
+            return Err(AuthError::BodyTooLarge);
+        }
+        buffer.extend_from_slice(&chunk);
+    }
+
+    Ok(buffer)
+}
+
+/// Keeps read-only methods as is, and replaces others with a stub returning an error,
+/// so that a client gets a clear error instead of "method not found".
+fn read_only_methods(methods: &Methods) -> Methods {
+    let mut read_only = RpcModule::new(());
+    for name in methods.method_names() {
+        let result = match methods.method_with_name(name) {
+            Some((name, callback)) if READ_ONLY_METHODS.contains(&name) => read_only
+                .verify_and_insert(name, callback.clone())
+                .map(|_| ()),
+            _ => read_only
+                .register_method(name, |_, _| Err::<(), _>(forbidden_error()))
+                .map(|_| ()),
+        };
+        result.expect("method names are unique, since they are taken from Methods");
+    }
+
+    read_only.into()
+}
+
+fn forbidden_error() -> ErrorObjectOwned {
+    CCPError::new(
+        CCPErrorCode::Forbidden,
+        "the method requires the control secret, the read-only one was provided",
+    )
+    .into()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn authorizer(scheme: RpcAuthScheme) -> Authorizer {
+        let auth = RpcAuth::new(scheme, b"control".to_vec(), Some(b"read-only".to_vec()));
+        Authorizer {
+            auth,
+            read_only_methods: Methods::new(),
+            seen_nonces: Mutex::new(HashMap::new()),
+        }
+    }
+
+    #[test]
+    fn bearer_access_levels() {
+        let authorizer = authorizer(RpcAuthScheme::Bearer);
+
+        let level = authorizer.bearer_access_level("Bearer control").unwrap();
+        assert_eq!(level, AccessLevel::Control);
+        let level = authorizer.bearer_access_level("Bearer read-only").unwrap();
+        assert_eq!(level, AccessLevel::ReadOnly);
+
+        let result = authorizer.bearer_access_level("Bearer other");
+        assert!(matches!(result, Err(AuthError::Invalid)));
+        let result = authorizer.bearer_access_level("Basic control");
+        assert!(matches!(result, Err(AuthError::Malformed)));
+    }
+
+    const BODY: &[u8] = br#"{"jsonrpc":"2.0","method":"ccp_get_status","id":0}"#;
+
+    fn hmac_authorization(secret: &[u8], timestamp: u64, nonce: &str) -> String {
+        SignedRequest {
+            method: "POST",
+            path: "/",
+            timestamp,
+            nonce,
+            body: BODY,
+        }
+        .authorization(secret)
+    }
+
+    #[test]
+    fn hmac_access_levels() {
+        let authorizer = authorizer(RpcAuthScheme::Hmac);
+        let now = 1_700_000_000;
+        let access_level = |authorization: &str, path| {
+            let credentials = authorizer.hmac_credentials(authorization, now)?;
+            authorizer.hmac_access_level(&credentials, "POST", path, BODY, now)
+        };
+
+        let authorization = hmac_authorization(b"control", now, "1");
+        let level = access_level(&authorization, "/").unwrap();
+        assert_eq!(level, AccessLevel::Control);
+
+        let authorization = hmac_authorization(b"read-only", now - 10, "2");
+        let level = access_level(&authorization, "/").unwrap();
+        assert_eq!(level, AccessLevel::ReadOnly);
+
+        let authorization = hmac_authorization(b"control", now, "3");
+        let result = access_level(&authorization, "/other");
+        assert!(matches!(result, Err(AuthError::Invalid)));
+
+        let authorization = hmac_authorization(b"control", now - 301, "4");
+        let result = access_level(&authorization, "/");
+        assert!(matches!(result, Err(AuthError::Expired)));
+
+        let result = access_level(&format!("{HMAC_SCHEME} {now}:00"), "/");
+        assert!(matches!(result, Err(AuthError::Malformed)));
+        let result = access_level(&format!("{HMAC_SCHEME} {now}:a/b:00"), "/");
+        assert!(matches!(result, Err(AuthError::Malformed)));
+    }
+
+    #[test]
+    fn replayed_hmac_nonces_are_rejected() {
+        let authorizer = authorizer(RpcAuthScheme::Hmac);
+        let now = 1_700_000_000;
+        let access_level = |authorization: &str, now| {
+            let credentials = authorizer.hmac_credentials(authorization, now)?;
+            authorizer.hmac_access_level(&credentials, "POST", "/", BODY, now)
+        };
+
+        let authorization = hmac_authorization(b"control", now, "nonce");
+        assert!(access_level(&authorization, now).is_ok());
+        let result = access_level(&authorization, now + 1);
+        assert!(matches!(result, Err(AuthError::Replayed)));
+
+        // a nonce is forgotten, once its signature has expired
+        let authorization = hmac_authorization(b"control", now + 400, "nonce");
+        assert!(access_level(&authorization, now + 400).is_ok());
+    }
+
+    #[tokio::test]
+    async fn hmac_header_is_checked_before_body() {
+        let authorizer = authorizer(RpcAuthScheme::Hmac);
+        // the body fails to be read, so an error about it means that it was read
+        let request = || {
+            let (sender, body) = Body::channel();
+            sender.abort();
+            Request::post("/").body(body).unwrap()
+        };
+        let with_authorization = |authorization: String| {
+            let mut request = request();
+            request
+                .headers_mut()
+                .insert(AUTHORIZATION, authorization.parse().unwrap());
+            request
+        };
+
+        let expired = hmac_authorization(b"control", unix_timestamp() - 301, "1");
+        let result = authorizer.authorize(with_authorization(expired)).await;
+        assert!(matches!(result, Err(AuthError::Expired)));
+
+        let malformed = format!("{HMAC_SCHEME} {}:1", unix_timestamp());
+        let result = authorizer.authorize(with_authorization(malformed)).await;
+        assert!(matches!(result, Err(AuthError::Malformed)));
+
+        let fresh = hmac_authorization(b"control", unix_timestamp(), "2");
+        let result = authorizer.authorize(with_authorization(fresh)).await;
+        assert!(matches!(result, Err(AuthError::BodyRead(_))));
+    }
+}
@@ -0,0 +1,99 @@
+/*
+ * Copyright 2024 Fluence Labs Limited
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use ccp::CCProverError;
+use ccp::CCProverErrorKind;
+use ccp_rpc_client::error::CCPError;
+use ccp_rpc_client::error::CCPErrorCode;
+use ccp_rpc_client::error::CCPErrorData;
+use ccp_rpc_client::error::CUProverErrorData;
+use ccp_shared::types::PhysicalCoreId;
+
+use crate::log_filter::LogFilterError;
+
+/// Converts errors of a `NoxCCPApi` implementation to errors sent to RPC clients.
+pub trait ToCCPError {
+    fn to_ccp_error(&self) -> CCPError;
+}
+
+impl ToCCPError for CCPError {
+    fn to_ccp_error(&self) -> CCPError {
+        self.clone()
+    }
+}
+
+impl ToCCPError for CCProverError {
+    fn to_ccp_error(&self) -> CCPError {
+        if let CCProverError::CUProverErrors(errors) = self {
+            if errors.len() > 1 {
+                let errors = errors
+                    .iter()
+                    .map(|error| {
+                        let (code, core_id) = classify(error.kind());
+                        CUProverErrorData {
+                            code,
+                            message: error.to_string(),
+                            core_id,
+                        }
+                    })
+                    .collect();
+                return CCPError::new(CCPErrorCode::CUProversFailed, self)
+                    .with_data(CCPErrorData::CUProverErrors { errors });
+            }
+        }
+
+        let (code, core_id) = classify(self.kind());
+        let error = CCPError::new(code, self);
+        match core_id {
+            Some(core_id) => error.with_data(CCPErrorData::Core { core_id }),
+            None => error,
+        }
+    }
+}
+
+impl ToCCPError for LogFilterError {
+    fn to_ccp_error(&self) -> CCPError {
+        match self {
+            LogFilterError::InvalidDirective { .. } => CCPError::invalid_param("directive", self),
+            LogFilterError::RevertTooLate { .. } => {
+                CCPError::invalid_param("revert_after_secs", self)
+            }
+            LogFilterError::Reload(_) => CCPError::internal(self),
+        }
+    }
+}
+
+fn classify(kind: CCProverErrorKind) -> (CCPErrorCode, Option<PhysicalCoreId>) {
+    match kind {
+        CCProverErrorKind::CoreNotFound { core_id } => (CCPErrorCode::CoreNotFound, core_id),
+        CCProverErrorKind::DatasetAllocationFailed => (CCPErrorCode::DatasetAllocationFailed, None),
+        CCProverErrorKind::MsrFailure => (CCPErrorCode::MsrFailure, None),
+        CCProverErrorKind::StorageIo => (CCPErrorCode::StorageIo, None),
+        CCProverErrorKind::InvalidParams => (CCPErrorCode::InvalidParams, None),
+        CCProverErrorKind::Internal => (CCPErrorCode::Internal, None),
+    }
+}
+
+pub(crate) fn busy_error() -> CCPError {
+    CCPError::busy("the prover is busy: probably on_active_commitment in progress, retry later")
+}
+
+pub(crate) fn storage_error(error: std::io::Error) -> CCPError {
+    CCPError::new(
+        CCPErrorCode::StorageIo,
+        format!("failed to save the state: {error}"),
+    )
+}
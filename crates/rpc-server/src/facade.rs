@@ -14,43 +14,90 @@
  * limitations under the License.
  */
 
-use std::fmt::Display;
 use std::sync::Arc;
+use std::sync::Mutex;
 
-use eyre::Context;
+use futures::stream::BoxStream;
 use tokio::sync::mpsc;
 use tokio::sync::mpsc::error::TryRecvError;
+use tokio::sync::mpsc::error::TrySendError;
 use tokio::sync::RwLock;
 use tokio::task::JoinHandle;
 
+use ccp::commitment_tracker::CommitmentTracker;
+use ccp::hashing_gate::HashingGate;
+use ccp::proofs_handle::ProofsHandle;
 use ccp::CCProver;
+use ccp_rpc_client::error::CCPError;
+use ccp_shared::alignment_plan::AlignmentPlan;
+use ccp_shared::commitment::CommitmentGeneration;
+use ccp_shared::commitment::CommitmentProgress;
+use ccp_shared::hashrate::HashrateReport;
 use ccp_shared::nox_ccp_api::NoxCCPApi;
 use ccp_shared::proof::CCProof;
 use ccp_shared::proof::ProofIdx;
+use ccp_shared::status::ProverStatus;
 use ccp_shared::types::CUAllocation;
 use ccp_shared::types::EpochParameters;
+use ccp_shared::types::GlobalNonce;
+
+use crate::errors::busy_error;
+use crate::errors::storage_error;
+use crate::errors::ToCCPError;
 
 /// An façade that handles RPC calls in background.
 pub struct BackgroundFacade<P> {
-    to_worker: mpsc::Sender<FacadeMessage>,
+    to_worker: mpsc::Sender<SequencedMessage>,
     prover: Arc<RwLock<P>>,
     worker: JoinHandle<()>,
+    progress: Arc<FacadeProgress>,
+    commitment_tracker: CommitmentTracker,
+    hashing_gate: HashingGate,
+    proofs: ProofsHandle,
+}
+
+/// Keeps the prover state as of the last commitment change handled by the worker.
+#[derive(Default)]
+struct FacadeProgress {
+    /// It's returned while the prover is busy with a commitment change.
+    last_status: Mutex<Option<ProverStatus>>,
+}
+
+impl FacadeProgress {
+    fn update_status(&self, status: ProverStatus) {
+        *self.last_status.lock().unwrap() = Some(status);
+    }
+
+    fn last_status(&self) -> Option<ProverStatus> {
+        self.last_status.lock().unwrap().clone()
+    }
 }
 
-impl<P> BackgroundFacade<P>
-where
-    P: NoxCCPApi + Sync + 'static,
-    <P as NoxCCPApi>::Error: Display,
-{
-    pub fn new(prover: Arc<RwLock<P>>, facade_queue_size: usize) -> Self {
+impl BackgroundFacade<CCProver> {
+    /// The commitment tracker, the hashing gate and the proofs handle should be taken
+    /// from the prover, they're used while the prover is busy: the tracker numbers
+    /// commitment changes and reports their progress, the gate pauses hashing,
+    /// and the handle serves found proofs.
+    pub fn new(
+        prover: Arc<RwLock<CCProver>>,
+        commitment_tracker: CommitmentTracker,
+        hashing_gate: HashingGate,
+        proofs: ProofsHandle,
+        facade_queue_size: usize,
+    ) -> Self {
         let (to_worker, from_facade) = mpsc::channel(facade_queue_size);
+        let progress = Arc::new(FacadeProgress::default());
 
-        let worker = tokio::task::spawn(facade_loop(prover.clone(), from_facade));
+        let worker = tokio::task::spawn(facade_loop(prover.clone(), from_facade, progress.clone()));
 
         Self {
             to_worker,
             prover,
             worker,
+            progress,
+            commitment_tracker,
+            hashing_gate,
+            proofs,
         }
     }
 
@@ -65,63 +112,152 @@ enum FacadeMessage {
     OnNoCommitment,
 }
 
+struct SequencedMessage {
+    generation: CommitmentGeneration,
+    message: FacadeMessage,
+}
+
 // implement for specific prover to implement granular state saving
 impl NoxCCPApi for BackgroundFacade<CCProver> {
-    type Error = eyre::Error;
+    type Error = CCPError;
 
     async fn on_active_commitment(
         &mut self,
         epoch_parameters: EpochParameters,
         cu_allocation: CUAllocation,
-    ) -> Result<(), Self::Error> {
+    ) -> Result<CommitmentGeneration, Self::Error> {
         // Save state early so that caller is sure it is saved.
         // Please note that the caller may be still stuck if dataset generation
         // is in progress and writer lock is held.
         {
             let guard = self.prover.read().await;
+            guard
+                .check_epoch(&epoch_parameters)
+                .map_err(|e| e.to_ccp_error())?;
             guard
                 .save_state(epoch_parameters, cu_allocation.clone())
-                .await?;
+                .await
+                .map_err(storage_error)?;
         }
+        let generation = self.commitment_tracker.request();
+        let message = SequencedMessage {
+            generation,
+            message: FacadeMessage::OnActiveCommitment(epoch_parameters, cu_allocation),
+        };
         self.to_worker
-            .try_send(FacadeMessage::OnActiveCommitment(
-                epoch_parameters,
-                cu_allocation,
-            ))
-            .context("on_active_commitment")
+            .try_send(message)
+            .inspect_err(|_| self.commitment_tracker.cancel(generation))
+            .map_err(|e| match e {
+                TrySendError::Full(_) => {
+                    CCPError::busy("too many commitment changes are queued, retry later")
+                }
+                TrySendError::Closed(_) => CCPError::internal(e),
+            })?;
+
+        Ok(generation)
     }
 
-    async fn on_no_active_commitment(&mut self) -> Result<(), Self::Error> {
+    async fn plan_active_commitment(
+        &self,
+        epoch_parameters: EpochParameters,
+        cu_allocation: CUAllocation,
+    ) -> Result<AlignmentPlan, Self::Error> {
+        // a plan made while the prover is aligning would be against a stale state
+        let guard = self.prover.try_read().map_err(|_| busy_error())?;
+        guard
+            .plan_commitment(epoch_parameters, cu_allocation)
+            .map_err(|e| e.to_ccp_error())
+    }
+
+    async fn on_no_active_commitment(&mut self) -> Result<CommitmentGeneration, Self::Error> {
         // Save state early so that caller is sure it is saved.
         // Please note that the caller may be still stuck if dataset generation
         // is in progress and writer lock is held.
         {
             let guard = self.prover.read().await;
-            guard.save_no_state().await?;
+            guard.save_no_state().await.map_err(storage_error)?;
         }
+        let generation = self.commitment_tracker.request();
+        let message = SequencedMessage {
+            generation,
+            message: FacadeMessage::OnNoCommitment,
+        };
         self.to_worker
-            .send(FacadeMessage::OnNoCommitment)
+            .send(message)
+            .await
+            .inspect_err(|_| self.commitment_tracker.cancel(generation))
+            .map_err(CCPError::internal)?;
+
+        Ok(generation)
+    }
+
+    async fn get_proofs_after(
+        &self,
+        proof_idx: ProofIdx,
+        limit: usize,
+        global_nonce: Option<GlobalNonce>,
+    ) -> Result<Vec<CCProof>, Self::Error> {
+        // proofs are served apart from the prover, so they're available during an alignment
+        self.proofs
+            .get_proofs_after(proof_idx, limit, global_nonce)
+            .await
+            .map_err(|e| e.to_ccp_error())
+    }
+
+    async fn subscribe_proofs(
+        &self,
+        from_idx: ProofIdx,
+    ) -> Result<BoxStream<'static, CCProof>, Self::Error> {
+        Ok(self.proofs.subscribe_proofs(from_idx))
+    }
+
+    async fn ack_proofs(
+        &self,
+        global_nonce: GlobalNonce,
+        proof_idx: ProofIdx,
+    ) -> Result<(), Self::Error> {
+        self.proofs
+            .ack_proofs(global_nonce, proof_idx)
             .await
-            .context("on_no_active_commitment")
-    }
-
-    async fn get_proofs_after(&self, proof_idx: ProofIdx) -> Result<Vec<CCProof>, Self::Error> {
-        let guard = match self.prover.try_read() {
-            Ok(g) => g,
-            Err(_) => {
-                tracing::debug!(
-                    "failed to get the prover lock: probably on_active_commitment in progress.\
- Return an empty list.",
-                );
-                return Ok(vec![]);
+            .map_err(|e| e.to_ccp_error())
+    }
+
+    async fn get_status(&self) -> Result<ProverStatus, Self::Error> {
+        let mut status = match self.prover.try_read() {
+            Ok(guard) => {
+                let status = guard.get_status().await.map_err(|e| e.to_ccp_error())?;
+                self.progress.update_status(status.clone());
+                status
             }
+            Err(_) => self.progress.last_status().ok_or_else(busy_error)?,
         };
-        guard
-            .get_proofs_after(proof_idx)
-            .await
-            // CCProverError is not Sync, so we convert it to a string in situ
-            .map_err(|e| eyre::eyre!(e.to_string()))
-            .context("get_proofs_after")
+        status.active_commitment_pending = self.commitment_tracker.is_pending();
+        status.paused = self.hashing_gate.is_paused();
+
+        Ok(status)
+    }
+
+    async fn get_hashrate(&self) -> Result<HashrateReport, Self::Error> {
+        let guard = self.prover.try_read().map_err(|_| busy_error())?;
+        guard.get_hashrate().await.map_err(|e| e.to_ccp_error())
+    }
+
+    async fn get_commitment_progress(&self) -> Result<CommitmentProgress, Self::Error> {
+        // the tracker is available while the prover is aligning
+        Ok(self.commitment_tracker.progress())
+    }
+
+    async fn pause(&self) -> Result<(), Self::Error> {
+        // the gate is shared with the prover, so it doesn't wait for an alignment in progress
+        tracing::info!("hashing is paused");
+        self.hashing_gate.pause();
+        Ok(())
+    }
+
+    async fn resume(&self) -> Result<(), Self::Error> {
+        tracing::info!("hashing is resumed");
+        self.hashing_gate.resume();
+        Ok(())
     }
 
     async fn realloc_utility_cores(&self, utility_core_ids: Vec<cpu_utils::LogicalCoreId>) {
@@ -134,30 +270,41 @@ impl NoxCCPApi for BackgroundFacade<CCProver> {
 }
 
 #[tracing::instrument(skip_all)]
-async fn facade_loop<P>(prover: Arc<RwLock<P>>, mut from_facade: mpsc::Receiver<FacadeMessage>)
-where
-    P: NoxCCPApi,
-    <P as NoxCCPApi>::Error: Display,
-{
+async fn facade_loop(
+    prover: Arc<RwLock<CCProver>>,
+    mut from_facade: mpsc::Receiver<SequencedMessage>,
+    progress: Arc<FacadeProgress>,
+) {
     use FacadeMessage::*;
-    while let Some(message) = receive_last(&mut from_facade).await {
+    // skipped messages are superseded by the last one, so their generations
+    // are considered applied along with it
+    while let Some(SequencedMessage {
+        generation,
+        message,
+    }) = receive_last(&mut from_facade).await
+    {
         let mut guard = prover.write().await;
         match message {
             OnActiveCommitment(epoch_parameters, cu_allocation) => {
                 let res = guard
-                    .on_active_commitment(epoch_parameters, cu_allocation)
+                    .apply_commitment(generation, epoch_parameters, cu_allocation)
                     .await;
                 if let Err(e) = res {
                     tracing::error!("nested prover on_active_commitment failed: {e}");
                 }
             }
             OnNoCommitment => {
-                let res = guard.on_no_active_commitment().await;
+                let res = guard.apply_no_commitment(generation).await;
                 if let Err(e) = res {
                     tracing::error!("nested prover on_no_active_commitment failed: {e}");
                 }
             }
         }
+
+        match guard.get_status().await {
+            Ok(status) => progress.update_status(status),
+            Err(e) => tracing::warn!("failed to get the prover status: {e}"),
+        }
     }
 }
 
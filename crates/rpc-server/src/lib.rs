@@ -26,71 +26,218 @@
     unreachable_patterns
 )]
 
+mod auth;
+mod errors;
 mod facade;
+mod log_filter;
+mod serve;
+mod stdio;
+mod unix;
 
 use std::collections::HashMap;
+use std::path::Path;
 use std::sync::Arc;
+use std::time::Duration;
 
 use ccp_shared::types::LogicalCoreId;
+use futures::StreamExt;
 use jsonrpsee::core::async_trait;
+use jsonrpsee::core::SubscriptionResult;
+use jsonrpsee::server::PendingSubscriptionSink;
 use jsonrpsee::server::Server;
-use jsonrpsee::server::ServerHandle;
+use jsonrpsee::server::ServerBuilder;
+use jsonrpsee::server::SubscriptionMessage;
 use jsonrpsee::tracing::instrument;
 use jsonrpsee::types::ErrorObjectOwned;
+use jsonrpsee::RpcModule;
+use tokio::net::TcpListener;
 use tokio::net::ToSocketAddrs;
 use tokio::sync::Mutex;
+use tower::layer::util::Identity;
 
+use ccp_rpc_client::error::CCPError;
+use ccp_rpc_client::error::CCPErrorCode;
 use ccp_rpc_client::CCPRpcServer;
+use ccp_rpc_client::CCPSubscriptionRpcServer;
+use ccp_rpc_client::LogFilterStatus;
 use ccp_rpc_client::OrHex;
+use ccp_shared::alignment_plan::AlignmentPlan;
+use ccp_shared::commitment::CommitmentGeneration;
+use ccp_shared::commitment::CommitmentProgress;
+use ccp_shared::hashrate::HashrateReport;
 use ccp_shared::nox_ccp_api::NoxCCPApi;
 use ccp_shared::proof::CCProof;
 use ccp_shared::proof::ProofIdx;
+use ccp_shared::status::ProverStatus;
+use ccp_shared::types::CUAllocation;
 use ccp_shared::types::Difficulty;
 use ccp_shared::types::EpochParameters;
 use ccp_shared::types::GlobalNonce;
 use ccp_shared::types::PhysicalCoreId;
 use ccp_shared::types::CUID;
 
+use crate::auth::Authorizer;
+use crate::serve::Endpoint;
+
+pub use crate::auth::RpcAuth;
+pub use crate::auth::RpcAuthScheme;
+pub use crate::errors::ToCCPError;
 pub use crate::facade::BackgroundFacade;
+pub use crate::log_filter::LogFilterControl;
+pub use crate::log_filter::LogFilterError;
+pub use jsonrpsee::server::ServerHandle;
 
 pub struct CCPRcpHttpServer<P> {
     // n.b. if NoxCCPApi would have internal mutability, we might get used of the Mutex
     cc_prover: Arc<Mutex<P>>,
+    auth: Option<RpcAuth>,
+    log_filter: Option<LogFilterControl>,
+}
+
+impl<P> Clone for CCPRcpHttpServer<P> {
+    fn clone(&self) -> Self {
+        Self {
+            cc_prover: self.cc_prover.clone(),
+            auth: self.auth.clone(),
+            log_filter: self.log_filter.clone(),
+        }
+    }
 }
 
 impl<P> CCPRcpHttpServer<P> {
     pub fn new(cc_prover: P) -> Self {
         Self {
             cc_prover: Arc::new(Mutex::new(cc_prover)),
+            auth: None,
+            log_filter: None,
         }
     }
+
+    /// Requires credentials for each HTTP request and WebSocket connection,
+    /// it isn't applied to the stdio transport.
+    pub fn with_auth(mut self, auth: RpcAuth) -> Self {
+        self.auth = Some(auth);
+        self
+    }
+
+    /// Allows to change the log filter at runtime, otherwise the log filter methods fail.
+    pub fn with_log_filter_control(mut self, log_filter: LogFilterControl) -> Self {
+        self.log_filter = Some(log_filter);
+        self
+    }
+
+    fn log_filter(&self) -> Result<&LogFilterControl, ErrorObjectOwned> {
+        self.log_filter.as_ref().ok_or_else(|| {
+            CCPError::new(
+                CCPErrorCode::Unavailable,
+                "log filter control isn't enabled",
+            )
+            .into()
+        })
+    }
 }
 
 impl<P> CCPRcpHttpServer<P>
 where
     P: NoxCCPApi + 'static,
-    <P as NoxCCPApi>::Error: ToString,
+    <P as NoxCCPApi>::Error: ToCCPError,
 {
-    ///  Run the JSON-RPC HTTP server in the background.
+    ///  Run the JSON-RPC HTTP and WebSocket server on the same address in the background.
     ///
     ///  The returned handle can be used to maniplate it.
     pub async fn run_server(
         self,
         bind_address: impl ToSocketAddrs + std::fmt::Debug,
     ) -> Result<ServerHandle, std::io::Error> {
-        let server = Server::builder().build(bind_address).await?;
+        self.run_tcp_server(bind_address, TcpProtocols::HttpAndWs)
+            .await
+    }
+
+    ///  Run the JSON-RPC HTTP server in the background, subscriptions aren't available.
+    pub async fn run_http_server(
+        self,
+        bind_address: impl ToSocketAddrs + std::fmt::Debug,
+    ) -> Result<ServerHandle, std::io::Error> {
+        self.run_tcp_server(bind_address, TcpProtocols::Http).await
+    }
+
+    ///  Run the JSON-RPC WebSocket server in the background.
+    pub async fn run_ws_server(
+        self,
+        bind_address: impl ToSocketAddrs + std::fmt::Debug,
+    ) -> Result<ServerHandle, std::io::Error> {
+        self.run_tcp_server(bind_address, TcpProtocols::Ws).await
+    }
+
+    ///  Run the JSON-RPC HTTP and WebSocket server on a Unix domain socket in the background.
+    ///
+    ///  An existing file at the socket path is replaced.
+    pub async fn run_unix_server(
+        self,
+        socket_path: impl AsRef<Path>,
+        permissions: u32,
+    ) -> Result<ServerHandle, std::io::Error> {
+        let endpoint = self.into_endpoint(Server::builder());
+        unix::run_unix_server(endpoint, socket_path.as_ref(), permissions).await
+    }
+
+    ///  Run the newline-delimited JSON-RPC server on stdin/stdout in the background,
+    ///  so nothing else should be written to stdout.
+    ///
+    ///  The server is stopped when stdin is closed.
+    pub fn run_stdio_server(self) -> ServerHandle {
+        stdio::run_stdio_server(self.into_module().into())
+    }
 
-        let handle = server.start(self.into_rpc());
+    async fn run_tcp_server(
+        self,
+        bind_address: impl ToSocketAddrs + std::fmt::Debug,
+        protocols: TcpProtocols,
+    ) -> Result<ServerHandle, std::io::Error> {
+        let builder = Server::builder();
+        let builder = match protocols {
+            TcpProtocols::HttpAndWs => builder,
+            TcpProtocols::Http => builder.http_only(),
+            TcpProtocols::Ws => builder.ws_only(),
+        };
+        let listener = TcpListener::bind(bind_address).await?;
+        let handle = self.into_endpoint(builder).spawn(listener, || {});
 
         Ok(handle)
     }
+
+    fn into_endpoint(self, builder: ServerBuilder<Identity, Identity>) -> Endpoint {
+        let auth = self.auth.clone();
+        let methods = self.into_module().into();
+        let authorizer = auth.map(|auth| Authorizer::new(auth, &methods));
+
+        Endpoint {
+            service_builder: builder.to_service_builder(),
+            methods,
+            authorizer,
+        }
+    }
+
+    fn into_module(self) -> RpcModule<Self> {
+        let mut module = CCPSubscriptionRpcServer::into_rpc(self.clone());
+        module
+            .merge(CCPRpcServer::into_rpc(self))
+            .expect("CCPRpc and CCPSubscriptionRpc method names don't overlap");
+        module
+    }
+}
+
+enum TcpProtocols {
+    HttpAndWs,
+    Http,
+    Ws,
 }
 
 #[async_trait]
 impl<P> CCPRpcServer for CCPRcpHttpServer<P>
 where
     P: NoxCCPApi + 'static,
-    <P as NoxCCPApi>::Error: ToString,
+    <P as NoxCCPApi>::Error: ToCCPError,
 {
     #[instrument(skip(self))]
     async fn on_active_commitment(
@@ -98,43 +245,48 @@ where
         global_nonce: OrHex<GlobalNonce>,
         difficulty: OrHex<Difficulty>,
         cu_allocation: HashMap<PhysicalCoreId, OrHex<CUID>>,
-    ) -> Result<(), ErrorObjectOwned> {
-        let global_nonce: GlobalNonce = global_nonce
-            .clone()
-            .unhex()
-            .map_err(|e| ErrorObjectOwned::owned(2, e.to_string(), Some(global_nonce)))?;
-        let difficulty = difficulty
-            .clone()
-            .unhex()
-            .map_err(|e| ErrorObjectOwned::owned(2, e.to_string(), Some(difficulty)))?;
-
-        let mut cu_allocation_real = HashMap::<_, CUID>::new();
-        for (id, cuid) in cu_allocation {
-            cu_allocation_real.insert(
-                id,
-                cuid.clone()
-                    .unhex()
-                    .map_err(|e| ErrorObjectOwned::owned(2, e.to_string(), Some(cuid)))?,
-            );
-        }
+    ) -> Result<CommitmentGeneration, ErrorObjectOwned> {
+        let (epoch, cu_allocation) = unhex_commitment(global_nonce, difficulty, cu_allocation)?;
 
         let mut guard = self.cc_prover.lock().await;
-        let epoch = EpochParameters::new(global_nonce, difficulty);
         guard
-            .on_active_commitment(epoch, cu_allocation_real)
+            .on_active_commitment(epoch, cu_allocation)
             .await
-            .map_err(|e| ErrorObjectOwned::owned::<()>(1, e.to_string(), None))?;
-        Ok(())
+            .map_err(|e| e.to_ccp_error().into())
+    }
+
+    #[instrument(skip(self))]
+    async fn plan_active_commitment(
+        &self,
+        global_nonce: OrHex<GlobalNonce>,
+        difficulty: OrHex<Difficulty>,
+        cu_allocation: HashMap<PhysicalCoreId, OrHex<CUID>>,
+    ) -> Result<AlignmentPlan, ErrorObjectOwned> {
+        let (epoch, cu_allocation) = unhex_commitment(global_nonce, difficulty, cu_allocation)?;
+
+        let guard = self.cc_prover.lock().await;
+        guard
+            .plan_active_commitment(epoch, cu_allocation)
+            .await
+            .map_err(|e| e.to_ccp_error().into())
     }
 
     #[instrument(skip(self))]
-    async fn on_no_active_commitment(&self) -> Result<(), ErrorObjectOwned> {
+    async fn on_no_active_commitment(&self) -> Result<CommitmentGeneration, ErrorObjectOwned> {
         let mut guard = self.cc_prover.lock().await;
         guard
             .on_no_active_commitment()
             .await
-            .map_err(|e| ErrorObjectOwned::owned::<()>(1, e.to_string(), None))?;
-        Ok(())
+            .map_err(|e| e.to_ccp_error().into())
+    }
+
+    #[instrument(skip(self))]
+    async fn get_commitment_progress(&self) -> Result<CommitmentProgress, ErrorObjectOwned> {
+        let guard = self.cc_prover.lock().await;
+        guard
+            .get_commitment_progress()
+            .await
+            .map_err(|e| e.to_ccp_error().into())
     }
 
     #[instrument(skip(self))]
@@ -142,18 +294,86 @@ where
         &self,
         proof_idx: ProofIdx,
         limit: usize,
+        global_nonce: Option<OrHex<GlobalNonce>>,
     ) -> Result<Vec<CCProof>, ErrorObjectOwned> {
+        let global_nonce = global_nonce
+            .map(|global_nonce| global_nonce.unhex())
+            .transpose()
+            .map_err(|e| CCPError::invalid_param("global_nonce", e))?;
+
         let guard = self.cc_prover.lock().await;
-        let mut proofs = guard
-            .get_proofs_after(proof_idx)
+        guard
+            .get_proofs_after(proof_idx, limit, global_nonce)
             .await
-            .map_err(|e| ErrorObjectOwned::owned::<()>(1, e.to_string(), None))?;
-        if proofs.len() > limit {
-            proofs.select_nth_unstable_by_key(limit, |p| p.id.idx);
-            proofs = proofs.drain(0..limit).collect();
-        }
-        proofs.sort_unstable_by_key(|p| p.id.idx);
-        Ok(proofs)
+            .map_err(|e| e.to_ccp_error().into())
+    }
+
+    #[instrument(skip(self))]
+    async fn ack_proofs(
+        &self,
+        global_nonce: OrHex<GlobalNonce>,
+        proof_idx: ProofIdx,
+    ) -> Result<(), ErrorObjectOwned> {
+        let global_nonce: GlobalNonce = global_nonce
+            .unhex()
+            .map_err(|e| CCPError::invalid_param("global_nonce", e))?;
+
+        let guard = self.cc_prover.lock().await;
+        guard
+            .ack_proofs(global_nonce, proof_idx)
+            .await
+            .map_err(|e| e.to_ccp_error().into())
+    }
+
+    #[instrument(skip(self))]
+    async fn get_status(&self) -> Result<ProverStatus, ErrorObjectOwned> {
+        let guard = self.cc_prover.lock().await;
+        guard
+            .get_status()
+            .await
+            .map_err(|e| e.to_ccp_error().into())
+    }
+
+    #[instrument(skip(self))]
+    async fn get_hashrate(&self) -> Result<HashrateReport, ErrorObjectOwned> {
+        let guard = self.cc_prover.lock().await;
+        guard
+            .get_hashrate()
+            .await
+            .map_err(|e| e.to_ccp_error().into())
+    }
+
+    #[instrument(skip(self))]
+    async fn pause(&self) -> Result<(), ErrorObjectOwned> {
+        let guard = self.cc_prover.lock().await;
+        guard.pause().await.map_err(|e| e.to_ccp_error().into())
+    }
+
+    #[instrument(skip(self))]
+    async fn resume(&self) -> Result<(), ErrorObjectOwned> {
+        let guard = self.cc_prover.lock().await;
+        guard.resume().await.map_err(|e| e.to_ccp_error().into())
+    }
+
+    #[instrument(skip(self))]
+    async fn get_log_filter(&self) -> Result<LogFilterStatus, ErrorObjectOwned> {
+        self.log_filter()?
+            .status()
+            .map_err(|e| e.to_ccp_error().into())
+    }
+
+    #[instrument(skip(self))]
+    async fn set_log_filter(
+        &self,
+        directive: Option<String>,
+        revert_after_secs: Option<u64>,
+    ) -> Result<LogFilterStatus, ErrorObjectOwned> {
+        self.log_filter()?
+            .set(
+                directive.as_deref(),
+                revert_after_secs.map(Duration::from_secs),
+            )
+            .map_err(|e| e.to_ccp_error().into())
     }
 
     #[instrument(skip(self))]
@@ -170,3 +390,68 @@ where
         guard.realloc_utility_cores(utility_core_ids).await;
     }
 }
+
+#[async_trait]
+impl<P> CCPSubscriptionRpcServer for CCPRcpHttpServer<P>
+where
+    P: NoxCCPApi + 'static,
+    <P as NoxCCPApi>::Error: ToCCPError,
+{
+    #[instrument(skip(self, pending))]
+    async fn subscribe_proofs(
+        &self,
+        pending: PendingSubscriptionSink,
+        from_idx: ProofIdx,
+    ) -> SubscriptionResult {
+        let proofs = {
+            let guard = self.cc_prover.lock().await;
+            guard.subscribe_proofs(from_idx).await
+        };
+        let mut proofs = match proofs {
+            Ok(proofs) => proofs,
+            Err(e) => {
+                pending
+                    .reject(ErrorObjectOwned::from(e.to_ccp_error()))
+                    .await;
+                return Ok(());
+            }
+        };
+
+        let sink = pending.accept().await?;
+        loop {
+            tokio::select! {
+                _ = sink.closed() => break,
+                proof = proofs.next() => match proof {
+                    Some(proof) => sink.send(SubscriptionMessage::from_json(&proof)?).await?,
+                    None => break,
+                },
+            }
+        }
+
+        Ok(())
+    }
+}
+
+fn unhex_commitment(
+    global_nonce: OrHex<GlobalNonce>,
+    difficulty: OrHex<Difficulty>,
+    cu_allocation: HashMap<PhysicalCoreId, OrHex<CUID>>,
+) -> Result<(EpochParameters, CUAllocation), ErrorObjectOwned> {
+    let global_nonce: GlobalNonce = global_nonce
+        .unhex()
+        .map_err(|e| CCPError::invalid_param("global_nonce", e))?;
+    let difficulty = difficulty
+        .unhex()
+        .map_err(|e| CCPError::invalid_param("difficulty", e))?;
+
+    let mut cu_allocation_real = HashMap::<_, CUID>::new();
+    for (id, cuid) in cu_allocation {
+        let cuid = cuid
+            .unhex()
+            .map_err(|e| CCPError::invalid_param(format!("cu_allocation.{id}"), e))?;
+        cu_allocation_real.insert(id, cuid);
+    }
+
+    let epoch = EpochParameters::new(global_nonce, difficulty);
+    Ok((epoch, cu_allocation_real))
+}
@@ -0,0 +1,292 @@
+/*
+ * Copyright 2024 Fluence Labs Limited
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::sync::Arc;
+use std::sync::Mutex;
+use std::time::Duration;
+
+use thiserror::Error as ThisError;
+use tokio::task::JoinHandle;
+use tracing_subscriber::filter::ParseError;
+use tracing_subscriber::reload;
+use tracing_subscriber::EnvFilter;
+
+use ccp_rpc_client::auth::unix_timestamp;
+use ccp_rpc_client::LogFilterStatus;
+
+/// A changed filter is reverted not later than this, if a revert is requested.
+const MAX_REVERT_AFTER: Duration = Duration::from_secs(7 * 24 * 60 * 60);
+
+type ReloadFn = dyn Fn(EnvFilter) -> Result<(), reload::Error> + Send + Sync;
+type CurrentFn = dyn Fn() -> Result<String, reload::Error> + Send + Sync;
+
+#[derive(ThisError, Debug)]
+pub enum LogFilterError {
+    #[error("invalid log filter directive {directive:?}: {error}")]
+    InvalidDirective {
+        directive: String,
+        #[source]
+        error: ParseError,
+    },
+
+    #[error(
+        "log filter revert is requested after {revert_after:?}, the maximum is {MAX_REVERT_AFTER:?}"
+    )]
+    RevertTooLate { revert_after: Duration },
+
+    #[error("log filter reload failed: {0}")]
+    Reload(#[from] reload::Error),
+}
+
+/// Changes the filter of the global tracing subscriber at runtime,
+/// a changed filter could be reverted to the configured one after a timeout.
+#[derive(Clone)]
+pub struct LogFilterControl {
+    inner: Arc<LogFilterInner>,
+}
+
+struct LogFilterInner {
+    reload: Box<ReloadFn>,
+    current: Box<CurrentFn>,
+    /// It's changed on a config reload.
+    configured_directive: Mutex<String>,
+    revert: Mutex<RevertState>,
+}
+
+#[derive(Default)]
+struct RevertState {
+    /// Incremented on each change, so a revert scheduled before it is skipped.
+    generation: u64,
+    scheduled: Option<ScheduledRevert>,
+}
+
+struct ScheduledRevert {
+    revert_at: u64,
+    task: JoinHandle<()>,
+}
+
+impl LogFilterControl {
+    /// The filter in effect at the moment is considered configured.
+    pub fn new<S: 'static>(handle: reload::Handle<EnvFilter, S>) -> Result<Self, LogFilterError> {
+        let configured_directive = handle.with_current(ToString::to_string)?;
+        let current_handle = handle.clone();
+        let inner = LogFilterInner {
+            reload: Box::new(move |filter| handle.reload(filter)),
+            current: Box::new(move || current_handle.with_current(ToString::to_string)),
+            configured_directive: Mutex::new(configured_directive),
+            revert: Mutex::new(RevertState::default()),
+        };
+
+        Ok(Self {
+            inner: Arc::new(inner),
+        })
+    }
+
+    pub fn status(&self) -> Result<LogFilterStatus, LogFilterError> {
+        let revert_at = self
+            .inner
+            .revert
+            .lock()
+            .unwrap()
+            .scheduled
+            .as_ref()
+            .map(|scheduled| scheduled.revert_at);
+
+        Ok(LogFilterStatus {
+            directive: (self.inner.current)()?,
+            configured_directive: self.configured_directive(),
+            revert_at,
+        })
+    }
+
+    /// Replaces the filter with the directive, or with the configured one if it's absent,
+    /// a previously scheduled revert is cancelled.
+    ///
+    /// Must be called within a Tokio runtime if `revert_after` is set.
+    pub fn set(
+        &self,
+        directive: Option<&str>,
+        revert_after: Option<Duration>,
+    ) -> Result<LogFilterStatus, LogFilterError> {
+        let configured_directive = self.configured_directive();
+        let directive = directive.unwrap_or(&configured_directive);
+        let filter = parse_filter(directive)?;
+        if let Some(revert_after) = revert_after.filter(|&after| after > MAX_REVERT_AFTER) {
+            return Err(LogFilterError::RevertTooLate { revert_after });
+        }
+
+        let mut revert = self.inner.revert.lock().unwrap();
+        revert.generation += 1;
+        if let Some(scheduled) = revert.scheduled.take() {
+            scheduled.task.abort();
+        }
+        (self.inner.reload)(filter)?;
+        tracing::info!("log filter is set to {directive:?}");
+
+        let is_configured = directive == configured_directive;
+        if let (Some(revert_after), false) = (revert_after, is_configured) {
+            let control = self.clone();
+            let generation = revert.generation;
+            let task = tokio::spawn(async move {
+                tokio::time::sleep(revert_after).await;
+                control.revert(generation);
+            });
+            revert.scheduled = Some(ScheduledRevert {
+                revert_at: unix_timestamp().saturating_add(revert_after.as_secs()),
+                task,
+            });
+        }
+        std::mem::drop(revert);
+
+        self.status()
+    }
+
+    fn revert(&self, generation: u64) {
+        let mut revert = self.inner.revert.lock().unwrap();
+        if revert.generation != generation {
+            // the filter was changed after the revert had been scheduled
+            return;
+        }
+        revert.scheduled = None;
+
+        let configured_directive = self.configured_directive();
+        let result = parse_filter(&configured_directive)
+            .and_then(|filter| (self.inner.reload)(filter).map_err(Into::into));
+        match result {
+            Ok(()) => tracing::info!("log filter is reverted to {configured_directive:?}"),
+            Err(e) => tracing::error!("failed to revert the log filter: {e}"),
+        }
+    }
+
+    /// Replaces the configured filter, e.g. on a config reload. It's put in effect
+    /// right away unless the filter has been changed by `set`, then it's used on a revert.
+    pub fn set_configured(&self, directive: &str) -> Result<(), LogFilterError> {
+        let filter = parse_filter(directive)?;
+
+        // keeps set and revert from interleaving
+        let _revert = self.inner.revert.lock().unwrap();
+        let mut configured_directive = self.inner.configured_directive.lock().unwrap();
+        if *configured_directive == directive {
+            return Ok(());
+        }
+        let is_changed = (self.inner.current)()? != *configured_directive;
+        *configured_directive = directive.to_string();
+
+        if !is_changed {
+            (self.inner.reload)(filter)?;
+            tracing::info!("log filter is set to the configured {directive:?}");
+        }
+        Ok(())
+    }
+
+    fn configured_directive(&self) -> String {
+        self.inner.configured_directive.lock().unwrap().clone()
+    }
+}
+
+fn parse_filter(directive: &str) -> Result<EnvFilter, LogFilterError> {
+    EnvFilter::try_new(directive).map_err(|error| LogFilterError::InvalidDirective {
+        directive: directive.to_string(),
+        error,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use tracing_subscriber::Registry;
+
+    use super::*;
+
+    fn control_with(directive: &str) -> (reload::Layer<EnvFilter, Registry>, LogFilterControl) {
+        let (layer, handle) = reload::Layer::new(EnvFilter::new(directive));
+        let control = LogFilterControl::new(handle).unwrap();
+        (layer, control)
+    }
+
+    #[tokio::test]
+    async fn filter_is_reverted_after_timeout() {
+        let (_layer, control) = control_with("info");
+
+        let status = control
+            .set(Some("ccp=debug"), Some(Duration::from_millis(10)))
+            .unwrap();
+        assert_eq!(status.directive, "ccp=debug");
+        assert_eq!(status.configured_directive, "info");
+        assert!(status.revert_at.is_some());
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        let status = control.status().unwrap();
+        assert_eq!(status.directive, "info");
+        assert_eq!(status.revert_at, None);
+    }
+
+    #[tokio::test]
+    async fn new_filter_cancels_scheduled_revert() {
+        let (_layer, control) = control_with("info");
+
+        control
+            .set(Some("ccp=debug"), Some(Duration::from_millis(10)))
+            .unwrap();
+        let status = control.set(Some("ccp=trace"), None).unwrap();
+        assert_eq!(status.revert_at, None);
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(control.status().unwrap().directive, "ccp=trace");
+
+        let status = control.set(None, None).unwrap();
+        assert_eq!(status.directive, "info");
+    }
+
+    #[tokio::test]
+    async fn reconfigured_filter_keeps_changed_one() {
+        let (_layer, control) = control_with("info");
+
+        control.set_configured("warn").unwrap();
+        assert_eq!(control.status().unwrap().directive, "warn");
+
+        control
+            .set(Some("ccp=debug"), Some(Duration::from_millis(10)))
+            .unwrap();
+        control.set_configured("error").unwrap();
+        let status = control.status().unwrap();
+        assert_eq!(status.directive, "ccp=debug");
+        assert_eq!(status.configured_directive, "error");
+
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert_eq!(control.status().unwrap().directive, "error");
+    }
+
+    #[test]
+    fn too_late_revert_is_rejected() {
+        let (_layer, control) = control_with("info");
+
+        let result = control.set(Some("ccp=debug"), Some(Duration::from_secs(u64::MAX)));
+        assert!(matches!(result, Err(LogFilterError::RevertTooLate { .. })));
+        assert_eq!(control.status().unwrap().directive, "info");
+    }
+
+    #[test]
+    fn invalid_directive_is_rejected() {
+        let (_layer, control) = control_with("info");
+
+        let result = control.set(Some("ccp=loud"), None);
+        assert!(matches!(
+            result,
+            Err(LogFilterError::InvalidDirective { .. })
+        ));
+        assert_eq!(control.status().unwrap().directive, "info");
+    }
+}
@@ -0,0 +1,174 @@
+/*
+ * Copyright 2024 Fluence Labs Limited
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::sync::Arc;
+use std::task::Context;
+use std::task::Poll;
+
+use hyper::service::service_fn;
+use hyper::Body;
+use hyper::Request;
+use hyper::Response;
+use jsonrpsee::server::stop_channel;
+use jsonrpsee::server::ServerHandle;
+use jsonrpsee::server::StopHandle;
+use jsonrpsee::server::TowerServiceBuilder;
+use jsonrpsee::Methods;
+use tokio::io::AsyncRead;
+use tokio::io::AsyncWrite;
+use tokio::net::TcpListener;
+use tokio::net::UnixListener;
+use tower::layer::util::Identity;
+use tower::Service;
+
+use crate::auth::AccessLevel;
+use crate::auth::Authorizer;
+
+type BoxError = Box<dyn std::error::Error + Send + Sync>;
+
+pub(crate) trait Listener: Send + Sync + 'static {
+    type Stream: AsyncRead + AsyncWrite + Unpin + Send + 'static;
+
+    fn poll_accept(&self, cx: &mut Context<'_>) -> Poll<std::io::Result<Self::Stream>>;
+}
+
+impl Listener for TcpListener {
+    type Stream = tokio::net::TcpStream;
+
+    fn poll_accept(&self, cx: &mut Context<'_>) -> Poll<std::io::Result<Self::Stream>> {
+        TcpListener::poll_accept(self, cx).map_ok(|(stream, _)| stream)
+    }
+}
+
+impl Listener for UnixListener {
+    type Stream = tokio::net::UnixStream;
+
+    fn poll_accept(&self, cx: &mut Context<'_>) -> Poll<std::io::Result<Self::Stream>> {
+        UnixListener::poll_accept(self, cx).map_ok(|(stream, _)| stream)
+    }
+}
+
+/// Serves JSON-RPC over HTTP and WebSocket on accepted connections,
+/// protocols are defined by the service builder.
+pub(crate) struct Endpoint {
+    pub(crate) service_builder: TowerServiceBuilder<Identity, Identity>,
+    pub(crate) methods: Methods,
+    pub(crate) authorizer: Option<Arc<Authorizer>>,
+}
+
+impl Endpoint {
+    /// Runs the accept loop in the background, `on_stopped` is called after the loop is finished.
+    pub(crate) fn spawn<L: Listener>(
+        self,
+        listener: L,
+        on_stopped: impl FnOnce() + Send + 'static,
+    ) -> ServerHandle {
+        let (stop_handle, server_handle) = stop_channel();
+        tokio::spawn(async move {
+            self.accept_loop(listener, stop_handle).await;
+            on_stopped();
+        });
+
+        server_handle
+    }
+
+    async fn accept_loop<L: Listener>(self, listener: L, stop_handle: StopHandle) {
+        let stopped = stop_handle.clone().shutdown();
+        tokio::pin!(stopped);
+
+        loop {
+            let stream = tokio::select! {
+                _ = &mut stopped => break,
+                accepted = std::future::poll_fn(|cx| listener.poll_accept(cx)) => match accepted {
+                    Ok(stream) => stream,
+                    Err(e) => {
+                        tracing::warn!("failed to accept an RPC connection: {e}");
+                        continue;
+                    }
+                },
+            };
+
+            let control = self
+                .service_builder
+                .clone()
+                .build(self.methods.clone(), stop_handle.clone());
+            match &self.authorizer {
+                None => spawn_connection(stream, control, stop_handle.clone()),
+                Some(authorizer) => {
+                    let read_only = self
+                        .service_builder
+                        .clone()
+                        .build(authorizer.read_only_methods(), stop_handle.clone());
+                    let authorizer = authorizer.clone();
+                    let service = service_fn(move |request| {
+                        route(
+                            authorizer.clone(),
+                            control.clone(),
+                            read_only.clone(),
+                            request,
+                        )
+                    });
+                    spawn_connection(stream, service, stop_handle.clone())
+                }
+            }
+        }
+    }
+}
+
+async fn route<S>(
+    authorizer: Arc<Authorizer>,
+    mut control: S,
+    mut read_only: S,
+    request: Request<Body>,
+) -> Result<Response<Body>, BoxError>
+where
+    S: Service<Request<Body>, Response = Response<Body>, Error = BoxError>,
+{
+    match authorizer.authorize(request).await {
+        Ok((AccessLevel::Control, request)) => control.call(request).await,
+        Ok((AccessLevel::ReadOnly, request)) => read_only.call(request).await,
+        Err(e) => {
+            tracing::debug!("rejected an unauthorized RPC request: {e}");
+            Ok(authorizer.unauthorized(&e))
+        }
+    }
+}
+
+fn spawn_connection<T, S>(stream: T, service: S, stop_handle: StopHandle)
+where
+    T: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+    S: Service<Request<Body>, Response = Response<Body>, Error = BoxError> + Send + 'static,
+    S::Future: Send + 'static,
+{
+    tokio::spawn(async move {
+        // upgrades are needed for WebSocket connections
+        let connection = hyper::server::conn::Http::new()
+            .serve_connection(stream, service)
+            .with_upgrades();
+        tokio::pin!(connection);
+
+        let result = tokio::select! {
+            result = &mut connection => result,
+            _ = stop_handle.shutdown() => {
+                connection.as_mut().graceful_shutdown();
+                connection.await
+            }
+        };
+        if let Err(e) = result {
+            tracing::debug!("RPC connection failed: {e}");
+        }
+    });
+}
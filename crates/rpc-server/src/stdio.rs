@@ -0,0 +1,190 @@
+/*
+ * Copyright 2024 Fluence Labs Limited
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use jsonrpsee::server::stop_channel;
+use jsonrpsee::server::MethodResponse;
+use jsonrpsee::server::ServerHandle;
+use jsonrpsee::server::StopHandle;
+use jsonrpsee::types::error::ErrorCode;
+use jsonrpsee::types::ErrorObject;
+use jsonrpsee::types::Id;
+use jsonrpsee::Methods;
+use tokio::io::AsyncBufReadExt;
+use tokio::io::AsyncRead;
+use tokio::io::AsyncWrite;
+use tokio::io::AsyncWriteExt;
+use tokio::io::BufReader;
+use tokio::sync::mpsc;
+
+const STDOUT_QUEUE_SIZE: usize = 128;
+const SUBSCRIPTION_BUFFER_SIZE: usize = 1024;
+
+/// Serves newline-delimited JSON-RPC on stdin/stdout in the background:
+/// each line of stdin is a request, each line of stdout is a response or
+/// a subscription notification. Batch requests aren't supported.
+///
+/// The server stops when stdin is closed.
+pub(crate) fn run_stdio_server(methods: Methods) -> ServerHandle {
+    let (stop_handle, server_handle) = stop_channel();
+    tokio::spawn(serve_lines(
+        methods,
+        tokio::io::stdin(),
+        tokio::io::stdout(),
+        stop_handle,
+    ));
+
+    server_handle
+}
+
+async fn serve_lines<R, W>(methods: Methods, reader: R, writer: W, stop_handle: StopHandle)
+where
+    R: AsyncRead + Unpin,
+    W: AsyncWrite + Unpin + Send + 'static,
+{
+    let (to_stdout, from_handlers) = mpsc::channel(STDOUT_QUEUE_SIZE);
+    tokio::spawn(write_stdout(writer, from_handlers));
+
+    let mut lines = BufReader::new(reader).lines();
+    let stopped = stop_handle.shutdown();
+    tokio::pin!(stopped);
+
+    loop {
+        let line = tokio::select! {
+            _ = &mut stopped => break,
+            line = lines.next_line() => match line {
+                Ok(Some(line)) => line,
+                Ok(None) => {
+                    tracing::info!("stdin is closed, stopping the stdio JSON-RPC server");
+                    break;
+                }
+                Err(e) => {
+                    tracing::error!("failed to read a JSON-RPC request from stdin: {e}");
+                    break;
+                }
+            },
+        };
+
+        if line.trim().is_empty() {
+            continue;
+        }
+
+        // requests are handled concurrently, as they are over other transports
+        let methods = methods.clone();
+        let to_stdout = to_stdout.clone();
+        tokio::spawn(async move { handle_request(&methods, &line, to_stdout).await });
+    }
+}
+
+async fn handle_request(methods: &Methods, request: &str, to_stdout: mpsc::Sender<String>) {
+    let (response, mut notifications) = match methods
+        .raw_json_request(request, SUBSCRIPTION_BUFFER_SIZE)
+        .await
+    {
+        Ok(result) => result,
+        Err(e) => {
+            tracing::debug!("failed to parse a JSON-RPC request from stdin: {e}");
+            let error = ErrorObject::from(ErrorCode::ParseError);
+            let response = MethodResponse::error(Id::Null, error);
+            let _ = to_stdout.send(response.result).await;
+            return;
+        }
+    };
+
+    if to_stdout.send(response.result).await.is_err() {
+        return;
+    }
+
+    // it yields something only for subscriptions, until they are closed
+    while let Some(notification) = notifications.recv().await {
+        if to_stdout.send(notification).await.is_err() {
+            return;
+        }
+    }
+}
+
+async fn write_stdout<W>(mut stdout: W, mut from_handlers: mpsc::Receiver<String>)
+where
+    W: AsyncWrite + Unpin,
+{
+    while let Some(message) = from_handlers.recv().await {
+        let result = async {
+            stdout.write_all(message.as_bytes()).await?;
+            stdout.write_all(b"\n").await?;
+            stdout.flush().await
+        }
+        .await;
+
+        if let Err(e) = result {
+            tracing::error!("failed to write a JSON-RPC message to stdout: {e}");
+            return;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use jsonrpsee::server::stop_channel;
+    use jsonrpsee::RpcModule;
+    use tokio::io::AsyncBufReadExt;
+    use tokio::io::AsyncWriteExt;
+    use tokio::io::BufReader;
+    use tokio::net::UnixStream;
+
+    use super::serve_lines;
+
+    #[tokio::test]
+    async fn requests_are_served_over_lines() {
+        let mut module = RpcModule::new(());
+        module
+            .register_method("echo", |params, _| params.one::<String>())
+            .unwrap();
+
+        let (server_side, client_side) = UnixStream::pair().unwrap();
+        let (server_reader, server_writer) = server_side.into_split();
+        let (stop_handle, server_handle) = stop_channel();
+        tokio::spawn(serve_lines(
+            module.into(),
+            server_reader,
+            server_writer,
+            stop_handle,
+        ));
+
+        let (client_reader, mut client_writer) = client_side.into_split();
+        let mut responses = BufReader::new(client_reader).lines();
+        let request = concat!(
+            r#"{"jsonrpc":"2.0","method":"echo","params":["hello"],"id":1}"#,
+            "\n",
+            "\n",
+            "not json\n",
+        );
+        client_writer.write_all(request.as_bytes()).await.unwrap();
+
+        let response = responses.next_line().await.unwrap().unwrap();
+        let parse_error = responses.next_line().await.unwrap().unwrap();
+        // requests are handled concurrently, so responses may be reordered
+        let (response, parse_error) = if response.contains("result") {
+            (response, parse_error)
+        } else {
+            (parse_error, response)
+        };
+        assert_eq!(response, r#"{"jsonrpc":"2.0","result":"hello","id":1}"#);
+        assert!(parse_error.contains(r#""code":-32700"#));
+
+        // the server stops when its input is closed
+        drop(client_writer);
+        server_handle.stopped().await;
+    }
+}
@@ -0,0 +1,128 @@
+/*
+ * Copyright 2024 Fluence Labs Limited
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+
+use jsonrpsee::server::ServerHandle;
+use tokio::net::UnixListener;
+
+use crate::serve::Endpoint;
+
+/// Serves HTTP and WebSocket JSON-RPC on a Unix domain socket in the background.
+pub(crate) async fn run_unix_server(
+    endpoint: Endpoint,
+    socket_path: &Path,
+    permissions: u32,
+) -> std::io::Result<ServerHandle> {
+    // a stale socket is left if CCP wasn't shut down gracefully
+    match tokio::fs::remove_file(socket_path).await {
+        Ok(()) => {}
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => return Err(e),
+    }
+
+    // The socket is bound in a private directory and moved into place after permissions
+    // are set, so nobody can connect to it in between.
+    let socket_dir = match socket_path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    let staging_dir = tempfile::Builder::new()
+        .prefix(".ccp-rpc-")
+        .tempdir_in(socket_dir)?;
+    let staging_path = staging_dir.path().join("socket");
+
+    let listener = UnixListener::bind(&staging_path)?;
+    let permissions = std::fs::Permissions::from_mode(permissions);
+    tokio::fs::set_permissions(&staging_path, permissions).await?;
+    tokio::fs::rename(&staging_path, socket_path).await?;
+    staging_dir.close()?;
+
+    let socket_path = socket_path.to_path_buf();
+    let handle = endpoint.spawn(listener, move || {
+        if let Err(e) = std::fs::remove_file(&socket_path) {
+            tracing::warn!(
+                "failed to remove unix socket {}: {e}",
+                socket_path.display()
+            );
+        }
+    });
+
+    Ok(handle)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::os::unix::fs::PermissionsExt;
+
+    use jsonrpsee::server::Server;
+    use jsonrpsee::RpcModule;
+    use tokio::io::AsyncReadExt;
+    use tokio::io::AsyncWriteExt;
+    use tokio::net::UnixStream;
+
+    use super::run_unix_server;
+    use crate::serve::Endpoint;
+
+    fn endpoint() -> Endpoint {
+        let mut module = RpcModule::new(());
+        module
+            .register_method("echo", |params, _| params.one::<String>())
+            .unwrap();
+
+        Endpoint {
+            service_builder: Server::builder().to_service_builder(),
+            methods: module.into(),
+            authorizer: None,
+        }
+    }
+
+    #[tokio::test]
+    async fn requests_are_served_on_unix_socket() {
+        let dir = tempfile::tempdir().unwrap();
+        let socket_path = dir.path().join("ccp.sock");
+        // a stale socket is replaced
+        std::fs::write(&socket_path, b"").unwrap();
+
+        let handle = run_unix_server(endpoint(), &socket_path, 0o600)
+            .await
+            .unwrap();
+
+        let mode = std::fs::metadata(&socket_path)
+            .unwrap()
+            .permissions()
+            .mode();
+        assert_eq!(mode & 0o777, 0o600);
+        // only the socket is left in its directory
+        assert_eq!(std::fs::read_dir(dir.path()).unwrap().count(), 1);
+
+        let body = r#"{"jsonrpc":"2.0","method":"echo","params":["hello"],"id":1}"#;
+        let request = format!(
+            "POST / HTTP/1.1\r\nHost: localhost\r\nContent-Type: application/json\r\n\
+             Content-Length: {}\r\nConnection: close\r\n\r\n{body}",
+            body.len()
+        );
+        let mut stream = UnixStream::connect(&socket_path).await.unwrap();
+        stream.write_all(request.as_bytes()).await.unwrap();
+        let mut response = String::new();
+        stream.read_to_string(&mut response).await.unwrap();
+        assert!(response.starts_with("HTTP/1.1 200 OK"));
+        assert!(response.ends_with(r#"{"jsonrpc":"2.0","result":"hello","id":1}"#));
+
+        handle.stop().unwrap();
+    }
+}
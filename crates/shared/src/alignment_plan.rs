@@ -0,0 +1,90 @@
+/*
+ * Copyright 2024 Fluence Labs Limited
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::types::EpochParameters;
+use crate::types::PhysicalCoreId;
+use crate::types::CUID;
+
+/// Shows what CCP would do to apply an active commitment, nothing is changed
+/// while the plan is computed.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AlignmentPlan {
+    pub epoch: EpochParameters,
+    pub pre_action: PlannedPreAction,
+    pub actions: Vec<PlannedAction>,
+    /// Number of CU provers which would initialize a new RandomX cache and dataset,
+    /// it's the most time consuming part of an alignment.
+    pub datasets_to_initialize: usize,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PlannedPreAction {
+    NoAction,
+    /// Proofs of the previous epoch would be moved to the archive.
+    CleanupProofCache,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum PlannedAction {
+    CreateCUProver {
+        new_core_id: PhysicalCoreId,
+        new_cu_id: CUID,
+    },
+    RemoveCUProver {
+        current_core_id: PhysicalCoreId,
+    },
+    NewCCJob {
+        current_core_id: PhysicalCoreId,
+        new_cu_id: CUID,
+    },
+    NewCCJobWithRepining {
+        current_core_id: PhysicalCoreId,
+        new_core_id: PhysicalCoreId,
+        new_cu_id: CUID,
+    },
+}
+
+impl PlannedAction {
+    /// Returns true if the action makes a CU prover initialize a new dataset.
+    pub fn initializes_dataset(&self) -> bool {
+        !matches!(self, Self::RemoveCUProver { .. })
+    }
+}
+
+impl AlignmentPlan {
+    pub fn new(
+        epoch: EpochParameters,
+        pre_action: PlannedPreAction,
+        actions: Vec<PlannedAction>,
+    ) -> Self {
+        let datasets_to_initialize = actions
+            .iter()
+            .filter(|action| action.initializes_dataset())
+            .count();
+
+        Self {
+            epoch,
+            pre_action,
+            actions,
+            datasets_to_initialize,
+        }
+    }
+}
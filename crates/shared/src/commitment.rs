@@ -0,0 +1,84 @@
+/*
+ * Copyright 2024 Fluence Labs Limited
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::types::PhysicalCoreId;
+use crate::types::CUID;
+
+/// Monotonically increasing number of a commitment change (an active commitment or its absence)
+/// accepted by CCP. A change with a lower generation may be superseded by a later one
+/// and never applied on its own, so a generation is considered applied once
+/// the applied generation reaches it.
+pub type CommitmentGeneration = u64;
+
+/// Shows how far CCP is in applying accepted commitment changes.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CommitmentProgress {
+    /// Generation of the last accepted commitment change.
+    pub requested_generation: CommitmentGeneration,
+    /// Generation of the last commitment change applied, successfully or not.
+    pub applied_generation: CommitmentGeneration,
+    /// A commitment change which is being applied at the moment.
+    pub in_flight: Option<InFlightCommitment>,
+    /// An error of the last applied commitment change, per CU details are in `cu_errors`.
+    pub last_error: Option<String>,
+    /// Errors of CU provers in the last applied commitment change.
+    pub cu_errors: Vec<CUCommitmentError>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct InFlightCommitment {
+    pub generation: CommitmentGeneration,
+    /// CU provers affected by the change, ordered by core id.
+    pub cu_provers: Vec<CUCommitmentProgress>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CUCommitmentProgress {
+    pub core_id: PhysicalCoreId,
+    /// Absent for CU provers which are being removed.
+    pub cu_id: Option<CUID>,
+    #[serde(flatten)]
+    pub phase: CUCommitmentPhase,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "phase", rename_all = "snake_case")]
+pub enum CUCommitmentPhase {
+    Pending,
+    Stopping,
+    CreatingCache,
+    /// Dataset initialization is split between threads of a CU prover,
+    /// the percentage is updated as they finish their parts.
+    InitializingDataset {
+        percent: u8,
+    },
+    StartingJobs,
+    Running,
+    Stopped,
+    Failed {
+        error: String,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CUCommitmentError {
+    pub core_id: PhysicalCoreId,
+    pub cu_id: Option<CUID>,
+    pub error: String,
+}
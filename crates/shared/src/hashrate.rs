@@ -0,0 +1,96 @@
+/*
+ * Copyright 2024 Fluence Labs Limited
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::types::LogicalCoreId;
+use crate::types::CUID;
+
+/// Hashrate of the current epoch, broken down by logical cores and CUs.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct HashrateReport {
+    /// Logical cores ordered by core id.
+    pub logical_cores: Vec<LogicalCoreHashrate>,
+    /// CUs which are proven at the moment, ordered by CUID.
+    pub cus: Vec<CUHashrate>,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct LogicalCoreHashrate {
+    pub core_id: LogicalCoreId,
+    #[serde(flatten)]
+    pub hashrate: HashrateStats,
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CUHashrate {
+    pub cu_id: CUID,
+    /// Logical cores of threads proving this CU.
+    pub core_ids: Vec<LogicalCoreId>,
+    /// Hashrates and proofs are summed up over the threads, cache and dataset timings
+    /// are the longest ones, since threads do it in parallel.
+    #[serde(flatten)]
+    pub hashrate: HashrateStats,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct HashrateStats {
+    /// Hashes per second since the epoch start, counting time spent on cache and dataset.
+    pub effective_hashrate: f64,
+    /// Hashes per second of proving only, none if proving hasn't started yet.
+    pub pure_hashrate: Option<f64>,
+    pub proofs_found: u64,
+    pub cache_creation_secs: Option<f64>,
+    pub dataset_initialization_secs: Option<f64>,
+    /// Pure hashrate over the last 10, 60 and 900 seconds.
+    pub windows: HashrateWindows,
+}
+
+#[derive(Copy, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct HashrateWindows {
+    pub secs_10: f64,
+    pub secs_60: f64,
+    pub secs_900: f64,
+}
+
+impl HashrateStats {
+    /// Adds up stats of threads working on the same CU.
+    pub fn merge(mut self, other: &HashrateStats) -> Self {
+        fn max(lhs: Option<f64>, rhs: Option<f64>) -> Option<f64> {
+            match (lhs, rhs) {
+                (Some(lhs), Some(rhs)) => Some(lhs.max(rhs)),
+                (lhs, rhs) => lhs.or(rhs),
+            }
+        }
+
+        self.effective_hashrate += other.effective_hashrate;
+        self.pure_hashrate = match (self.pure_hashrate, other.pure_hashrate) {
+            (Some(lhs), Some(rhs)) => Some(lhs + rhs),
+            (lhs, rhs) => lhs.or(rhs),
+        };
+        self.proofs_found += other.proofs_found;
+        self.cache_creation_secs = max(self.cache_creation_secs, other.cache_creation_secs);
+        self.dataset_initialization_secs = max(
+            self.dataset_initialization_secs,
+            other.dataset_initialization_secs,
+        );
+        self.windows.secs_10 += other.windows.secs_10;
+        self.windows.secs_60 += other.windows.secs_60;
+        self.windows.secs_900 += other.windows.secs_900;
+        self
+    }
+}
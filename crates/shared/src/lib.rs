@@ -26,9 +26,13 @@
     unreachable_patterns
 )]
 
+pub mod alignment_plan;
+pub mod commitment;
+pub mod hashrate;
 pub mod meet_difficulty;
 pub mod nox_ccp_api;
 pub mod proof;
+pub mod status;
 pub mod types;
 
 /// Size of the RandomX result hash in bytes.
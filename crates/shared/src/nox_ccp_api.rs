@@ -14,9 +14,16 @@
  * limitations under the License.
  */
 
+use futures::stream::BoxStream;
+
 use crate::proof::ProofIdx;
 
+use super::alignment_plan::AlignmentPlan;
+use super::commitment::CommitmentGeneration;
+use super::commitment::CommitmentProgress;
+use super::hashrate::HashrateReport;
 use super::proof::CCProof;
+use super::status::ProverStatus;
 use super::types::*;
 
 pub trait NoxCCPApi: Send {
@@ -39,23 +46,80 @@ pub trait NoxCCPApi: Send {
     ///  - CU allocation is changed (e.g. a core is assigned or released to CC)
     ///    after event from the on-chain part
     ///  - Nox (re)started
+    ///
+    /// Returns a generation assigned to the change, it could be applied later,
+    /// so its progress is available via `get_commitment_progress`.
     fn on_active_commitment(
         &mut self,
         epoch_parameters: EpochParameters,
         cu_allocation: CUAllocation,
-    ) -> impl std::future::Future<Output = Result<(), Self::Error>> + Send;
+    ) -> impl std::future::Future<Output = Result<CommitmentGeneration, Self::Error>> + Send;
 
-    /// Stops all active jobs.
+    /// Returns actions `on_active_commitment` would take to apply the allocation
+    /// to the current CU provers, without applying them.
+    fn plan_active_commitment(
+        &self,
+        epoch_parameters: EpochParameters,
+        cu_allocation: CUAllocation,
+    ) -> impl std::future::Future<Output = Result<AlignmentPlan, Self::Error>> + Send;
+
+    /// Stops all active jobs, returns a generation assigned to the change.
     fn on_no_active_commitment(
         &mut self,
-    ) -> impl std::future::Future<Output = Result<(), Self::Error>> + Send;
+    ) -> impl std::future::Future<Output = Result<CommitmentGeneration, Self::Error>> + Send;
 
-    /// Returns proofs after the provided proof idx for current epoch.
+    /// Returns at most `limit` proofs after the provided proof idx, ordered by proof idx.
+    /// Proofs of the current epoch are returned if global nonce isn't provided,
+    /// otherwise proofs of the epoch with the provided global nonce, including archived ones.
     fn get_proofs_after(
         &self,
         proof_idx: ProofIdx,
+        limit: usize,
+        global_nonce: Option<GlobalNonce>,
     ) -> impl std::future::Future<Output = Result<Vec<CCProof>, Self::Error>> + Send;
 
+    /// Returns a stream of proofs of the current epoch after the provided proof idx,
+    /// already stored proofs are replayed first, then new ones are yielded as they are found.
+    fn subscribe_proofs(
+        &self,
+        from_idx: ProofIdx,
+    ) -> impl std::future::Future<Output = Result<BoxStream<'static, CCProof>, Self::Error>> + Send;
+
+    /// Acknowledges that all proofs of the epoch with the provided global nonce
+    /// up to the provided proof idx (inclusive) are submitted on-chain,
+    /// so CCP doesn't need to keep them anymore.
+    fn ack_proofs(
+        &self,
+        global_nonce: GlobalNonce,
+        proof_idx: ProofIdx,
+    ) -> impl std::future::Future<Output = Result<(), Self::Error>> + Send;
+
+    /// Returns a snapshot of the prover state: its status, CU provers with their cores,
+    /// and utility cores.
+    fn get_status(
+        &self,
+    ) -> impl std::future::Future<Output = Result<ProverStatus, Self::Error>> + Send;
+
+    /// Returns cumulative and sliding hashrate of the current epoch
+    /// per logical core and per CU.
+    fn get_hashrate(
+        &self,
+    ) -> impl std::future::Future<Output = Result<HashrateReport, Self::Error>> + Send;
+
+    /// Returns generations of accepted and applied commitment changes,
+    /// phases of the change being applied, and errors of the last applied one.
+    fn get_commitment_progress(
+        &self,
+    ) -> impl std::future::Future<Output = Result<CommitmentProgress, Self::Error>> + Send;
+
+    /// Suspends hashing on all CU provers, their caches, datasets and jobs are kept,
+    /// so `resume` continues proving without re-initialization.
+    /// It lasts across commitment changes, but not across restarts.
+    fn pause(&self) -> impl std::future::Future<Output = Result<(), Self::Error>> + Send;
+
+    /// Resumes hashing suspended by `pause`.
+    fn resume(&self) -> impl std::future::Future<Output = Result<(), Self::Error>> + Send;
+
     /// Set utility
     fn realloc_utility_cores(
         &self,
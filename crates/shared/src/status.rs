@@ -0,0 +1,118 @@
+/*
+ * Copyright 2024 Fluence Labs Limited
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use serde::Deserialize;
+use serde::Serialize;
+
+use crate::types::EpochParameters;
+use crate::types::LogicalCoreId;
+use crate::types::PhysicalCoreId;
+use crate::types::CUID;
+
+/// Represents a status of a CC prover.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum CCStatus {
+    Running { epoch: EpochParameters },
+    Idle,
+}
+
+/// Represents a status of a CU prover.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum CUStatus {
+    Running { cu_id: CUID },
+    Idle,
+}
+
+/// Represents a state of the proof rate circuit breaker, which stops persisting proofs
+/// until the next epoch, if they are found dangerously often.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+pub enum ProofCircuitStatus {
+    Closed,
+    Tripped { reason: ProofCircuitTripReason },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ProofCircuitTripReason {
+    CURateExceeded { cu_id: CUID, proofs_per_minute: u64 },
+    EpochLimitExceeded { proofs: u64 },
+}
+
+impl std::fmt::Display for ProofCircuitTripReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::CURateExceeded {
+                cu_id,
+                proofs_per_minute,
+            } => write!(
+                f,
+                "CU {cu_id} found more than {proofs_per_minute} proofs per minute"
+            ),
+            Self::EpochLimitExceeded { proofs } => {
+                write!(f, "more than {proofs} proofs were found in the epoch")
+            }
+        }
+    }
+}
+
+/// Represents how much free space is left on the filesystem of the state directory.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DiskSpaceLevel {
+    #[default]
+    Normal,
+    Warning,
+    Critical,
+}
+
+impl std::fmt::Display for DiskSpaceLevel {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Normal => write!(f, "normal"),
+            Self::Warning => write!(f, "warning"),
+            Self::Critical => write!(f, "critical"),
+        }
+    }
+}
+
+/// A snapshot of what the prover is doing at the moment.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProverStatus {
+    pub status: CCStatus,
+    pub cu_provers: Vec<CUProverStatus>,
+    /// Logical cores used by the utility thread and other non-proving tasks.
+    pub utility_core_ids: Vec<LogicalCoreId>,
+    /// True if an accepted `on_active_commitment` call is still queued or being applied,
+    /// so the rest of the status could be outdated.
+    pub active_commitment_pending: bool,
+    /// True if hashing is paused by `pause`, caches and datasets are kept.
+    pub paused: bool,
+    pub proof_circuit: ProofCircuitStatus,
+    pub disk_space_level: DiskSpaceLevel,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CUProverStatus {
+    pub status: CUStatus,
+    /// A CU the prover is working for, or none if it's idle.
+    pub cu_id: Option<CUID>,
+    pub pinned_core_id: PhysicalCoreId,
+    /// Logical cores the proving threads are pinned to.
+    pub thread_core_ids: Vec<LogicalCoreId>,
+}
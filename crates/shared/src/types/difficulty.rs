@@ -32,6 +32,19 @@ impl Difficulty {
     pub fn new(inner: DifficultyInner) -> Self {
         Self(inner)
     }
+
+    /// Returns the number of leading zero bits, a hash meets the difficulty
+    /// with a probability of roughly 2^-leading_zero_bits.
+    pub fn leading_zero_bits(&self) -> u32 {
+        let mut bits = 0;
+        for byte in self.0 {
+            if byte != 0 {
+                return bits + byte.leading_zeros();
+            }
+            bits += u8::BITS;
+        }
+        bits
+    }
 }
 
 impl AsRef<DifficultyInner> for Difficulty {
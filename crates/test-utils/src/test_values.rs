@@ -14,6 +14,8 @@
  * limitations under the License.
  */
 
+use ccp_shared::proof::CCProof;
+use ccp_shared::proof::CCProofId;
 use ccp_shared::types::*;
 
 pub fn generate_epoch_params(nonce: u8, difficulty: u8) -> EpochParameters {
@@ -51,6 +53,23 @@ pub fn generate_difficulty(difficulty: u8) -> Difficulty {
     ])
 }
 
+/// Generates a proof with a zeroed result hash, so it doesn't pass verification.
+pub fn generate_dummy_proof(global_nonce: u8, idx: u64) -> CCProof {
+    let idx = idx.to_string().parse().expect("u64 is a valid proof idx");
+    let id = CCProofId::new(
+        generate_global_nonce(global_nonce),
+        generate_difficulty(1),
+        idx,
+    );
+
+    CCProof::new(
+        id,
+        generate_local_nonce(1),
+        generate_cu_id(1),
+        ResultHash::from_slice([0; 32]),
+    )
+}
+
 pub fn generate_allocation(cores: &[u8]) -> CUAllocation {
     cores
         .iter()
@@ -0,0 +1,65 @@
+/*
+ * Copyright 2024 Fluence Labs Limited
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::path::PathBuf;
+
+use thiserror::Error as ThisError;
+
+use ccp_randomx::RandomXError;
+use ccp_shared::types::Difficulty;
+use ccp_shared::types::ResultHash;
+
+#[derive(ThisError, Debug)]
+pub enum ProofVerificationError {
+    #[error("result hash mismatch: proof contains {in_proof}, but recomputed one is {computed}")]
+    ResultHashMismatch {
+        in_proof: ResultHash,
+        computed: ResultHash,
+    },
+
+    #[error("result hash {result_hash} doesn't meet difficulty {difficulty}")]
+    DifficultyNotMet {
+        result_hash: ResultHash,
+        difficulty: Difficulty,
+    },
+
+    #[error(transparent)]
+    RandomXError(#[from] RandomXError),
+
+    #[error("failed to read proof from {path:?}: {error}")]
+    IOError {
+        path: PathBuf,
+        #[source]
+        error: std::io::Error,
+    },
+
+    #[error("failed to parse proof from {path:?}: {error}")]
+    ParseError {
+        path: PathBuf,
+        #[source]
+        error: serde_json::Error,
+    },
+}
+
+impl ProofVerificationError {
+    pub fn io_error(path: PathBuf, error: std::io::Error) -> Self {
+        Self::IOError { path, error }
+    }
+
+    pub fn parse_error(path: PathBuf, error: serde_json::Error) -> Self {
+        Self::ParseError { path, error }
+    }
+}
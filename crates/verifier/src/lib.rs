@@ -14,22 +14,26 @@
  * limitations under the License.
  */
 
-use super::ThreadHashrateRecord;
+#![warn(rust_2018_idioms)]
+#![warn(rust_2021_compatibility)]
+#![deny(
+    dead_code,
+    nonstandard_style,
+    unused_imports,
+    unused_mut,
+    unused_variables,
+    unused_unsafe,
+    unreachable_patterns
+)]
 
-pub(crate) type SlidingHashrate = ();
+mod errors;
+mod loader;
+#[cfg(test)]
+mod tests;
+mod verifier;
 
-/// Replacement of SlidingHashrateCollector which does nothing to make the crossterm dep optional.
-#[derive(Clone, Debug, Default)]
-pub(crate) struct SlidingHashrateCollector {}
+pub type VResult<T> = Result<T, ProofVerificationError>;
 
-impl SlidingHashrateCollector {
-    pub(crate) fn new() -> Self {
-        Self::default()
-    }
-
-    pub(crate) fn account_record(&mut self, _record: ThreadHashrateRecord) {}
-
-    pub(crate) fn hashrate(&self) -> &SlidingHashrate {
-        &()
-    }
-}
+pub use errors::ProofVerificationError;
+pub use loader::load_proofs;
+pub use verifier::ProofVerifier;
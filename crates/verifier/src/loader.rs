@@ -0,0 +1,74 @@
+/*
+ * Copyright 2024 Fluence Labs Limited
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::path::Path;
+use std::path::PathBuf;
+use std::str::FromStr;
+
+use ccp_shared::proof::CCProof;
+use ccp_shared::proof::ProofIdx;
+
+use crate::ProofVerificationError;
+use crate::VResult;
+
+/// Loads proofs from the provided path, which could be either a single proof JSON file
+/// or a proof directory of CCP, where each proof is stored in a file named by its index.
+///
+/// Files with non-numeric names in a proof directory are ignored, they are sorted by proof
+/// index otherwise. A file that can't be read or parsed doesn't stop loading of other ones.
+pub fn load_proofs(path: &Path) -> VResult<Vec<(PathBuf, VResult<CCProof>)>> {
+    if !path.is_dir() {
+        let proof = load_proof(path);
+        return Ok(vec![(path.to_path_buf(), proof)]);
+    }
+
+    let directory = std::fs::read_dir(path)
+        .map_err(|e| ProofVerificationError::io_error(path.to_path_buf(), e))?;
+
+    let mut proof_paths = Vec::new();
+    for entry in directory {
+        let entry = entry.map_err(|e| ProofVerificationError::io_error(path.to_path_buf(), e))?;
+        let entry_path = entry.path();
+        if !entry_path.is_file() {
+            continue;
+        }
+
+        let proof_idx = entry
+            .file_name()
+            .to_str()
+            .and_then(|name| ProofIdx::from_str(name).ok());
+        if let Some(proof_idx) = proof_idx {
+            proof_paths.push((proof_idx, entry_path));
+        }
+    }
+    proof_paths.sort_unstable();
+
+    let proofs = proof_paths
+        .into_iter()
+        .map(|(_, path)| {
+            let proof = load_proof(&path);
+            (path, proof)
+        })
+        .collect();
+    Ok(proofs)
+}
+
+fn load_proof(path: &Path) -> VResult<CCProof> {
+    let content =
+        std::fs::read(path).map_err(|e| ProofVerificationError::io_error(path.to_path_buf(), e))?;
+    serde_json::from_slice(&content)
+        .map_err(|e| ProofVerificationError::parse_error(path.to_path_buf(), e))
+}
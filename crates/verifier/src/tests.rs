@@ -0,0 +1,149 @@
+/*
+ * Copyright 2024 Fluence Labs Limited
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use ccp_randomx::RandomXFlags;
+use ccp_shared::proof::CCProof;
+use ccp_shared::proof::CCProofId;
+use ccp_shared::proof::ProofIdx;
+use ccp_shared::types::Difficulty;
+use ccp_shared::types::CUID;
+use ccp_test_utils::randomx::run_light_randomx;
+use ccp_test_utils::test_values::*;
+
+use crate::load_proofs;
+use crate::ProofVerificationError;
+use crate::ProofVerifier;
+
+fn generate_proof(difficulty: Difficulty, idx: ProofIdx) -> CCProof {
+    generate_cu_proof(generate_cu_id(3), difficulty, idx)
+}
+
+fn generate_cu_proof(cu_id: CUID, difficulty: Difficulty, idx: ProofIdx) -> CCProof {
+    let global_nonce = generate_global_nonce(1);
+    let local_nonce = generate_local_nonce(2);
+
+    let global_nonce_cu = ccp_utils::hash::compute_global_nonce_cu(&global_nonce, &cu_id);
+    let result_hash = run_light_randomx(
+        global_nonce_cu.as_slice(),
+        local_nonce.as_ref(),
+        RandomXFlags::recommended(),
+    );
+
+    let id = CCProofId::new(global_nonce, difficulty, idx);
+    CCProof::new(id, local_nonce, cu_id, result_hash)
+}
+
+#[test]
+fn valid_proof_verified() {
+    let proof = generate_proof(Difficulty::new([0xFF; 32]), ProofIdx::zero());
+
+    let mut verifier = ProofVerifier::default();
+    let result = verifier.verify(&proof);
+
+    assert!(result.is_ok(), "{result:?}");
+}
+
+#[test]
+fn proof_with_wrong_local_nonce_rejected() {
+    let mut proof = generate_proof(Difficulty::new([0xFF; 32]), ProofIdx::zero());
+    proof.local_nonce = generate_local_nonce(42);
+
+    let mut verifier = ProofVerifier::default();
+    let result = verifier.verify(&proof);
+
+    assert!(
+        matches!(
+            result,
+            Err(ProofVerificationError::ResultHashMismatch { .. })
+        ),
+        "{result:?}"
+    );
+}
+
+#[test]
+fn proof_not_meeting_difficulty_rejected() {
+    let proof = generate_proof(Difficulty::new([0x00; 32]), ProofIdx::zero());
+
+    let mut verifier = ProofVerifier::default();
+    let result = verifier.verify(&proof);
+
+    assert!(
+        matches!(result, Err(ProofVerificationError::DifficultyNotMet { .. })),
+        "{result:?}"
+    );
+}
+
+#[test]
+fn verify_all_keeps_order() {
+    let valid_proof = generate_proof(Difficulty::new([0xFF; 32]), ProofIdx::zero());
+    let mut invalid_proof = valid_proof;
+    invalid_proof.cu_id = generate_cu_id(4);
+
+    let mut verifier = ProofVerifier::default();
+    let results = verifier.verify_all(&[invalid_proof, valid_proof, invalid_proof]);
+
+    assert_eq!(results.len(), 3);
+    assert!(results[0].is_err());
+    assert!(results[1].is_ok());
+    assert!(results[2].is_err());
+}
+
+#[test]
+fn proofs_of_alternating_cus_verified() {
+    // more CUs than cached VMs, so some VMs are evicted and created again
+    let proofs = (3..9)
+        .map(|cu_seed| {
+            generate_cu_proof(
+                generate_cu_id(cu_seed),
+                Difficulty::new([0xFF; 32]),
+                ProofIdx::zero(),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let mut verifier = ProofVerifier::default();
+    for proof in proofs.iter().chain(proofs.iter().rev()) {
+        let result = verifier.verify(proof);
+        assert!(result.is_ok(), "{result:?}");
+    }
+}
+
+#[test]
+fn proofs_loaded_from_directory() {
+    let dir = tempdir::TempDir::new("ccp-verifier").unwrap();
+
+    let mut idx = ProofIdx::zero();
+    let first_proof = generate_proof(Difficulty::new([0xFF; 32]), idx);
+    idx.increment();
+    let second_proof = generate_proof(Difficulty::new([0xFF; 32]), idx);
+
+    for proof in [&second_proof, &first_proof] {
+        let content = serde_json::to_vec(proof).unwrap();
+        std::fs::write(dir.path().join(proof.id.idx.to_string()), content).unwrap();
+    }
+    std::fs::write(dir.path().join("not_a_proof"), b"garbage").unwrap();
+    std::fs::write(dir.path().join("2"), b"garbage").unwrap();
+
+    let proofs = load_proofs(dir.path()).unwrap();
+
+    assert_eq!(proofs.len(), 3);
+    assert_eq!(proofs[0].1.as_ref().unwrap(), &first_proof);
+    assert_eq!(proofs[1].1.as_ref().unwrap(), &second_proof);
+    assert!(matches!(
+        proofs[2].1,
+        Err(ProofVerificationError::ParseError { .. })
+    ));
+}
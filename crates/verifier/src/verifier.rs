@@ -0,0 +1,132 @@
+/*
+ * Copyright 2024 Fluence Labs Limited
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::collections::VecDeque;
+
+use ccp_randomx::cache::CacheHandle;
+use ccp_randomx::Cache;
+use ccp_randomx::RandomXFlags;
+use ccp_randomx::RandomXVM;
+use ccp_shared::meet_difficulty::MeetDifficulty;
+use ccp_shared::proof::CCProof;
+use ccp_shared::types::GlobalNonce;
+use ccp_shared::types::ResultHash;
+use ccp_shared::types::CUID;
+
+use crate::ProofVerificationError;
+use crate::VResult;
+
+/// How many light-mode VMs are kept at most, each of them holds a 256 MiB cache.
+const MAX_CACHED_VMS: usize = 4;
+
+type VMKey = (GlobalNonce, CUID);
+
+/// Checks proofs independently of the prover that found them: it recomputes
+/// the result hash with a light-mode RandomX VM and checks it against the difficulty
+/// from the proof id.
+///
+/// Light-mode VMs of a few recently used (global nonce, CU id) pairs are kept, because
+/// cache creation is much more expensive than a single hash computation.
+pub struct ProofVerifier {
+    flags: RandomXFlags,
+    /// The most recently used VM is the last one.
+    vms: VecDeque<(VMKey, RandomXVM<CacheHandle>)>,
+}
+
+impl ProofVerifier {
+    /// Creates a verifier with the provided flags, the full mem flag is removed,
+    /// since verification is always done in the light mode.
+    pub fn new(flags: RandomXFlags) -> Self {
+        let mut flags = flags;
+        flags.remove(RandomXFlags::FULL_MEM);
+
+        Self {
+            flags,
+            vms: VecDeque::with_capacity(MAX_CACHED_VMS),
+        }
+    }
+
+    pub fn verify(&mut self, proof: &CCProof) -> VResult<()> {
+        let computed = self.compute_result_hash(proof)?;
+        if computed != proof.result_hash {
+            return Err(ProofVerificationError::ResultHashMismatch {
+                in_proof: proof.result_hash,
+                computed,
+            });
+        }
+
+        if !proof.result_hash.meet_difficulty(&proof.id.difficulty) {
+            return Err(ProofVerificationError::DifficultyNotMet {
+                result_hash: proof.result_hash,
+                difficulty: proof.id.difficulty,
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Verifies the supplied proofs grouped by (global nonce, CU id), so a cache
+    /// is created only once per group, results are returned in the original order.
+    pub fn verify_all(&mut self, proofs: &[CCProof]) -> Vec<VResult<()>> {
+        let mut order = (0..proofs.len()).collect::<Vec<_>>();
+        order.sort_by_key(|&idx| (*proofs[idx].id.global_nonce.as_ref(), proofs[idx].cu_id));
+
+        let mut results = order
+            .into_iter()
+            .map(|idx| (idx, self.verify(&proofs[idx])))
+            .collect::<Vec<_>>();
+        results.sort_unstable_by_key(|(idx, _)| *idx);
+
+        results.into_iter().map(|(_, result)| result).collect()
+    }
+
+    fn compute_result_hash(&mut self, proof: &CCProof) -> VResult<ResultHash> {
+        let key = Self::vm_key(proof);
+
+        match self.vms.iter().position(|(vm_key, _)| *vm_key == key) {
+            Some(position) => {
+                let entry = self.vms.remove(position).expect("position is in bounds");
+                self.vms.push_back(entry);
+            }
+            None => {
+                if self.vms.len() == MAX_CACHED_VMS {
+                    // drop the least recently used VM before creating a new one
+                    // to not hold an extra cache
+                    self.vms.pop_front();
+                }
+
+                let global_nonce_cu =
+                    ccp_utils::hash::compute_global_nonce_cu(&proof.id.global_nonce, &proof.cu_id);
+                let cache = Cache::new(global_nonce_cu.as_slice(), self.flags)?;
+                let vm = RandomXVM::light(cache.handle(), self.flags)?;
+                self.vms.push_back((key, vm));
+            }
+        }
+
+        let (_, vm) = self.vms.back().expect("the VM has just been pushed");
+        Ok(vm.hash(proof.local_nonce.as_ref()))
+    }
+
+    fn vm_key(proof: &CCProof) -> VMKey {
+        (proof.id.global_nonce, proof.cu_id)
+    }
+}
+
+impl Default for ProofVerifier {
+    fn default() -> Self {
+        Self::new(RandomXFlags::recommended())
+    }
+}
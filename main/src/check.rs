@@ -0,0 +1,458 @@
+/*
+ * Copyright 2024 Fluence Labs Limited
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::collections::BTreeSet;
+use std::fmt;
+use std::path::Path;
+
+use ccp_config::CCPConfig;
+use ccp_config::LoadedConfig;
+use ccp_config::RandomXFlags;
+use ccp_config::RpcTransport;
+use ccp_randomx::Dataset;
+use cpu_utils::CPUTopology;
+use cpu_utils::LogicalCoreId;
+use cpu_utils::PhysicalCoreId;
+
+const MEMINFO_PATH: &str = "/proc/meminfo";
+const CPU_DEVICES_DIR: &str = "/dev/cpu";
+
+enum CheckOutcome {
+    Passed(String),
+    Skipped(String),
+    Failed(String),
+}
+
+/// Checks that the host is able to run the prover with the config and prints the outcome
+/// of every check; fails if at least one of them failed.
+pub(crate) fn run_checks(loaded_config: &LoadedConfig) -> eyre::Result<()> {
+    // ignored keys are likely typos, so they are shown along with the checks
+    for warning in config_warnings(loaded_config) {
+        println!("config: warning, {warning}");
+    }
+
+    let config = &loaded_config.config;
+    let allocatable_cores = allocatable_cores(config);
+    let expected_datasets = allocatable_cores.as_ref().map(Vec::len).ok();
+
+    let checks = [
+        ("cores", check_cores(config, &allocatable_cores)),
+        ("huge pages", check_huge_pages(config, expected_datasets)),
+        ("msr", check_msr(config)),
+        ("cpu features", check_cpu_features(config)),
+        ("rpc endpoint", check_rpc_endpoint(config)),
+        ("prometheus endpoint", check_prometheus_endpoint(config)),
+        ("state dir", check_state_dir(&config.state_dir)),
+    ];
+
+    let mut failed_count = 0;
+    for (name, outcome) in &checks {
+        if matches!(outcome, CheckOutcome::Failed(_)) {
+            failed_count += 1;
+        }
+        println!("{name}: {outcome}");
+    }
+
+    if failed_count != 0 {
+        eyre::bail!("{failed_count} of {} checks failed", checks.len());
+    }
+
+    println!("all checks passed");
+    Ok(())
+}
+
+fn config_warnings(loaded_config: &LoadedConfig) -> Vec<String> {
+    let unknown_keys = loaded_config
+        .unknown_keys
+        .iter()
+        .map(|key| format!("unknown config key {key} is ignored"));
+    let ignored_env_vars = loaded_config
+        .ignored_env_vars
+        .iter()
+        .map(|var| format!("env var {var} doesn't match any config key and is ignored"));
+    unknown_keys.chain(ignored_env_vars).collect()
+}
+
+/// Returns physical cores, which could be allocated to CUs, i.e. ones
+/// which logical cores aren't used as utility ones.
+fn allocatable_cores(config: &CCPConfig) -> Result<Vec<PhysicalCoreId>, String> {
+    let topology = CPUTopology::new().map_err(|e| format!("failed to get CPU topology: {e}"))?;
+    let physical_cores = topology
+        .physical_cores()
+        .map_err(|e| format!("failed to get physical cores: {e}"))?;
+
+    let utility_cores = config
+        .tokio
+        .utility_cores_ids
+        .iter()
+        .copied()
+        .collect::<BTreeSet<_>>();
+    let mut logical_cores = BTreeSet::new();
+    let mut allocatable_cores = vec![];
+    for physical_core in physical_cores {
+        let core_logical_cores = topology
+            .logical_cores_for_physical(physical_core)
+            .map_err(|e| format!("failed to get logical cores of {physical_core}: {e}"))?;
+        if core_logical_cores
+            .iter()
+            .all(|core| !utility_cores.contains(core))
+        {
+            allocatable_cores.push(physical_core);
+        }
+        logical_cores.extend(core_logical_cores);
+    }
+
+    let missing_cores = utility_cores
+        .difference(&logical_cores)
+        .map(ToString::to_string)
+        .collect::<Vec<_>>();
+    if !missing_cores.is_empty() {
+        return Err(format!(
+            "utility cores {} don't exist",
+            missing_cores.join(", ")
+        ));
+    }
+
+    Ok(allocatable_cores)
+}
+
+fn check_cores(
+    config: &CCPConfig,
+    allocatable_cores: &Result<Vec<PhysicalCoreId>, String>,
+) -> CheckOutcome {
+    match allocatable_cores {
+        Ok(cores) if cores.is_empty() => {
+            CheckOutcome::Failed("no physical cores are left for CUs besides utility ones".into())
+        }
+        Ok(cores) => CheckOutcome::Passed(format!(
+            "{} utility cores, {} physical cores could be allocated to CUs",
+            describe_utility_cores(&config.tokio.utility_cores_ids),
+            cores.len()
+        )),
+        Err(e) => CheckOutcome::Failed(e.clone()),
+    }
+}
+
+fn describe_utility_cores(utility_cores: &[LogicalCoreId]) -> String {
+    if utility_cores.is_empty() {
+        return "all cores are".to_string();
+    }
+
+    let cores = utility_cores
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>();
+    format!("[{}] are", cores.join(", "))
+}
+
+fn check_huge_pages(config: &CCPConfig, expected_datasets: Option<usize>) -> CheckOutcome {
+    if !config
+        .optimizations
+        .randomx_flags
+        .contains(RandomXFlags::LARGE_PAGES)
+    {
+        return CheckOutcome::Skipped("large pages are disabled".into());
+    }
+    let Some(expected_datasets) = expected_datasets else {
+        return CheckOutcome::Skipped("the number of datasets is unknown".into());
+    };
+
+    let meminfo = match std::fs::read_to_string(MEMINFO_PATH) {
+        Ok(meminfo) => meminfo,
+        Err(e) => return CheckOutcome::Failed(format!("failed to read {MEMINFO_PATH}: {e}")),
+    };
+    let (Some(free_pages), Some(page_size_kb)) = (
+        meminfo_value(&meminfo, "HugePages_Free"),
+        meminfo_value(&meminfo, "Hugepagesize"),
+    ) else {
+        return CheckOutcome::Failed(format!("huge pages aren't reported in {MEMINFO_PATH}"));
+    };
+
+    const BYTES_IN_MB: u64 = 1024 * 1024;
+    let free_bytes = free_pages * page_size_kb * 1024;
+    let required_bytes = Dataset::memory_size() * expected_datasets as u64;
+    let message = format!(
+        "{} MB of huge pages are free, {} MB are required for {expected_datasets} datasets",
+        free_bytes / BYTES_IN_MB,
+        required_bytes / BYTES_IN_MB
+    );
+    if free_bytes < required_bytes {
+        return CheckOutcome::Failed(message);
+    }
+    CheckOutcome::Passed(message)
+}
+
+/// Parses a numeric value of a /proc/meminfo entry like `Hugepagesize:    2048 kB`.
+fn meminfo_value(meminfo: &str, key: &str) -> Option<u64> {
+    meminfo.lines().find_map(|line| {
+        let (line_key, value) = line.split_once(':')?;
+        if line_key.trim() != key {
+            return None;
+        }
+        value.split_whitespace().next()?.parse().ok()
+    })
+}
+
+fn check_msr(config: &CCPConfig) -> CheckOutcome {
+    if !config.optimizations.msr_enabled {
+        return CheckOutcome::Skipped("msr-enabled isn't set".into());
+    }
+
+    let entries = match std::fs::read_dir(CPU_DEVICES_DIR) {
+        Ok(entries) => entries,
+        Err(e) => {
+            return CheckOutcome::Failed(format!(
+                "failed to read {CPU_DEVICES_DIR}, is the msr module loaded? {e}"
+            ))
+        }
+    };
+
+    let mut msr_files_count = 0;
+    for entry in entries.flatten() {
+        let path = entry.path().join("msr");
+        if !path.exists() {
+            continue;
+        }
+        msr_files_count += 1;
+        if let Err(e) = std::fs::OpenOptions::new().write(true).open(&path) {
+            return CheckOutcome::Failed(format!("{} isn't writable: {e}", path.display()));
+        }
+    }
+
+    if msr_files_count == 0 {
+        return CheckOutcome::Failed(format!(
+            "no MSR files found in {CPU_DEVICES_DIR}, is the msr module loaded?"
+        ));
+    }
+    CheckOutcome::Passed(format!("{msr_files_count} MSR files are writable"))
+}
+
+fn check_cpu_features(config: &CCPConfig) -> CheckOutcome {
+    let required_features = required_cpu_features(config.optimizations.randomx_flags);
+    if required_features.is_empty() {
+        return CheckOutcome::Skipped("no specific CPU features are requested".into());
+    }
+
+    let unsupported_features = required_features
+        .iter()
+        .copied()
+        .filter(|feature| !is_cpu_feature_supported(feature))
+        .collect::<Vec<_>>();
+    if !unsupported_features.is_empty() {
+        return CheckOutcome::Failed(format!(
+            "the CPU doesn't support {}",
+            unsupported_features.join(", ")
+        ));
+    }
+    CheckOutcome::Passed(format!("{} supported", required_features.join(", ")))
+}
+
+fn required_cpu_features(flags: RandomXFlags) -> Vec<&'static str> {
+    let mut required_features = vec![];
+    if flags.contains(RandomXFlags::HARD_AES) {
+        required_features.push("aes");
+    }
+    // both argon2 flags let RandomX choose the best supported implementation
+    if !flags.contains(RandomXFlags::FLAG_ARGON2) {
+        if flags.contains(RandomXFlags::FLAG_ARGON2_AVX2) {
+            required_features.push("avx2");
+        }
+        if flags.contains(RandomXFlags::FLAG_ARGON2_SSSE3) {
+            required_features.push("ssse3");
+        }
+    }
+    required_features
+}
+
+#[cfg(target_arch = "x86_64")]
+fn is_cpu_feature_supported(feature: &str) -> bool {
+    match feature {
+        "aes" => std::arch::is_x86_feature_detected!("aes"),
+        "avx2" => std::arch::is_x86_feature_detected!("avx2"),
+        "ssse3" => std::arch::is_x86_feature_detected!("ssse3"),
+        _ => false,
+    }
+}
+
+#[cfg(not(target_arch = "x86_64"))]
+fn is_cpu_feature_supported(_feature: &str) -> bool {
+    false
+}
+
+fn check_rpc_endpoint(config: &CCPConfig) -> CheckOutcome {
+    match &config.rpc_endpoint.transport {
+        RpcTransport::HttpAndWs { host, port }
+        | RpcTransport::Http { host, port }
+        | RpcTransport::Ws { host, port } => check_port(host, *port),
+        RpcTransport::Unix { path, .. } => match path.parent().map(dir_or_current) {
+            Some(dir) => match check_writable(dir) {
+                Ok(()) => CheckOutcome::Passed(format!("{} could be created", path.display())),
+                Err(e) => CheckOutcome::Failed(format!("{e:#}")),
+            },
+            None => CheckOutcome::Failed(format!("{} isn't a file path", path.display())),
+        },
+        RpcTransport::Stdio => CheckOutcome::Skipped("the stdio transport is used".into()),
+    }
+}
+
+fn check_prometheus_endpoint(config: &CCPConfig) -> CheckOutcome {
+    match &config.prometheus_endpoint {
+        Some(endpoint) => check_port(&endpoint.host, endpoint.port),
+        None => CheckOutcome::Skipped("it isn't configured".into()),
+    }
+}
+
+fn check_port(host: &str, port: u16) -> CheckOutcome {
+    match std::net::TcpListener::bind((host, port)) {
+        Ok(_) => CheckOutcome::Passed(format!("{host}:{port} is free")),
+        Err(e) => CheckOutcome::Failed(format!("failed to bind {host}:{port}: {e}")),
+    }
+}
+
+fn check_state_dir(state_dir: &Path) -> CheckOutcome {
+    if !state_dir.exists() {
+        // it's created on start, so the closest existing parent should be writable
+        let parent = state_dir
+            .ancestors()
+            .map(dir_or_current)
+            .find(|path| path.exists());
+        return match parent.map(check_writable) {
+            Some(Ok(())) => CheckOutcome::Passed(format!(
+                "{} doesn't exist, but could be created",
+                state_dir.display()
+            )),
+            Some(Err(e)) => CheckOutcome::Failed(format!("{e:#}")),
+            None => CheckOutcome::Failed(format!("{} can't be created", state_dir.display())),
+        };
+    }
+
+    match check_writable(state_dir) {
+        Ok(()) => CheckOutcome::Passed(format!("{} is writable", state_dir.display())),
+        Err(e) => CheckOutcome::Failed(format!("{e:#}")),
+    }
+}
+
+/// Maps the empty parent of a relative path to the current dir.
+fn dir_or_current(dir: &Path) -> &Path {
+    if dir.as_os_str().is_empty() {
+        Path::new(".")
+    } else {
+        dir
+    }
+}
+
+/// Unlike checking permissions, it takes the current user and mount options into account.
+fn check_writable(dir: &Path) -> eyre::Result<()> {
+    use eyre::WrapErr as _;
+
+    super::check_writable_dir(dir)?;
+    let probe_path = dir.join(".ccp-check");
+    std::fs::write(&probe_path, b"")
+        .wrap_err_with(|| format!("{} is not writable", dir.display()))?;
+    std::fs::remove_file(&probe_path)
+        .wrap_err_with(|| format!("failed to remove {}", probe_path.display()))
+}
+
+impl fmt::Display for CheckOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CheckOutcome::Passed(details) => write!(f, "ok, {details}"),
+            CheckOutcome::Skipped(details) => write!(f, "skipped, {details}"),
+            CheckOutcome::Failed(details) => write!(f, "failed, {details}"),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn config_warnings_are_listed() {
+        let dir = tempfile::tempdir().unwrap();
+        let config_path = dir.path().join("Config.toml");
+        let config = "[rpc-endpoint]\nhost = \"127.0.0.1\"\nport = 9383\n\n[workers]\nhashes = 1\n";
+        std::fs::write(&config_path, config).unwrap();
+
+        let loaded_config = ccp_config::ConfigLoader::new(config_path)
+            .with_env_vars([("CCP_TOKIO__THREADS".to_string(), "2".to_string())])
+            .with_unknown_keys_allowed(true)
+            .load()
+            .unwrap();
+
+        assert_eq!(
+            config_warnings(&loaded_config),
+            vec![
+                "unknown config key workers.hashes is ignored",
+                "env var CCP_TOKIO__THREADS doesn't match any config key and is ignored",
+            ]
+        );
+    }
+
+    #[test]
+    fn meminfo_values_are_parsed() {
+        let meminfo = "\
+MemTotal:       32617268 kB
+HugePages_Total:    1280
+HugePages_Free:     1024
+Hugepagesize:       2048 kB
+";
+        assert_eq!(meminfo_value(meminfo, "HugePages_Free"), Some(1024));
+        assert_eq!(meminfo_value(meminfo, "Hugepagesize"), Some(2048));
+        assert_eq!(meminfo_value(meminfo, "HugePages"), None);
+        assert_eq!(meminfo_value("Hugepagesize: n/a", "Hugepagesize"), None);
+    }
+
+    #[test]
+    fn existing_state_dir_is_checked() {
+        let dir = tempfile::tempdir().unwrap();
+        let outcome = check_state_dir(dir.path());
+        assert!(matches!(outcome, CheckOutcome::Passed(_)), "{outcome}");
+    }
+
+    #[test]
+    fn missing_state_dir_is_checked_by_ancestor() {
+        let dir = tempfile::tempdir().unwrap();
+        let outcome = check_state_dir(&dir.path().join("state").join("proofs"));
+        assert!(matches!(outcome, CheckOutcome::Passed(_)), "{outcome}");
+    }
+
+    #[test]
+    fn relative_state_dir_is_checked_in_current_dir() {
+        let outcome = check_state_dir(Path::new("missing-ccp-state"));
+        assert!(matches!(outcome, CheckOutcome::Passed(_)), "{outcome}");
+    }
+
+    #[test]
+    fn cpu_features_are_required_by_flags() {
+        assert!(required_cpu_features(RandomXFlags::empty()).is_empty());
+        assert_eq!(required_cpu_features(RandomXFlags::HARD_AES), vec!["aes"]);
+        assert_eq!(
+            required_cpu_features(RandomXFlags::HARD_AES | RandomXFlags::FLAG_ARGON2_AVX2),
+            vec!["aes", "avx2"]
+        );
+        assert_eq!(
+            required_cpu_features(RandomXFlags::FLAG_ARGON2_SSSE3),
+            vec!["ssse3"]
+        );
+        // both argon2 flags together let RandomX pick a supported implementation
+        assert_eq!(
+            RandomXFlags::FLAG_ARGON2,
+            RandomXFlags::FLAG_ARGON2_AVX2 | RandomXFlags::FLAG_ARGON2_SSSE3
+        );
+        assert!(required_cpu_features(RandomXFlags::FLAG_ARGON2).is_empty());
+    }
+}
@@ -26,52 +26,161 @@
     unreachable_patterns
 )]
 
+mod check;
+mod verify;
+
 use std::cell::Cell;
 use std::path::Path;
+use std::path::PathBuf;
 use std::sync::Arc;
 
+use ccp::config_reload::ConfigReloader;
 use ccp::cpuids_handle::CpuIdsHandle;
 use clap::Parser;
+use clap::Subcommand;
 use eyre::WrapErr as _;
 use tokio::sync::RwLock;
 use tracing_subscriber::filter::Directive;
 use tracing_subscriber::EnvFilter;
 
 use ccp::CCProver;
-use ccp_config::load_config;
 use ccp_config::CCPConfig;
+use ccp_config::ConfigLoader;
+use ccp_config::ConfigOverride;
+use ccp_config::RpcTransport;
 use ccp_rpc_server::BackgroundFacade;
 use ccp_rpc_server::CCPRcpHttpServer;
+use ccp_rpc_server::LogFilterControl;
+use ccp_rpc_server::RpcAuth;
+use ccp_rpc_server::RpcAuthScheme;
+use ccp_rpc_server::ServerHandle;
 
 const CCP_LOG_ENV_VAR: &str = "CCP_LOG";
 
 #[derive(Parser, Debug)]
 #[clap(
-    about = "Run CCP server with a CCP TOML config.  You may override logging settings with `CCP_LOG` env var."
+    about = "Run CCP server with a CCP TOML config.  You may override logging settings with `CCP_LOG` env var, \
+and config values with env vars like `CCP_RPC_ENDPOINT__PORT=9383`.",
+    args_conflicts_with_subcommands = true,
+    subcommand_negates_reqs = true
 )]
 struct Args {
-    #[arg(help = "CCP config file")]
-    config_path: String,
+    #[arg(help = "CCP config file", required = true)]
+    config_path: Option<String>,
+
+    #[arg(
+        long = "set",
+        value_name = "KEY=VALUE",
+        help = "Override a config value, e.g. `--set rpc-endpoint.port=9383`; takes precedence over env vars"
+    )]
+    overrides: Vec<ConfigOverride>,
+
+    #[arg(
+        long,
+        help = "Print the resolved config with the origin of each value and exit"
+    )]
+    print_config: bool,
+
+    #[arg(long, help = "Warn about unknown config keys instead of failing")]
+    allow_unknown_keys: bool,
+
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+enum Command {
+    #[command(about = "Verify proofs offline, without running the prover")]
+    Verify {
+        #[arg(help = "A proof JSON file or a proof directory, e.g. <state-dir>/cc_proofs")]
+        proofs_path: PathBuf,
+    },
+    #[command(about = "Check that the host is ready to run the prover with a CCP TOML config")]
+    Check {
+        #[arg(help = "CCP config file")]
+        config_path: String,
+
+        #[arg(
+            long = "set",
+            value_name = "KEY=VALUE",
+            help = "Override a config value, e.g. `--set rpc-endpoint.port=9383`"
+        )]
+        overrides: Vec<ConfigOverride>,
+
+        #[arg(long, help = "Warn about unknown config keys instead of failing")]
+        allow_unknown_keys: bool,
+    },
+    #[command(about = "Print a commented config with default values")]
+    PrintDefaultConfig,
+    #[command(about = "Print a JSON schema of the config")]
+    PrintConfigSchema,
 }
 
 fn main() -> eyre::Result<()> {
     let args = Args::parse();
-    let config = load_config(args.config_path.as_str())?;
+    match args.command {
+        Some(Command::Verify { proofs_path }) => return verify::verify_proofs(&proofs_path),
+        Some(Command::Check {
+            config_path,
+            overrides,
+            allow_unknown_keys,
+        }) => {
+            let loaded_config = ConfigLoader::new(config_path)
+                .with_env_vars(std::env::vars())
+                .with_overrides(overrides)
+                .with_unknown_keys_allowed(allow_unknown_keys)
+                .load()?;
+            return check::run_checks(&loaded_config);
+        }
+        Some(Command::PrintDefaultConfig) => {
+            print!("{}", ccp_config::default_config_toml());
+            return Ok(());
+        }
+        Some(Command::PrintConfigSchema) => {
+            println!("{}", ccp_config::config_json_schema());
+            return Ok(());
+        }
+        None => {}
+    }
+
+    // clap makes sure the config path is present if there is no subcommand
+    let config_path = args.config_path.unwrap_or_default();
+    let config_loader = ConfigLoader::new(config_path)
+        .with_env_vars(std::env::vars())
+        .with_overrides(args.overrides)
+        .with_unknown_keys_allowed(args.allow_unknown_keys);
+    let loaded_config = config_loader.load()?;
+    if args.print_config {
+        print!("{loaded_config}");
+        return Ok(());
+    }
+    let unknown_keys = loaded_config.unknown_keys;
+    let ignored_env_vars = loaded_config.ignored_env_vars;
+    let config = loaded_config.config;
 
     let filter = EnvFilter::builder()
         .with_env_var(CCP_LOG_ENV_VAR)
         .with_default_directive(Directive::from(config.logs.log_level))
         .from_env_lossy();
-    let subscriber = tracing_subscriber::fmt()
+    let subscriber_builder = tracing_subscriber::fmt()
         .with_env_filter(filter)
         .with_writer(std::io::stderr)
         .with_thread_ids(true)
-        .finish();
+        .with_filter_reloading();
+    let log_filter = LogFilterControl::new(subscriber_builder.reload_handle())?;
+    let subscriber = subscriber_builder.finish();
 
     tracing::subscriber::set_global_default(subscriber)
         .wrap_err("setting global tracing subscriber failed")?;
     tracing_log::LogTracer::init()?;
 
+    for key in unknown_keys {
+        tracing::warn!("unknown config key {key} is ignored");
+    }
+    for var in ignored_env_vars {
+        tracing::warn!("env var {var} doesn't match any config key and is ignored");
+    }
+
     if !config.state_dir.exists() {
         std::fs::create_dir_all(&config.state_dir)?
     }
@@ -84,7 +193,12 @@ fn main() -> eyre::Result<()> {
     let tokio_core_ids_state_async = CpuIdsHandle::new(tokio_cores);
     let runtime = build_tokio_runtime(&config, &tokio_core_ids_state_async)?;
 
-    runtime.block_on(async_main(config, tokio_core_ids_state_async))
+    runtime.block_on(async_main(
+        config,
+        config_loader,
+        tokio_core_ids_state_async,
+        log_filter,
+    ))
 }
 
 fn build_tokio_runtime(
@@ -140,8 +254,19 @@ fn build_tokio_runtime(
     builder.build().wrap_err("failed to build tokio runtime")
 }
 
-async fn async_main(config: CCPConfig, tokio_core_ids_state: CpuIdsHandle) -> eyre::Result<()> {
-    let rpc_bind_address = (config.rpc_endpoint.host.clone(), config.rpc_endpoint.port);
+async fn async_main(
+    config: CCPConfig,
+    config_loader: ConfigLoader,
+    tokio_core_ids_state: CpuIdsHandle,
+    log_filter: LogFilterControl,
+) -> eyre::Result<()> {
+    let rpc_transport = config.rpc_endpoint.transport.clone();
+    let rpc_auth = config
+        .rpc_endpoint
+        .auth
+        .as_ref()
+        .map(load_rpc_auth)
+        .transpose()?;
     let facade_queue_size = config.rpc_endpoint.facade_queue_size;
 
     tracing::info!("Creating prover from a saved state");
@@ -149,16 +274,23 @@ async fn async_main(config: CCPConfig, tokio_core_ids_state: CpuIdsHandle) -> ey
         .await
         .map_err(|e| eyre::eyre!(e.to_string()))?;
 
-    tracing::info!(
-        "starting RPC endpoint on {}:{}",
-        rpc_bind_address.0,
-        rpc_bind_address.1
-    );
+    let commitment_tracker = prover.commitment_tracker();
+    let hashing_gate = prover.hashing_gate();
+    let proofs = prover.proofs();
+    let config_reloader = prover.config_reloader();
     let prover = Arc::new(RwLock::new(prover));
-    let rpc_endpoint =
-        CCPRcpHttpServer::new(BackgroundFacade::new(prover.clone(), facade_queue_size));
-    let server_handle = rpc_endpoint
-        .run_server(rpc_bind_address)
+    let mut rpc_endpoint = CCPRcpHttpServer::new(BackgroundFacade::new(
+        prover.clone(),
+        commitment_tracker,
+        hashing_gate,
+        proofs,
+        facade_queue_size,
+    ))
+    .with_log_filter_control(log_filter.clone());
+    if let Some(rpc_auth) = rpc_auth {
+        rpc_endpoint = rpc_endpoint.with_auth(rpc_auth);
+    }
+    let server_handle = run_rpc_endpoint(rpc_endpoint, rpc_transport)
         .await
         .wrap_err("starting an RPC endpoint failed")?;
 
@@ -166,14 +298,27 @@ async fn async_main(config: CCPConfig, tokio_core_ids_state: CpuIdsHandle) -> ey
     use tokio::signal::unix as signal;
     let mut sig_int = signal::signal(signal::SignalKind::interrupt())?;
     let mut sig_term = signal::signal(signal::SignalKind::terminate())?;
+    let mut sig_hup = signal::signal(signal::SignalKind::hangup())?;
 
-    // wait for interruption
-    select! {
-        _ = sig_int.recv() => {
-            tracing::info!("Iterrupted, exiting...");
-        }
-        _ = sig_term.recv() => {
-            tracing::info!("Terminated, exiting...");
+    // wait for interruption, reloading the config on SIGHUP
+    loop {
+        select! {
+            _ = sig_hup.recv() => {
+                reload_config(&config_loader, &config_reloader, &log_filter).await;
+            }
+            _ = sig_int.recv() => {
+                tracing::info!("Iterrupted, exiting...");
+                break;
+            }
+            _ = sig_term.recv() => {
+                tracing::info!("Terminated, exiting...");
+                break;
+            }
+            // e.g. stdin of the stdio transport is closed
+            _ = server_handle.clone().stopped() => {
+                tracing::info!("RPC endpoint is stopped, exiting...");
+                break;
+            }
         }
     }
 
@@ -195,6 +340,95 @@ async fn async_main(config: CCPConfig, tokio_core_ids_state: CpuIdsHandle) -> ey
     Ok(())
 }
 
+/// Applies config changes, which don't need a restart, the log level is applied
+/// unless it's overridden by the env var.
+async fn reload_config(
+    config_loader: &ConfigLoader,
+    config_reloader: &ConfigReloader,
+    log_filter: &LogFilterControl,
+) {
+    tracing::info!("Reloading the config");
+    let config = match config_loader.load() {
+        Ok(loaded_config) => {
+            for key in loaded_config.unknown_keys {
+                tracing::warn!("unknown config key {key} is ignored");
+            }
+            for var in loaded_config.ignored_env_vars {
+                tracing::warn!("env var {var} doesn't match any config key and is ignored");
+            }
+            loaded_config.config
+        }
+        Err(e) => {
+            tracing::error!("failed to reload the config, the current one is kept: {e:#}");
+            return;
+        }
+    };
+
+    if std::env::var_os(CCP_LOG_ENV_VAR).is_none() {
+        let directive = Directive::from(config.logs.log_level).to_string();
+        if let Err(e) = log_filter.set_configured(&directive) {
+            tracing::error!("failed to apply the reloaded log level: {e}");
+        }
+    }
+
+    let report = config_reloader.reload(&config).await;
+    for key in report.applied {
+        tracing::info!("{key} is reloaded");
+    }
+    for key in report.needs_restart {
+        tracing::warn!("{key} is changed, but it is applied only after a restart");
+    }
+    for (key, error) in report.failed {
+        tracing::error!("failed to reload {key}, the previous value is kept: {error}");
+    }
+}
+
+fn load_rpc_auth(config: &ccp_config::RpcAuth) -> eyre::Result<RpcAuth> {
+    let scheme = match config.scheme {
+        ccp_config::RpcAuthScheme::Bearer => RpcAuthScheme::Bearer,
+        ccp_config::RpcAuthScheme::Hmac => RpcAuthScheme::Hmac,
+    };
+    tracing::info!("RPC endpoint authentication is enabled with the {scheme:?} scheme");
+
+    RpcAuth::from_files(
+        scheme,
+        &config.secret_path,
+        config.read_only_secret_path.as_deref(),
+    )
+    .wrap_err("failed to read RPC endpoint secrets")
+}
+
+async fn run_rpc_endpoint(
+    rpc_endpoint: CCPRcpHttpServer<BackgroundFacade<CCProver>>,
+    transport: RpcTransport,
+) -> std::io::Result<ServerHandle> {
+    match transport {
+        RpcTransport::HttpAndWs { host, port } => {
+            tracing::info!("starting HTTP and WebSocket RPC endpoint on {host}:{port}");
+            rpc_endpoint.run_server((host, port)).await
+        }
+        RpcTransport::Http { host, port } => {
+            tracing::info!("starting HTTP RPC endpoint on {host}:{port}");
+            rpc_endpoint.run_http_server((host, port)).await
+        }
+        RpcTransport::Ws { host, port } => {
+            tracing::info!("starting WebSocket RPC endpoint on {host}:{port}");
+            rpc_endpoint.run_ws_server((host, port)).await
+        }
+        RpcTransport::Unix { path, permissions } => {
+            tracing::info!(
+                "starting RPC endpoint on unix socket {} with permissions {permissions:o}",
+                path.display()
+            );
+            rpc_endpoint.run_unix_server(path, permissions).await
+        }
+        RpcTransport::Stdio => {
+            tracing::info!("starting RPC endpoint on stdio");
+            Ok(rpc_endpoint.run_stdio_server())
+        }
+    }
+}
+
 // Preliminary check that is useful on early diagnostics.
 fn check_writable_dir(path: &Path) -> eyre::Result<()> {
     if !path.is_dir() {
@@ -0,0 +1,66 @@
+/*
+ * Copyright 2024 Fluence Labs Limited
+ *
+ * Licensed under the Apache License, Version 2.0 (the "License");
+ * you may not use this file except in compliance with the License.
+ * You may obtain a copy of the License at
+ *
+ *     http://www.apache.org/licenses/LICENSE-2.0
+ *
+ * Unless required by applicable law or agreed to in writing, software
+ * distributed under the License is distributed on an "AS IS" BASIS,
+ * WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+ * See the License for the specific language governing permissions and
+ * limitations under the License.
+ */
+
+use std::path::Path;
+
+use ccp_verifier::load_proofs;
+use ccp_verifier::ProofVerifier;
+
+/// Verifies either a single proof file or a whole proof directory and prints
+/// a verdict for every proof; fails if at least one proof isn't valid.
+pub(crate) fn verify_proofs(proofs_path: &Path) -> eyre::Result<()> {
+    let loaded_proofs = load_proofs(proofs_path)?;
+    if loaded_proofs.is_empty() {
+        println!("no proofs found in {}", proofs_path.display());
+        return Ok(());
+    }
+
+    let proofs = loaded_proofs
+        .iter()
+        .filter_map(|(_, proof)| proof.as_ref().ok().copied())
+        .collect::<Vec<_>>();
+    let mut verdicts = ProofVerifier::default().verify_all(&proofs).into_iter();
+
+    let mut failed_count = 0;
+    for (path, proof) in &loaded_proofs {
+        let verdict = match proof {
+            // verdicts are in the same order as successfully loaded proofs
+            Ok(_) => verdicts
+                .next()
+                .expect("each loaded proof has a verdict")
+                .map_err(|e| e.to_string()),
+            Err(error) => Err(error.to_string()),
+        };
+
+        match verdict {
+            Ok(()) => println!("{}: ok", path.display()),
+            Err(error) => {
+                failed_count += 1;
+                println!("{}: failed, {error}", path.display());
+            }
+        }
+    }
+
+    if failed_count != 0 {
+        eyre::bail!(
+            "{failed_count} of {} proofs failed verification",
+            loaded_proofs.len()
+        );
+    }
+
+    println!("all {} proofs are valid", loaded_proofs.len());
+    Ok(())
+}